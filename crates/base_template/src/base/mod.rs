@@ -1,3 +0,0 @@
-pub mod element_defs;
-pub mod parser;
-pub mod stream;
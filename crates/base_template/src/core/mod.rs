@@ -1,2 +0,0 @@
-mod element_defs;
-pub mod parser;
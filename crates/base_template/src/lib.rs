@@ -1,2 +0,0 @@
-pub mod base;
-pub mod core;
@@ -0,0 +1,433 @@
+use iron_ebmlem::base::raw::{RawElement, RawElementReader};
+use iron_ebmlem::dynamic::{DynParser, DynValue};
+use iron_ebmlem::parser_gen::Builder;
+
+use clap::{ArgEnum, Parser};
+
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+const BINARY_PREVIEW_LEN: usize = 16;
+
+/// Prints the element tree of an EBML document.
+#[derive(Parser, Debug)]
+#[clap(name = "ebml-dump", version)]
+struct Args {
+    /// The EBML document to dump
+    file: PathBuf,
+    /// An EBML schema describing the document; without one, only top-level elements are listed
+    #[clap(short, long)]
+    schema: Option<PathBuf>,
+    /// Only print elements up to this depth (top-level elements have depth 0)
+    #[clap(long)]
+    max_depth: Option<usize>,
+    /// Only print elements at or below this path, e.g. "\Files\File"
+    #[clap(long)]
+    path: Option<String>,
+    /// The output format
+    #[clap(long, arg_enum, default_value = "text")]
+    format: Format,
+}
+
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+}
+
+struct Record {
+    element: RawElement,
+    name: String,
+    path: String,
+    value: Option<DynValue>, // `None` -> master element, or no schema
+}
+
+struct Filter {
+    max_depth: Option<usize>,
+    path: Option<String>,
+}
+
+impl Filter {
+    fn shows(&self, element: &RawElement, path: &str) -> bool {
+        self.max_depth.map_or(true, |depth| element.depth <= depth)
+            && self
+                .path
+                .as_ref()
+                .map_or(true, |filter| is_path_within(path, filter))
+    }
+
+    fn descends(&self, element: &RawElement, path: &str) -> bool {
+        self.max_depth.map_or(true, |depth| element.depth < depth)
+            && self.path.as_ref().map_or(true, |filter| {
+                is_path_within(path, filter) || is_path_within(filter, path)
+            })
+    }
+}
+
+fn is_path_within(path: &str, ancestor: &str) -> bool {
+    path.strip_prefix(ancestor)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('\\'))
+}
+
+fn main() {
+    if let Err(error) = run(Args::parse()) {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let file = std::io::BufReader::new(std::fs::File::open(&args.file)?);
+    let filter = Filter {
+        max_depth: args.max_depth,
+        path: args.path,
+    };
+    let mut printer = Printer::new(std::io::stdout().lock(), args.format)?;
+
+    match args.schema {
+        Some(schema_path) => {
+            let schema_file = std::io::BufReader::new(std::fs::File::open(schema_path)?);
+            let parser = Builder::new(schema_file)?.build_dynamic()?;
+            dump_with_schema(&parser, file, &filter, &mut printer)?;
+        }
+        None => dump_raw(file, &filter, &mut printer)?,
+    }
+
+    printer.finish()?;
+    Ok(())
+}
+
+fn dump_with_schema<R: BufRead, W: Write>(
+    parser: &DynParser,
+    reader: R,
+    filter: &Filter,
+    printer: &mut Printer<W>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut reader = parser.reader(reader);
+
+    while let Some(entry) = reader.next() {
+        let entry = entry?;
+        if filter.shows(&entry.element, &entry.path) {
+            printer.print(&Record {
+                element: entry.element,
+                name: entry.name.clone(),
+                path: entry.path.clone(),
+                value: entry.value.clone(),
+            })?;
+        }
+        // unknown-sized elements cannot be skipped over, only read through
+        if entry.value.is_none()
+            && entry.element.data_len.is_some()
+            && !filter.descends(&entry.element, &entry.path)
+        {
+            reader.skip_children()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn dump_raw<R: BufRead, W: Write>(
+    reader: R,
+    filter: &Filter,
+    printer: &mut Printer<W>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for element in RawElementReader::new(reader) {
+        let element = element?;
+        let name = format!("{:#X}", element.id);
+        let path = format!("\\{}", name);
+
+        if filter.shows(&element, &path) {
+            printer.print(&Record {
+                element,
+                name,
+                path,
+                value: None,
+            })?;
+        }
+        if element.data_len.is_none() {
+            // the extent of an unknown-sized element cannot be known without a schema
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+struct Printer<W> {
+    writer: W,
+    format: Format,
+    is_first: bool,
+}
+
+impl<W: Write> Printer<W> {
+    fn new(mut writer: W, format: Format) -> std::io::Result<Self> {
+        if format == Format::Json {
+            writer.write_all(b"[")?;
+        }
+
+        Ok(Self {
+            writer,
+            format,
+            is_first: true,
+        })
+    }
+
+    fn print(&mut self, record: &Record) -> std::io::Result<()> {
+        let element = &record.element;
+
+        match self.format {
+            Format::Text => {
+                write!(
+                    self.writer,
+                    "{indent}+ {name} [{id:#X}] @{offset} header {header_len}, size {data_len}",
+                    indent = "| ".repeat(element.depth),
+                    name = record.name,
+                    id = element.id,
+                    offset = element.offset,
+                    header_len = element.header_len,
+                    data_len = element
+                        .data_len
+                        .map_or_else(|| "unknown".to_string(), |len| len.to_string()),
+                )?;
+                if let Some(value) = record.value.as_ref() {
+                    write!(self.writer, ": {}", format_value(value))?;
+                }
+                writeln!(self.writer)
+            }
+            Format::Json => {
+                let object = serde_json::json!({
+                    "name": record.name,
+                    "id": element.id,
+                    "path": record.path,
+                    "offset": element.offset,
+                    "header_len": element.header_len,
+                    "data_len": element.data_len,
+                    "depth": element.depth,
+                    "value": record.value.as_ref().map(json_value),
+                });
+                if !self.is_first {
+                    self.writer.write_all(b",")?;
+                }
+                self.is_first = false;
+                write!(self.writer, "\n  {}", object)
+            }
+        }
+    }
+
+    fn finish(mut self) -> std::io::Result<()> {
+        if self.format == Format::Json {
+            self.writer.write_all(b"\n]\n")?;
+        }
+        self.writer.flush()
+    }
+}
+
+fn format_value(value: &DynValue) -> String {
+    match value {
+        DynValue::Master(_) => String::new(),
+        DynValue::UInt(x) => x.to_string(),
+        DynValue::Int(x) => x.to_string(),
+        DynValue::Float(x) => x.to_string(),
+        DynValue::Date(x) => x.to_string(),
+        DynValue::String(x) | DynValue::Utf8(x) => format!("{:?}", x),
+        DynValue::Binary(x) => format_binary(x),
+        DynValue::Unknown => "(unknown element)".to_string(),
+    }
+}
+
+fn json_value(value: &DynValue) -> serde_json::Value {
+    match value {
+        DynValue::Master(_) | DynValue::Unknown => serde_json::Value::Null,
+        DynValue::UInt(x) => (*x).into(),
+        DynValue::Int(x) => (*x).into(),
+        DynValue::Float(x) => (*x).into(),
+        DynValue::String(x) | DynValue::Utf8(x) => x.as_str().into(),
+        DynValue::Date(_) | DynValue::Binary(_) => format_value(value).into(),
+    }
+}
+
+fn format_binary(data: &[u8]) -> String {
+    let preview = hex_bytes(data.iter().take(BINARY_PREVIEW_LEN));
+    if data.len() > BINARY_PREVIEW_LEN {
+        format!("[{} ...] ({} bytes)", preview, data.len())
+    } else {
+        format!("[{}]", preview)
+    }
+}
+
+fn hex_bytes<'a, I: Iterator<Item = &'a u8>>(bytes: I) -> String {
+    bytes
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(&[], "[]")]
+    #[case(&[0x01, 0xAB], "[01 AB]")]
+    #[case(&[0xFF; 17], "[FF FF FF FF FF FF FF FF FF FF FF FF FF FF FF FF ...] (17 bytes)")]
+    fn test_format_binary(#[case] data: &[u8], #[case] expt_result: &str) {
+        assert_eq!(format_binary(data), expt_result);
+    }
+
+    #[rstest]
+    #[case(r"\Files\File", r"\Files", true)]
+    #[case(r"\Files", r"\Files", true)]
+    #[case(r"\FilesX", r"\Files", false)]
+    #[case(r"\Files", r"\Files\File", false)]
+    fn test_is_path_within(#[case] path: &str, #[case] ancestor: &str, #[case] expt_result: bool) {
+        assert_eq!(is_path_within(path, ancestor), expt_result);
+    }
+
+    #[test]
+    fn dump_text() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="files-in-ebml-demo" version="1">
+              <element name="Files" path="\Files" id="0x1946696C" type="master"/>
+              <element name="File" path="\Files\File" id="0x6146" type="master"/>
+              <element name="FileName" path="\Files\File\FileName" id="0x614E" type="utf-8"/>
+              <element name="Data" path="\Files\File\Data" id="0x4664" type="binary"/>
+            </EBMLSchema>
+        "#;
+        let stream = [
+            0x19, 0x46, 0x69, 0x6C, 0x8C, // Files element header
+            0x61, 0x46, 0x89, // File element header
+            0x61, 0x4E, 0x82, 0x61, 0x62, // FileName element = "ab"
+            0x46, 0x64, 0x81, 0x01, // Data element
+        ];
+        let parser = Builder::new(schema.as_bytes())
+            .unwrap()
+            .build_dynamic()
+            .unwrap();
+        let filter = Filter {
+            max_depth: None,
+            path: Some(r"\Files\File\FileName".to_string()),
+        };
+
+        let mut output = Vec::new();
+        let mut printer = Printer::new(&mut output, Format::Text).unwrap();
+        dump_with_schema(&parser, &stream[..], &filter, &mut printer).unwrap();
+        printer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "| | + FileName [0x614E] @8 header 3, size 2: \"ab\"\n"
+        );
+    }
+
+    #[test]
+    fn dump_json() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="files-in-ebml-demo" version="1">
+              <element name="Files" path="\Files" id="0x1946696C" type="master"/>
+              <element name="File" path="\Files\File" id="0x6146" type="master"/>
+              <element name="FileName" path="\Files\File\FileName" id="0x614E" type="utf-8"/>
+              <element name="Data" path="\Files\File\Data" id="0x4664" type="binary"/>
+            </EBMLSchema>
+        "#;
+        let stream = [
+            0x19, 0x46, 0x69, 0x6C, 0x8C, // Files element header
+            0x61, 0x46, 0x89, // File element header
+            0x61, 0x4E, 0x82, 0x61, 0x62, // FileName element = "ab"
+            0x46, 0x64, 0x81, 0x01, // Data element
+        ];
+        let parser = Builder::new(schema.as_bytes())
+            .unwrap()
+            .build_dynamic()
+            .unwrap();
+        let filter = Filter {
+            max_depth: None,
+            path: None,
+        };
+
+        let mut output = Vec::new();
+        let mut printer = Printer::new(&mut output, Format::Json).unwrap();
+        dump_with_schema(&parser, &stream[..], &filter, &mut printer).unwrap();
+        printer.finish().unwrap();
+
+        let output: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(
+            output,
+            serde_json::json!([
+                {
+                    "name": "Files",
+                    "id": 0x1946696C,
+                    "path": r"\Files",
+                    "offset": 0,
+                    "header_len": 5,
+                    "data_len": 12,
+                    "depth": 0,
+                    "value": null,
+                },
+                {
+                    "name": "File",
+                    "id": 0x6146,
+                    "path": r"\Files\File",
+                    "offset": 5,
+                    "header_len": 3,
+                    "data_len": 9,
+                    "depth": 1,
+                    "value": null,
+                },
+                {
+                    "name": "FileName",
+                    "id": 0x614E,
+                    "path": r"\Files\File\FileName",
+                    "offset": 8,
+                    "header_len": 3,
+                    "data_len": 2,
+                    "depth": 2,
+                    "value": "ab",
+                },
+                {
+                    "name": "Data",
+                    "id": 0x4664,
+                    "path": r"\Files\File\Data",
+                    "offset": 13,
+                    "header_len": 3,
+                    "data_len": 1,
+                    "depth": 2,
+                    "value": "[01]",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn dump_unknown_id() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="files-in-ebml-demo" version="1">
+              <element name="Files" path="\Files" id="0x1946696C" type="master"/>
+            </EBMLSchema>
+        "#;
+        let stream = [
+            0x19, 0x46, 0x69, 0x6C, 0x84, // Files element header
+            0x5F, 0xFF, 0x81, 0x00, // unknown element
+        ];
+        let parser = Builder::new(schema.as_bytes())
+            .unwrap()
+            .build_dynamic()
+            .unwrap();
+        let filter = Filter {
+            max_depth: None,
+            path: None,
+        };
+
+        let mut output = Vec::new();
+        let mut printer = Printer::new(&mut output, Format::Text).unwrap();
+        dump_with_schema(&parser, &stream[..], &filter, &mut printer).unwrap();
+        printer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "+ Files [0x1946696C] @0 header 5, size 4\n\
+             | + 0x5FFF [0x5FFF] @5 header 3, size 1: (unknown element)\n"
+        );
+    }
+}
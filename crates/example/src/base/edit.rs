@@ -0,0 +1,596 @@
+use crate::base::raw::RawElement;
+use crate::base::stream::{parse, serialize};
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+// the longest possible element header: a 4-byte element ID + an 8-byte data size
+const MAX_HEADER_LEN: usize = 12;
+const VOID_ID: u32 = 0xEC;
+const CRC32_ID: u32 = 0xBF;
+
+#[derive(thiserror::Error, Debug)]
+pub enum EditError {
+    #[error("IOError: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the element header at offset {0} doesn't match the element being edited")]
+    HeaderMismatch(u64),
+    #[error("cannot edit an unknown-sized element (at offset {0})")]
+    UnknownSized(u64),
+    #[error("{0} bytes of data don't fit in the space of the element at offset {1}")]
+    DoesNotFit(u64, u64),
+    #[error("the element at offset {0} isn't within its given parent at offset {1}")]
+    NotWithinParent(u64, u64),
+}
+
+// The result of an in-place edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edited {
+    pub element: RawElement,
+    pub void: Option<RawElement>, // the Void element that fills any space left after the element
+}
+
+/**
+An editor of elements in place, within a seekable file, that never moves the bytes of any other
+element; it follows RFC 8794's "Guidelines for Updating Elements".
+
+The elements to edit are located beforehand, e.g. with a `RawElementReader`. An edited element
+keeps its total length (along with any Void element directly after it), so the sizes of its
+ancestors stay valid, but their CRC-32 elements (if any) don't; see `update_crc`.
+
+**/
+#[derive(Debug)]
+pub struct ElementEditor<F> {
+    file: F,
+}
+
+impl<F: Read + Write + Seek> ElementEditor<F> {
+    pub fn new(file: F) -> Self {
+        Self { file }
+    }
+
+    pub fn into_inner(self) -> F {
+        self.file
+    }
+
+    /**
+    Overwrites the data of a (leaf) element, where `parent_end` is the end offset of its innermost
+    sized parent (see `RawElementReader::parent_end`).
+
+    Smaller data is followed by a Void element in the freed space, or takes up a wider data size
+    VINT when the space is too small for one. Larger data takes up any spare width of the data size
+    VINT, or else a Void element directly after it within the same parent. Data that doesn't fit
+    fails without any writes.
+
+    **/
+    pub fn overwrite(
+        &mut self,
+        element: &RawElement,
+        parent_end: Option<u64>,
+        data: &[u8],
+    ) -> Result<Edited, EditError> {
+        let element_end = self.check_header(element)?;
+        let id_len = id_len(element.id);
+        let len_width = element.header_len - id_len;
+        let data_len = data.len() as u64;
+        let space = |end: u64| end - element.offset - id_len as u64;
+
+        let (len_width, void_len) = match layout(space(element_end), data_len, len_width) {
+            Some(layout) => layout,
+            None => self
+                .following_void_end(element_end, parent_end)?
+                .and_then(|end| layout(space(end), data_len, len_width))
+                .ok_or(EditError::DoesNotFit(data_len, element.offset))?,
+        };
+
+        let edited = RawElement {
+            header_len: id_len + len_width,
+            data_len: Some(data_len),
+            ..*element
+        };
+        self.file
+            .seek(SeekFrom::Start(element.offset + id_len as u64))?;
+        self.file.write_all(&len_bytes(data_len, len_width))?;
+        self.file.write_all(data)?;
+
+        let void = match void_len {
+            0 => None,
+            void_len => {
+                // the Void's data is left as is, since it's ignored anyway
+                let offset = edited.end_offset().expect("the edited element is sized");
+                let void_header = void_header(void_len);
+                self.file.write_all(&void_header)?;
+                Some(RawElement {
+                    id: VOID_ID,
+                    header_len: void_header.len(),
+                    data_len: Some(void_len - void_header.len() as u64),
+                    offset,
+                    depth: element.depth,
+                })
+            }
+        };
+        self.file.flush()?;
+
+        Ok(Edited {
+            element: edited,
+            void,
+        })
+    }
+
+    /**
+    Turns an element (of any type, along with any children) into a Void element of the same total
+    length, by rewriting only its header; the data is left as is, since it's ignored anyway.
+
+    If its parent element is given, the parent's CRC-32 element (if any) is updated to match; those
+    of further ancestors aren't.
+
+    **/
+    pub fn replace_with_void(
+        &mut self,
+        element: &RawElement,
+        parent: Option<&RawElement>,
+    ) -> Result<RawElement, EditError> {
+        let end = self.check_header(element)?;
+        if let Some(parent) = parent {
+            let parent_end = self.check_header(parent)?;
+            if element.offset < parent.data_offset() || end > parent_end {
+                return Err(EditError::NotWithinParent(element.offset, parent.offset));
+            }
+        }
+
+        // the data size VINT takes up the rest of the header (up to its widest), so that the data
+        // stays in place; any header bytes left over are taken into the Void's data
+        let len_width = (element.header_len - 1).min(8);
+        let data_len = end - element.offset - 1 - len_width as u64;
+        if !fits_len_width(data_len, len_width) {
+            return Err(EditError::DoesNotFit(data_len, element.offset));
+        }
+
+        self.file.seek(SeekFrom::Start(element.offset))?;
+        self.file.write_all(&[VOID_ID as u8])?;
+        self.file.write_all(&len_bytes(data_len, len_width))?;
+        if let Some(parent) = parent {
+            self.update_crc(parent)?;
+        }
+        self.file.flush()?;
+
+        Ok(RawElement {
+            id: VOID_ID,
+            header_len: 1 + len_width,
+            data_len: Some(data_len),
+            ..*element
+        })
+    }
+
+    /**
+    Updates the CRC-32 element of a master element, i.e. its first child if it's a CRC-32 element,
+    to match the rest of the master element's data. Returns whether there is a CRC-32 element.
+
+    **/
+    pub fn update_crc(&mut self, element: &RawElement) -> Result<bool, EditError> {
+        let end = self.check_header(element)?;
+        let crc_data_offset = match self.read_header(element.data_offset())? {
+            Some((CRC32_ID, Some(4), header_len))
+                if element.data_offset() + header_len as u64 + 4 <= end =>
+            {
+                element.data_offset() + header_len as u64
+            }
+            _ => return Ok(false),
+        };
+
+        self.file.seek(SeekFrom::Start(crc_data_offset + 4))?;
+        let mut crc = !0u32;
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut len_left = end - crc_data_offset - 4;
+        while len_left > 0 {
+            let len = len_left.min(buffer.len() as u64) as usize;
+            self.file.read_exact(&mut buffer[..len])?;
+            crc = crc32_update(crc, &buffer[..len]);
+            len_left -= len as u64;
+        }
+        self.file.seek(SeekFrom::Start(crc_data_offset))?;
+        self.file.write_all(&(!crc).to_le_bytes())?;
+        self.file.flush()?;
+
+        Ok(true)
+    }
+
+    // Checks that the file holds the given element header, returning the element's end offset
+    fn check_header(&mut self, element: &RawElement) -> Result<u64, EditError> {
+        let end = element
+            .end_offset()
+            .ok_or(EditError::UnknownSized(element.offset))?;
+        match self.read_header(element.offset)? {
+            Some((id, data_len, header_len))
+                if id == element.id
+                    && data_len == element.data_len
+                    && header_len == element.header_len =>
+            {
+                Ok(end)
+            }
+            _ => Err(EditError::HeaderMismatch(element.offset)),
+        }
+    }
+
+    // The end offset of a Void element directly after the given offset, within the same parent
+    fn following_void_end(
+        &mut self,
+        offset: u64,
+        parent_end: Option<u64>,
+    ) -> Result<Option<u64>, EditError> {
+        if matches!(parent_end, Some(parent_end) if offset >= parent_end) {
+            return Ok(None);
+        }
+        Ok(match self.read_header(offset)? {
+            Some((VOID_ID, Some(data_len), header_len)) => {
+                let end = offset + header_len as u64 + data_len;
+                Some(end).filter(|&end| !matches!(parent_end, Some(parent_end) if end > parent_end))
+            }
+            _ => None,
+        })
+    }
+
+    // Reads the element header at the given offset, if there is a valid one
+    fn read_header(&mut self, offset: u64) -> Result<Option<(u32, Option<u64>, usize)>, EditError> {
+        self.file.seek(SeekFrom::Start(offset))?;
+        let mut header = Vec::with_capacity(MAX_HEADER_LEN);
+        (&mut self.file)
+            .take(MAX_HEADER_LEN as u64)
+            .read_to_end(&mut header)?;
+
+        let header = parse::element_id(&header).and_then(|(stream, id)| {
+            let (next_stream, len) = parse::element_len(stream)?;
+            Ok((id, len, header.len() - next_stream.len()))
+        });
+        Ok(header.ok())
+    }
+}
+
+// The data size VINT width & the length of a trailing Void element that fit the data into the given
+// space (i.e., the element length minus its ID), preferring the current width, then a Void element
+fn layout(space: u64, data_len: u64, len_width: usize) -> Option<(usize, u64)> {
+    let min_width = min_len_width(data_len);
+    let rest = |width: usize| space.checked_sub(width as u64 + data_len);
+    let current = Some(len_width).filter(|&width| width >= min_width);
+
+    current
+        .filter(|&width| rest(width) == Some(0))
+        .or_else(|| {
+            current
+                .into_iter()
+                .chain(min_width..=8)
+                .find(|&width| matches!(rest(width), Some(rest) if rest >= 2))
+        })
+        .or_else(|| (min_width..=8).find(|&width| rest(width) == Some(0)))
+        .map(|width| (width, rest(width).expect("the data fits")))
+}
+
+// The byte length of an element ID, whose value includes its VINT marker
+fn id_len(id: u32) -> usize {
+    4 - id.leading_zeros() as usize / 8
+}
+
+// The narrowest data size VINT that can hold a length
+fn min_len_width(len: u64) -> usize {
+    (1..8)
+        .find(|&width| fits_len_width(len, width))
+        .unwrap_or(8)
+}
+
+// Whether a data size VINT of the given width (up to 8) can hold a length, whose VINT_DATA bits
+// can't all be 1's
+fn fits_len_width(len: u64, width: usize) -> bool {
+    len < (1 << (7 * width)) - 1
+}
+
+fn len_bytes(len: u64, width: usize) -> Vec<u8> {
+    let mut buffer = [0u8; 8];
+    serialize::element_len(&mut buffer, Some(len), Some(width))
+        .expect("the width is checked beforehand");
+    buffer[..width].to_vec()
+}
+
+// The lookup table of the (reflected) IEEE CRC-32 polynomial, as used by CRC-32 elements
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < table.len() {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+// Continues a CRC-32 (before its final inversion) over the given bytes
+fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+// The header of a Void element that is `len` bytes long in total (`len >= 2`)
+fn void_header(len: u64) -> Vec<u8> {
+    let width = (1..=8)
+        .find(|&width| min_len_width(len - 1 - width as u64) <= width)
+        .expect("a Void element fits in 2 bytes or more");
+    let mut header = vec![VOID_ID as u8];
+    header.extend(len_bytes(len - 1 - width as u64, width));
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::raw::RawElementReader;
+    use rstest::*;
+
+    use std::io::Cursor;
+
+    const STREAM: [u8; 21] = [
+        0x1A, 0x45, 0xDF, 0xA3, // EBML element ID
+        0x90, // EBML length = 16
+        0x42, 0x82, // DocType element ID
+        0x84, // DocType length = 4
+        0x77, 0x65, 0x62, 0x6D, // DocType data = "webm"
+        0xEC, // Void element ID
+        0x83, // Void length = 3
+        0x00, 0x00, 0x00, // Void data
+        0x42, 0x87, // DocTypeVersion element ID
+        0x81, // DocTypeVersion length = 1
+        0x04, // DocTypeVersion data
+    ];
+
+    // Overwrites the element with the given ID (within the EBML element) in a copy of the stream
+    fn overwrite(stream: &[u8], id: u32, data: &[u8]) -> (Vec<u8>, Result<Edited, EditError>) {
+        let mut reader = RawElementReader::new(stream);
+        reader.next().unwrap().unwrap();
+        reader.descend().unwrap();
+        let element = reader
+            .find(|element| element.as_ref().unwrap().id == id)
+            .unwrap()
+            .unwrap();
+        let parent_end = reader.parent_end();
+
+        let mut editor = ElementEditor::new(Cursor::new(stream.to_vec()));
+        let result = editor.overwrite(&element, parent_end, data);
+        (editor.into_inner().into_inner(), result)
+    }
+
+    #[test]
+    fn overwrite_same_size() {
+        let (stream, result) = overwrite(&STREAM, 0x4282, b"mkv!");
+
+        assert_eq!(&stream[8..12], b"mkv!");
+        assert_eq!(stream[12..], STREAM[12..]);
+        assert_eq!(result.unwrap().void, None);
+    }
+
+    #[test]
+    fn overwrite_shrink_void() {
+        let (stream, result) = overwrite(&STREAM, 0x4282, b"mk");
+
+        assert_eq!(stream[5..10], [0x42, 0x82, 0x82, 0x6D, 0x6B]);
+        // a Void element fills the 2 freed bytes, followed by the previous Void element
+        assert_eq!(stream[10..12], [0xEC, 0x80]);
+        assert_eq!(stream[12..], STREAM[12..]);
+
+        let result = result.unwrap();
+        assert_eq!(result.element.data_len, Some(2));
+        assert_eq!(
+            result.void.map(|void| (void.offset, void.end_offset())),
+            Some((10, Some(12)))
+        );
+    }
+
+    #[test]
+    fn overwrite_shrink_vint() {
+        // a single freed byte can't hold a Void element -> widen the data size VINT
+        let (stream, result) = overwrite(&STREAM, 0x4282, b"mkv");
+
+        assert_eq!(stream[5..12], [0x42, 0x82, 0x40, 0x03, 0x6D, 0x6B, 0x76]);
+        assert_eq!(stream[12..], STREAM[12..]);
+
+        let result = result.unwrap();
+        assert_eq!(result.element.header_len, 4);
+        assert_eq!(result.void, None);
+    }
+
+    #[test]
+    fn overwrite_grow_into_void() {
+        // the following Void element is taken up entirely...
+        let (stream, result) = overwrite(&STREAM, 0x4282, b"matroska");
+        assert_eq!(stream[5..9], [0x42, 0x82, 0x40, 0x08]);
+        assert_eq!(&stream[9..17], b"matroska");
+        assert_eq!(stream[17..], STREAM[17..]);
+        assert_eq!(result.unwrap().void, None);
+
+        // ...or shrunk
+        let (stream, result) = overwrite(&STREAM, 0x4282, b"webm!");
+        assert_eq!(&stream[8..13], b"webm!");
+        assert_eq!(stream[13..15], [0xEC, 0x82]);
+        assert_eq!(stream[15..], STREAM[15..]);
+        assert_eq!(result.unwrap().void.map(|void| void.offset), Some(13));
+    }
+
+    #[rstest]
+    #[case(0x4282, b"matroska!!")] // too large, even with the following Void element
+    #[case(0x4287, b"\x04\x00")] // the last element in its parent
+    fn overwrite_err_does_not_fit(#[case] id: u32, #[case] data: &[u8]) {
+        let (stream, result) = overwrite(&STREAM, id, data);
+
+        assert!(matches!(result, Err(EditError::DoesNotFit(..))));
+        assert_eq!(stream, STREAM);
+    }
+
+    #[test]
+    fn overwrite_grow_narrow_vint() {
+        let stream = [
+            0x42, 0x82, // DocType element ID
+            0x10, 0x00, 0x00, 0x04, // DocType length = 4 (4-byte VINT)
+            0x77, 0x65, 0x62, 0x6D, // DocType data = "webm"
+        ];
+        let element = RawElementReader::new(&stream[..]).next().unwrap().unwrap();
+        let mut editor = ElementEditor::new(Cursor::new(stream.to_vec()));
+
+        let result = editor.overwrite(&element, None, b"webm!!").unwrap();
+        assert_eq!(result.element.header_len, 4);
+        assert_eq!(
+            editor.into_inner().into_inner(),
+            [0x42, 0x82, 0x40, 0x06, 0x77, 0x65, 0x62, 0x6D, 0x21, 0x21]
+        );
+    }
+
+    #[test]
+    fn overwrite_err_header_mismatch() {
+        let element = RawElement {
+            id: 0x4282,
+            header_len: 3,
+            data_len: Some(4),
+            offset: 4,
+            depth: 1,
+        };
+        let mut editor = ElementEditor::new(Cursor::new(STREAM.to_vec()));
+
+        assert!(matches!(
+            editor.overwrite(&element, Some(21), b"mkv!"),
+            Err(EditError::HeaderMismatch(4))
+        ));
+    }
+
+    #[test]
+    fn crc32() {
+        assert_eq!(!crc32_update(!0, b"123456789"), 0xCBF43926);
+    }
+
+    #[test]
+    fn replace_with_void_leaf() {
+        let mut reader = RawElementReader::new(&STREAM[..]);
+        let parent = reader.next().unwrap().unwrap();
+        reader.descend().unwrap();
+        let element = reader.next().unwrap().unwrap();
+        let mut editor = ElementEditor::new(Cursor::new(STREAM.to_vec()));
+
+        let void = editor.replace_with_void(&element, Some(&parent)).unwrap();
+        let stream = editor.into_inner().into_inner();
+        assert_eq!(stream[5..8], [0xEC, 0x40, 0x04]);
+        assert_eq!(stream[..5], STREAM[..5]);
+        assert_eq!(stream[8..], STREAM[8..]);
+
+        let mut reader = RawElementReader::new(&stream[..]);
+        reader.next().unwrap().unwrap();
+        reader.descend().unwrap();
+        assert_eq!(reader.next().unwrap().unwrap(), void);
+    }
+
+    #[test]
+    fn replace_with_void_crc() {
+        let stream = [
+            0x1A, 0x45, 0xDF, 0xA3, // EBML element ID
+            0x91, // EBML length = 17
+            0xBF, // CRC-32 element ID
+            0x84, // CRC-32 length = 4
+            0x00, 0x00, 0x00, 0x00, // CRC-32 data (outdated)
+            0x42, 0x82, // DocType element ID
+            0x84, // DocType length = 4
+            0x77, 0x65, 0x62, 0x6D, // DocType data = "webm"
+            0x42, 0x87, // DocTypeVersion element ID
+            0x81, // DocTypeVersion length = 1
+            0x04, // DocTypeVersion data
+            0x1A, 0x45, 0xDF, 0xA3, // EBML element ID
+            0x80, // EBML length = 0
+        ];
+        let mut reader = RawElementReader::new(&stream[..]);
+        let parent = reader.next().unwrap().unwrap();
+        reader.descend().unwrap();
+        let elements = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        let mut editor = ElementEditor::new(Cursor::new(stream.to_vec()));
+
+        // the whole DocType element is covered by the CRC-32
+        editor
+            .replace_with_void(&elements[1], Some(&parent))
+            .unwrap();
+        let edited = editor.into_inner().into_inner();
+        assert_eq!(edited[11..14], [0xEC, 0x40, 0x04]);
+        assert_eq!(
+            edited[7..11],
+            (!crc32_update(!0, &edited[11..22])).to_le_bytes()
+        );
+
+        let mut editor = ElementEditor::new(Cursor::new(stream.to_vec()));
+        assert!(matches!(
+            editor.replace_with_void(&elements[2], Some(&elements[3])),
+            Err(EditError::NotWithinParent(18, 22))
+        ));
+        assert!(!editor.update_crc(&elements[3]).unwrap());
+        assert_eq!(editor.into_inner().into_inner(), stream);
+    }
+
+    #[test]
+    fn replace_with_void_wide_header() {
+        let stream = [
+            0x61, 0xA7, // AttachedFile element ID
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x03, // AttachedFile length = 3 (8 bytes)
+            0x46, 0x6E, // FileName element ID
+            0x80, // FileName length = 0
+        ];
+        let element = RawElementReader::new(&stream[..]).next().unwrap().unwrap();
+        let mut editor = ElementEditor::new(Cursor::new(stream.to_vec()));
+
+        // the 9 bytes after the Void ID only take an 8-byte VINT -> the last one becomes data
+        let void = editor.replace_with_void(&element, None).unwrap();
+        let stream = editor.into_inner().into_inner();
+        assert_eq!(
+            stream[..9],
+            [0xEC, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04]
+        );
+        assert_eq!(stream[9..], [0x03, 0x46, 0x6E, 0x80]);
+        assert_eq!((void.header_len, void.data_len), (9, Some(4)));
+
+        let mut reader = RawElementReader::new(&stream[..]);
+        assert_eq!(reader.next().unwrap().unwrap(), void);
+        assert!(reader.next().is_none());
+
+        // the spare header byte would make the Void's length the reserved "unknown" value
+        let stream = [
+            0x61, 0xA7, // AttachedFile element ID
+            0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, // AttachedFile length = 2^56 - 2
+        ];
+        let element = RawElementReader::new(&stream[..]).next().unwrap().unwrap();
+        let mut editor = ElementEditor::new(Cursor::new(stream.to_vec()));
+        assert!(matches!(
+            editor.replace_with_void(&element, None),
+            Err(EditError::DoesNotFit(0xFF_FFFF_FFFF_FFFF, 0))
+        ));
+        assert_eq!(editor.into_inner().into_inner(), stream);
+    }
+
+    #[rstest]
+    #[case(0, 1)]
+    #[case(126, 1)]
+    #[case(127, 2)]
+    #[case(16382, 2)]
+    #[case(16383, 3)]
+    fn min_len_width_cases(#[case] len: u64, #[case] expt_width: usize) {
+        assert_eq!(min_len_width(len), expt_width);
+    }
+
+    #[rstest]
+    #[case(2, vec![0xEC, 0x80])]
+    #[case(128, vec![0xEC, 0xFE])]
+    #[case(129, vec![0xEC, 0x40, 0x7E])] // 1-byte length = 127 is reserved
+    fn void_header_cases(#[case] len: u64, #[case] expt_header: Vec<u8>) {
+        assert_eq!(void_header(len), expt_header);
+    }
+}
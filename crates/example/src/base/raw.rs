@@ -0,0 +1,429 @@
+use crate::base::stream::parse;
+
+use std::io::{BufRead, Read};
+
+// the longest possible element header: a 4-byte element ID + an 8-byte data size
+const MAX_HEADER_LEN: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawElement {
+    pub id: u32,
+    pub header_len: usize,
+    pub data_len: Option<u64>, // `None` -> unknown-sized element
+    pub offset: u64,           // stream position of the first byte of the element ID
+    pub depth: usize,          // number of enclosing elements that were descended into
+}
+
+impl RawElement {
+    pub fn data_offset(&self) -> u64 {
+        self.offset + self.header_len as u64
+    }
+
+    pub fn end_offset(&self) -> Option<u64> {
+        self.data_len.map(|len| self.data_offset() + len)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RawReaderError {
+    #[error("IOError: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid element header at offset {0}")]
+    InvalidHeader(u64),
+    #[error("element at offset {0} overruns the end of its parent at offset {1}")]
+    OverrunsParent(u64, u64),
+    #[error("cannot skip the data of an unknown-sized element (at offset {0})")]
+    SkipUnknownSized(u64),
+    #[error("no parent element of unknown size to ascend from")]
+    NoUnknownSizedParent,
+    #[error("no current element")]
+    NoCurrentElement,
+}
+
+/**
+A schema-less reader over the raw element headers in a byte stream.
+
+Each call to `next` reads the next element header in document order. Before the following call,
+the caller may either `descend` into the current element (i.e., treat its data as child elements),
+`skip_data`, or read its data with `read_data`; by default, the data is skipped.
+
+Without a schema, the end of an unknown-sized element cannot be detected from the stream alone.
+Its data can thus be neither skipped nor read: `next` fails with `SkipUnknownSized` unless the
+caller descends into the element first. Unknown-sized elements that were descended into are
+treated as extending to the end of their own parent; callers with further knowledge (e.g., a
+schema) can `ascend` out of them once a non-child element is reached.
+
+**/
+#[derive(Debug)]
+pub struct RawElementReader<R> {
+    reader: R,
+    offset: u64,
+    parent_ends: Vec<Option<u64>>, // `None` -> unknown-sized parent
+    current: Option<RawElement>,
+}
+
+impl<R: BufRead> RawElementReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            offset: 0,
+            parent_ends: Vec::new(),
+            current: None,
+        }
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn depth(&self) -> usize {
+        self.parent_ends.len()
+    }
+
+    // the end offset of the innermost sized element that was descended into, if any
+    pub fn parent_end(&self) -> Option<u64> {
+        self.parent_ends.iter().rev().flatten().next().copied()
+    }
+
+    pub fn current(&self) -> Option<&RawElement> {
+        self.current.as_ref()
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_next(&mut self) -> Result<Option<RawElement>, RawReaderError> {
+        if self.current.is_some() {
+            self.skip_data()?;
+        }
+        self.close_finished_parents();
+
+        let offset = self.offset;
+        let header = match self.read_header()? {
+            Some(header) => header,
+            None => {
+                return match self.parent_ends.iter().flatten().next() {
+                    Some(_) => Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+                    None => {
+                        self.parent_ends.clear();
+                        Ok(None)
+                    }
+                };
+            }
+        };
+        let (id, data_len) =
+            Self::parse_header(&header).ok_or(RawReaderError::InvalidHeader(offset))?;
+
+        let element = RawElement {
+            id,
+            header_len: header.len(),
+            data_len,
+            offset,
+            depth: self.parent_ends.len(),
+        };
+        if let Some(parent_end) = self.parent_ends.iter().rev().flatten().next() {
+            if element.end_offset().unwrap_or(element.data_offset()) > *parent_end {
+                return Err(RawReaderError::OverrunsParent(offset, *parent_end));
+            }
+        }
+
+        self.current = Some(element);
+        Ok(Some(element))
+    }
+
+    pub fn descend(&mut self) -> Result<(), RawReaderError> {
+        let element = self
+            .current
+            .take()
+            .ok_or(RawReaderError::NoCurrentElement)?;
+        self.parent_ends.push(element.end_offset());
+
+        Ok(())
+    }
+
+    pub fn ascend(&mut self) -> Result<(), RawReaderError> {
+        match self.parent_ends.last() {
+            Some(None) => {
+                self.parent_ends.pop();
+                if let Some(element) = self.current.as_mut() {
+                    element.depth -= 1;
+                }
+                Ok(())
+            }
+            _ => Err(RawReaderError::NoUnknownSizedParent),
+        }
+    }
+
+    pub fn skip_data(&mut self) -> Result<(), RawReaderError> {
+        let element = self
+            .current
+            .as_ref()
+            .ok_or(RawReaderError::NoCurrentElement)?;
+        let data_len = element
+            .data_len
+            .ok_or(RawReaderError::SkipUnknownSized(element.offset))?;
+
+        let skipped = std::io::copy(&mut (&mut self.reader).take(data_len), &mut std::io::sink())?;
+        self.offset += skipped;
+        if skipped < data_len {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
+        self.current = None;
+        Ok(())
+    }
+
+    pub fn read_data(&mut self) -> Result<Vec<u8>, RawReaderError> {
+        let element = self
+            .current
+            .as_ref()
+            .ok_or(RawReaderError::NoCurrentElement)?;
+        let data_len = element
+            .data_len
+            .ok_or(RawReaderError::SkipUnknownSized(element.offset))?;
+
+        let mut buffer = Vec::new();
+        (&mut self.reader).take(data_len).read_to_end(&mut buffer)?;
+        self.offset += buffer.len() as u64;
+        if (buffer.len() as u64) < data_len {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
+        self.current = None;
+        Ok(buffer)
+    }
+
+    fn close_finished_parents(&mut self) {
+        // unknown-sized parents end along with their innermost sized ancestor
+        while let Some(i) = self.parent_ends.iter().rposition(Option::is_some) {
+            if self.parent_ends[i] > Some(self.offset) {
+                break;
+            }
+            self.parent_ends.truncate(i);
+        }
+    }
+
+    // Reads exactly the bytes of one element header, or `None` at the end of the stream.
+    fn read_header(&mut self) -> Result<Option<Vec<u8>>, RawReaderError> {
+        let mut header = Vec::with_capacity(MAX_HEADER_LEN);
+
+        if self.reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        for max_width in [4, 8] {
+            // the first byte of each VINT determines its total width
+            let first_byte = self.read_byte()?;
+            header.push(first_byte);
+            let width = first_byte.leading_zeros() as usize + 1;
+            if width > max_width {
+                return Ok(Some(header)); // fails in `parse_header`
+            }
+
+            let start = header.len();
+            header.resize(start + width - 1, 0);
+            self.reader.read_exact(&mut header[start..])?;
+            self.offset += (width - 1) as u64;
+        }
+
+        Ok(Some(header))
+    }
+
+    fn read_byte(&mut self) -> Result<u8, RawReaderError> {
+        let mut byte = [0u8];
+        self.reader.read_exact(&mut byte)?;
+        self.offset += 1;
+
+        Ok(byte[0])
+    }
+
+    fn parse_header(header: &[u8]) -> Option<(u32, Option<u64>)> {
+        let (stream, id) = parse::element_id(header).ok()?;
+        let (stream, len) = parse::element_len(stream).ok()?;
+        if !stream.is_empty() {
+            return None;
+        }
+
+        Some((id, len))
+    }
+}
+
+impl<R: BufRead> Iterator for RawElementReader<R> {
+    type Item = Result<RawElement, RawReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    const STREAM: [u8; 21] = [
+        0x1A, 0x45, 0xDF, 0xA3, // EBML element ID
+        0x8A, // EBML length = 10
+        0x42, 0x86, // EBMLVersion element ID
+        0x81, // EBMLVersion length = 1
+        0x01, // EBMLVersion data
+        0xEC, // Void element ID
+        0x84, // Void length = 4
+        0xFF, 0xFF, 0xFF, 0xFF, // Void data
+        0xEC, // Void element ID
+        0x40, 0x01, // Void length = 1
+        0x00, // Void data
+        0x18, 0x53, // incomplete element ID
+    ];
+
+    #[test]
+    fn iter_top_level() {
+        let mut reader = RawElementReader::new(&STREAM[..STREAM.len() - 2]);
+
+        assert_eq!(
+            reader.next().unwrap().unwrap(),
+            RawElement {
+                id: 0x1A45DFA3,
+                header_len: 5,
+                data_len: Some(10),
+                offset: 0,
+                depth: 0,
+            }
+        );
+        assert_eq!(
+            reader.next().unwrap().unwrap(),
+            RawElement {
+                id: 0xEC,
+                header_len: 3,
+                data_len: Some(1),
+                offset: 15,
+                depth: 0,
+            }
+        );
+        assert!(reader.next().is_none());
+        assert_eq!(reader.offset(), 19);
+    }
+
+    #[test]
+    fn iter_descend() {
+        let mut reader = RawElementReader::new(&STREAM[..STREAM.len() - 2]);
+        let mut result = Vec::new();
+
+        while let Some(element) = reader.next() {
+            let element = element.unwrap();
+            result.push((element.id, element.offset, element.depth));
+            if element.id == 0x1A45DFA3 {
+                reader.descend().unwrap();
+            } else if element.id == 0x4286 {
+                assert_eq!(reader.read_data().unwrap(), vec![0x01]);
+            }
+        }
+
+        assert_eq!(
+            result,
+            vec![
+                (0x1A45DFA3, 0, 0),
+                (0x4286, 5, 1),
+                (0xEC, 9, 1),
+                (0xEC, 15, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn parent_end() {
+        let mut reader = RawElementReader::new(&STREAM[..STREAM.len() - 2]);
+
+        reader.next().unwrap().unwrap();
+        assert_eq!(reader.parent_end(), None);
+        reader.descend().unwrap();
+        reader.next().unwrap().unwrap();
+        assert_eq!(reader.parent_end(), Some(15));
+
+        // the EBML element ends at the following top-level Void
+        reader.next().unwrap().unwrap();
+        reader.next().unwrap().unwrap();
+        assert_eq!(reader.parent_end(), None);
+    }
+
+    #[test]
+    fn iter_unknown_size() {
+        let stream = [
+            0x1A, 0x45, 0xDF, 0xA3, // EBML element ID
+            0xFF, // EBML length = unknown
+            0xEC, // Void element ID
+            0x81, // Void length = 1
+            0x00, // Void data
+        ];
+        let mut reader = RawElementReader::new(&stream[..]);
+
+        assert_eq!(reader.next().unwrap().unwrap().data_len, None);
+        assert!(matches!(
+            reader.skip_data(),
+            Err(RawReaderError::SkipUnknownSized(0))
+        ));
+        reader.descend().unwrap();
+        assert_eq!(reader.next().unwrap().unwrap().depth, 1);
+        reader.ascend().unwrap();
+        assert_eq!(reader.current().unwrap().depth, 0);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn iter_unknown_size_not_descended() {
+        let stream = [
+            0x1A, 0x45, 0xDF, 0xA3, // EBML element ID
+            0xFF, // EBML length = unknown
+            0xEC, // Void element ID
+            0x80, // Void length = 0
+        ];
+        let mut reader = RawElementReader::new(&stream[..]);
+
+        reader.next().unwrap().unwrap();
+        assert!(matches!(
+            reader.next(),
+            Some(Err(RawReaderError::SkipUnknownSized(0)))
+        ));
+    }
+
+    fn read_to_error(stream: &[u8]) -> RawReaderError {
+        let mut reader = RawElementReader::new(stream);
+        loop {
+            match reader.next() {
+                Some(Ok(RawElement { id: 0x1A45DFA3, .. })) => reader.descend().unwrap(),
+                Some(Ok(_)) => (),
+                Some(Err(error)) => break error,
+                None => panic!("expected an error"),
+            }
+        }
+    }
+
+    #[test]
+    fn iter_err_incomplete() {
+        assert!(matches!(
+            read_to_error(&STREAM[..]),
+            RawReaderError::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[rstest]
+    #[case(&[0x00, 0x81], 0)]
+    #[case(&[0x40, 0x7E, 0x81], 0)]
+    #[case(&[0xEC, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01], 0)]
+    #[case(&[0x1A, 0x45, 0xDF, 0xA3, 0x82, 0xFF, 0x80], 5)]
+    fn iter_err_invalid_header(#[case] stream: &'static [u8], #[case] expt_offset: u64) {
+        assert!(matches!(
+            read_to_error(stream),
+            RawReaderError::InvalidHeader(offset) if offset == expt_offset
+        ));
+    }
+
+    #[test]
+    fn iter_err_overrun() {
+        let stream = [0x1A, 0x45, 0xDF, 0xA3, 0x81, 0xEC, 0x82, 0x00, 0x00];
+        assert!(matches!(
+            read_to_error(&stream[..]),
+            RawReaderError::OverrunsParent(5, 6)
+        ));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::base::codec::{CodecError, EventEncoder};
+use crate::base::events::Event;
+use crate::base::parser::StateError;
+use crate::core::decoder::Decoder;
+
+use bytes::BytesMut;
+
+use std::collections::VecDeque;
+
+/**
+A `tokio_util` codec between EBML streams & their `Start`, `Value` & `End` events, e.g. to read &
+write documents over framed sockets.
+
+Decoding is incremental, as with `Decoder`; any events decoded before an error are yielded first.
+Encoding buffers each top-level element until it ends, as with `EventEncoder`.
+
+**/
+#[derive(Debug, Default)]
+pub struct EventCodec {
+    decoder: Decoder,
+    pending: VecDeque<Event>,
+    error: Option<StateError>,
+    encoder: EventEncoder,
+}
+
+impl EventCodec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encodes with the given encoder, e.g. one with set `SizeWidth`s.
+    pub fn with_encoder(encoder: EventEncoder) -> Self {
+        Self {
+            encoder,
+            ..Self::default()
+        }
+    }
+}
+
+impl tokio_util::codec::Decoder for EventCodec {
+    type Item = Event;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Event>, CodecError> {
+        // the decoder buffers incomplete elements itself
+        if !src.is_empty() && self.error.is_none() {
+            let result = self.decoder.push(&src.split());
+            self.pending.extend(self.decoder.events());
+            self.error = result.err();
+        }
+
+        match self.pending.pop_front() {
+            Some(event) => Ok(Some(event)),
+            None => self.error.take().map_or(Ok(None), |err| Err(err.into())),
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Event>, CodecError> {
+        if let Some(event) = self.decode(src)? {
+            return Ok(Some(event));
+        }
+
+        // the stream may end within unknown-sized elements
+        let complete = self.decoder.finish();
+        self.pending.extend(self.decoder.events());
+        match self.pending.pop_front() {
+            Some(event) => Ok(Some(event)),
+            None if complete => Ok(None),
+            None => Err(CodecError::Incomplete),
+        }
+    }
+}
+
+impl tokio_util::codec::Encoder<Event> for EventCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, event: Event, dst: &mut BytesMut) -> Result<(), CodecError> {
+        self.encoder.encode(event, dst)
+    }
+}
@@ -1,7 +1,7 @@
 #[allow(unused_imports)]
 use crate::base::element_defs::{
-    BinaryElementDef, DateElementDef, ElementDef, FloatElementDef, IntElementDef, MasterElementDef,
-    Range, StringElementDef, UIntElementDef, Utf8ElementDef,
+    BinaryElementDef, Date, DateElementDef, ElementDef, FloatElementDef, IntElementDef,
+    MasterElementDef, Range, StringElementDef, UIntElementDef, Utf8ElementDef,
 };
 
 use core::ops::Bound;
@@ -125,8 +125,8 @@ impl ElementDef for ModificationTimestampDef {
 }
 
 impl DateElementDef for ModificationTimestampDef {
-    const RANGE: Range<i64> = Range::IsWithin(Bound::Unbounded, Bound::Unbounded);
-    const DEFAULT: Option<i64> = None;
+    const RANGE: Range<Date> = Range::IsWithin(Bound::Unbounded, Bound::Unbounded);
+    const DEFAULT: Option<Date> = None;
 }
 
 // parent: File
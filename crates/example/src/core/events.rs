@@ -0,0 +1,209 @@
+#[allow(unused_imports)]
+use crate::base::date::Date;
+use crate::base::element_defs::ElementDef;
+use crate::base::events::{Event, OffsetReader, Value};
+#[allow(unused_imports)]
+use crate::base::parser::{NextReaderNavigation, ReaderDataParser, ReaderError};
+use crate::core::{element_defs, parser};
+
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+/**
+An iterator over the elements of a document as `Start`, `Value` & `End` events, in document order.
+
+Every element is started & ended; leaf elements yield their decoded value in between. Iteration
+ends at the end of the document, or after the first error.
+
+**/
+pub struct Events<R> {
+    reader: Option<parser::Readers<OffsetReader<R>>>,
+    open: Vec<(u32, usize)>, // the IDs of the started elements, & the path lengths before them
+    path: String,
+    pending: VecDeque<Event>,
+}
+
+impl<R: BufRead> Events<R> {
+    pub fn new(reader: parser::_DocumentReader<R>) -> Self {
+        Self {
+            reader: Some(parser::_DocumentReader::new(OffsetReader::new(reader.reader)).into()),
+            open: Vec::new(),
+            path: String::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    // Moves the reader by a single step, queueing the resulting events
+    fn step(
+        &mut self,
+        reader: parser::Readers<OffsetReader<R>>,
+    ) -> Result<Option<parser::Readers<OffsetReader<R>>>, ReaderError> {
+        let next_reader = match reader {
+            parser::Readers::_Document(mut r) => {
+                if r.reader.fill_buf()?.is_empty() {
+                    return Ok(None);
+                }
+                let next = r.next()?.into();
+                self.start(&next)?;
+                next
+            }
+            parser::Readers::Data(mut r) => {
+                let value: &[u8] = r.read()?;
+                self.value(Value::Binary(value.to_vec()));
+                self.end();
+                r.next()?.into()
+            }
+            parser::Readers::File(r) => match r.next()? {
+                parser::FileNextReaders::Parent(r_parent) => {
+                    self.end();
+                    r_parent.into()
+                }
+                r_next => {
+                    let next = r_next.into();
+                    self.start(&next)?;
+                    next
+                }
+            },
+            parser::Readers::FileName(mut r) => {
+                let value: &str = r.read()?;
+                self.value(Value::Utf8(value.to_owned()));
+                self.end();
+                r.next()?.into()
+            }
+            parser::Readers::Files(r) => match r.next()? {
+                parser::FilesNextReaders::Parent(r_parent) => {
+                    self.end();
+                    r_parent.into()
+                }
+                r_next => {
+                    let next = r_next.into();
+                    self.start(&next)?;
+                    next
+                }
+            },
+            parser::Readers::MimeType(mut r) => {
+                let value: &str = r.read()?;
+                self.value(Value::String(value.to_owned()));
+                self.end();
+                r.next()?.into()
+            }
+            parser::Readers::ModificationTimestamp(mut r) => {
+                let value: Date = r.read()?;
+                self.value(Value::Date(value));
+                self.end();
+                r.next()?.into()
+            }
+            parser::Readers::Void(mut r) => {
+                let value: &[u8] = r.read()?;
+                self.value(Value::Binary(value.to_vec()));
+                self.end();
+                r.next()?.into()
+            }
+        };
+        Ok(Some(next_reader))
+    }
+
+    // Starts the element that the reader just descended into: having discarded any skipped elements
+    // before it, the reader consumed the element's header last
+    fn start(&mut self, reader: &parser::Readers<OffsetReader<R>>) -> Result<(), ReaderError> {
+        let (id, name, size, offset, data_offset) = match reader {
+            parser::Readers::_Document(_) => unreachable!("the document isn't a child element"),
+            parser::Readers::Data(r) => (
+                element_defs::DataDef::ID,
+                "Data",
+                r.state.size(),
+                r.reader.last_offset(),
+                r.reader.offset(),
+            ),
+            parser::Readers::File(r) => (
+                element_defs::FileDef::ID,
+                "File",
+                r.state.size(),
+                r.reader.last_offset(),
+                r.reader.offset(),
+            ),
+            parser::Readers::FileName(r) => (
+                element_defs::FileNameDef::ID,
+                "FileName",
+                r.state.size(),
+                r.reader.last_offset(),
+                r.reader.offset(),
+            ),
+            parser::Readers::Files(r) => (
+                element_defs::FilesDef::ID,
+                "Files",
+                r.state.size(),
+                r.reader.last_offset(),
+                r.reader.offset(),
+            ),
+            parser::Readers::MimeType(r) => (
+                element_defs::MimeTypeDef::ID,
+                "MimeType",
+                r.state.size(),
+                r.reader.last_offset(),
+                r.reader.offset(),
+            ),
+            parser::Readers::ModificationTimestamp(r) => (
+                element_defs::ModificationTimestampDef::ID,
+                "ModificationTimestamp",
+                r.state.size(),
+                r.reader.last_offset(),
+                r.reader.offset(),
+            ),
+            parser::Readers::Void(r) => (
+                element_defs::VoidDef::ID,
+                "Void",
+                r.state.size(),
+                r.reader.last_offset(),
+                r.reader.offset(),
+            ),
+        };
+        if offset >= data_offset {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "the element header wasn't consumed in a single step",
+            )
+            .into());
+        }
+
+        self.open.push((id, self.path.len()));
+        self.path.push('\\');
+        self.path.push_str(name);
+        self.pending.push_back(Event::Start {
+            id,
+            path: self.path.clone(),
+            offset,
+            size: size.map(|size| size as u64),
+        });
+        Ok(())
+    }
+
+    fn value(&mut self, value: Value) {
+        let (id, _) = *self
+            .open
+            .last()
+            .expect("values are read from started elements");
+        self.pending.push_back(Event::Value { id, value });
+    }
+
+    fn end(&mut self) {
+        let (id, path_len) = self.open.pop().expect("only started elements are ended");
+        self.path.truncate(path_len);
+        self.pending.push_back(Event::End { id });
+    }
+}
+
+impl<R: BufRead> Iterator for Events<R> {
+    type Item = Result<Event, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            let reader = self.reader.take()?;
+            match self.step(reader) {
+                Ok(next_reader) => self.reader = next_reader,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
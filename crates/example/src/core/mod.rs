@@ -1,2 +1,9 @@
-mod element_defs;
+#[cfg(feature = "codec")]
+pub mod codec;
+pub mod decoder;
+pub mod element_defs;
+pub mod events;
+pub mod owned;
 pub mod parser;
+pub mod select;
+pub mod visitor;
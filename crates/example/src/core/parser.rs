@@ -1,16 +1,17 @@
 use crate::base::element_defs::ElementDef;
 #[allow(unused_imports)]
 use crate::base::parser::{
-    BoundTo, ElementReader, ElementState, IntoReader, NextStateNavigation, ReaderError,
-    SkipStateNavigation, StateDataParser, StateError,
+    BoundTo, BytesLeft, ElementReader, ElementState, IntoReader, NextStateNavigation, ReaderError,
+    SkipStateNavigation, StateDataParser, StateError, UnknownSizeSkip, Unread,
 };
 #[allow(unused_imports)]
 use crate::base::stream::{parse, serialize, stream_diff};
 use crate::core::element_defs;
 #[allow(unused_imports)]
 use crate::{
-    impl_from_readers_for_states, impl_from_subreaders_for_readers, impl_from_substates_for_states,
-    impl_into_reader, impl_next_state_navigation, impl_skip_state_navigation,
+    impl_bytes_left, impl_from_readers_for_states, impl_from_subreaders_for_readers,
+    impl_from_substates_for_states, impl_into_reader, impl_next_state_navigation,
+    impl_skip_state_navigation,
 };
 
 use enum_dispatch::enum_dispatch;
@@ -31,9 +32,12 @@ use std::io::BufRead;
 #[enum_dispatch(_DocumentNextReaders<R>)]
 #[enum_dispatch(FilesNextReaders<R>)]
 #[enum_dispatch(FileNextReaders<R>)]
-trait BlankTrait {}
+trait _BlankTrait {}
 
+// the variants are named after the schema's elements (e.g., `StatesElement`)
+#[allow(clippy::enum_variant_names)]
 #[enum_dispatch]
+#[derive(Debug, Clone, PartialEq)]
 pub enum States {
     _Document(_DocumentState),
     Void(VoidState),
@@ -45,6 +49,7 @@ pub enum States {
     Data(DataState),
 }
 
+#[allow(clippy::enum_variant_names)]
 #[enum_dispatch]
 pub enum Readers<R> {
     _Document(_DocumentReader<R>),
@@ -91,6 +96,24 @@ impl_from_readers_for_states!(
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct _DocumentState;
+
+// the document isn't bounded by any size
+impl BytesLeft for _DocumentState {
+    fn set_bytes_left(&mut self, _bytes_left: usize) {}
+}
+
+// The IDs of the children of an unknown-sized element, which ends at the first element that isn't
+// one of them
+#[allow(dead_code)]
+fn unknown_size_children(id: u32) -> Result<&'static [u32], StateError> {
+    match id {
+        0xEC | 0x464D | 0x4654 | 0x4664 | 0x6146 | 0x614E | 0x1946696C => {
+            Err(StateError::UnknownSizeNotAllowed(id))
+        }
+        id => Err(StateError::UnknownSizeSkipped(id)),
+    }
+}
+
 pub type _DocumentReader<R> = ElementReader<R, _DocumentState>;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -124,6 +147,7 @@ impl<R: BufRead> _DocumentReader<R> {
         Self {
             reader,
             state: _DocumentState,
+            unread: None,
         }
     }
 }
@@ -135,6 +159,12 @@ impl<R: BufRead> IntoReader<R> for _DocumentState {
     }
 }
 
+impl<R> From<_DocumentReader<R>> for _DocumentState {
+    fn from(reader: _DocumentReader<R>) -> Self {
+        reader.state
+    }
+}
+
 // Files Objects #########################################################################
 
 pub type FilesState = ElementState<element_defs::FilesDef, _DocumentState>;
@@ -166,6 +196,7 @@ impl FilesState {
     pub fn new(bytes_left: usize, parent_state: _DocumentState) -> Self {
         Self {
             bytes_left,
+            unknown_size: false,
             parent_state,
             _phantom: PhantomData::<_>,
         }
@@ -181,7 +212,11 @@ impl_next_state_navigation!(
 
 impl<R: BufRead> FilesReader<R> {
     pub fn new(reader: R, state: FilesState) -> Self {
-        Self { reader, state }
+        Self {
+            reader,
+            state,
+            unread: None,
+        }
     }
 }
 
@@ -266,6 +301,7 @@ impl FileState {
     pub fn new(bytes_left: usize, parent_state: FilesState) -> Self {
         Self {
             bytes_left,
+            unknown_size: false,
             parent_state,
             _phantom: PhantomData::<_>,
         }
@@ -287,7 +323,11 @@ impl_next_state_navigation!(
 
 impl<R: BufRead> FileReader<R> {
     pub fn new(reader: R, state: FileState) -> Self {
-        Self { reader, state }
+        Self {
+            reader,
+            state,
+            unread: None,
+        }
     }
 }
 
@@ -300,6 +340,7 @@ impl FileNameState {
     pub fn new(bytes_left: usize, parent_state: FileState) -> Self {
         Self {
             bytes_left,
+            unknown_size: false,
             parent_state,
             _phantom: PhantomData::<_>,
         }
@@ -311,7 +352,11 @@ impl_next_state_navigation!(FileNameState, FileState, []);
 
 impl<R: BufRead> FileNameReader<R> {
     pub fn new(reader: R, state: FileNameState) -> Self {
-        Self { reader, state }
+        Self {
+            reader,
+            state,
+            unread: None,
+        }
     }
 }
 
@@ -324,6 +369,7 @@ impl MimeTypeState {
     pub fn new(bytes_left: usize, parent_state: FileState) -> Self {
         Self {
             bytes_left,
+            unknown_size: false,
             parent_state,
             _phantom: PhantomData::<_>,
         }
@@ -335,7 +381,11 @@ impl_next_state_navigation!(MimeTypeState, FileState, []);
 
 impl<R: BufRead> MimeTypeReader<R> {
     pub fn new(reader: R, state: MimeTypeState) -> Self {
-        Self { reader, state }
+        Self {
+            reader,
+            state,
+            unread: None,
+        }
     }
 }
 
@@ -349,6 +399,7 @@ impl ModificationTimestampState {
     pub fn new(bytes_left: usize, parent_state: FileState) -> Self {
         Self {
             bytes_left,
+            unknown_size: false,
             parent_state,
             _phantom: PhantomData::<_>,
         }
@@ -360,7 +411,11 @@ impl_next_state_navigation!(ModificationTimestampState, FileState, []);
 
 impl<R: BufRead> ModificationTimestampReader<R> {
     pub fn new(reader: R, state: ModificationTimestampState) -> Self {
-        Self { reader, state }
+        Self {
+            reader,
+            state,
+            unread: None,
+        }
     }
 }
 
@@ -373,6 +428,7 @@ impl DataState {
     pub fn new(bytes_left: usize, parent_state: FileState) -> Self {
         Self {
             bytes_left,
+            unknown_size: false,
             parent_state,
             _phantom: PhantomData::<_>,
         }
@@ -384,7 +440,11 @@ impl_next_state_navigation!(DataState, FileState, []);
 
 impl<R: BufRead> DataReader<R> {
     pub fn new(reader: R, state: DataState) -> Self {
-        Self { reader, state }
+        Self {
+            reader,
+            state,
+            unread: None,
+        }
     }
 }
 
@@ -409,6 +469,7 @@ pub enum VoidPrevReaders<R> {
 }
 
 impl_from_substates_for_states!(VoidPrevStates, States, [_Document, Files, File]);
+impl_bytes_left!(VoidPrevStates, [_Document, Files, File]);
 impl_from_subreaders_for_readers!(VoidPrevReaders, Readers, [_Document, Files, File]);
 
 impl_into_reader!(VoidPrevStates, VoidPrevReaders, [_Document, Files, File]);
@@ -418,6 +479,7 @@ impl VoidState {
     pub fn new(bytes_left: usize, parent_state: VoidPrevStates) -> Self {
         Self {
             bytes_left,
+            unknown_size: false,
             parent_state,
             _phantom: PhantomData::<_>,
         }
@@ -429,7 +491,11 @@ impl_next_state_navigation!(VoidState, VoidPrevStates, []);
 
 impl<R: BufRead> VoidReader<R> {
     pub fn new(reader: R, state: VoidState) -> Self {
-        Self { reader, state }
+        Self {
+            reader,
+            state,
+            unread: None,
+        }
     }
 }
 
@@ -447,7 +513,7 @@ mod tests {
             case(
                 _DocumentState,
                 &[0x19, 0x46, 0x69, 0x6C, 0x82, 0xFF, 0xFF, 0xFF],
-                (&[0xFF, 0xFF, 0xFF][..], _DocumentNextStates::Files(FilesState{bytes_left: 2, parent_state: _DocumentState, _phantom: PhantomData}))
+                (&[0xFF, 0xFF, 0xFF][..], _DocumentNextStates::Files(FilesState{bytes_left: 2, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}))
             ),
         )]
         fn state_next(
@@ -464,12 +530,12 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                FilesState{bytes_left: 5, parent_state: _DocumentState, _phantom: PhantomData},
+                FilesState{bytes_left: 5, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData},
                 &[0x61, 0x46, 0x82, 0xFF, 0xFF, 0xFF],
-                (&[0xFF, 0xFF, 0xFF][..], FilesNextStates::File(FileState{bytes_left: 2, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}))
+                (&[0xFF, 0xFF, 0xFF][..], FilesNextStates::File(FileState{bytes_left: 2, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}))
             ),
             case(
-                FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData},
+                FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData},
                 &[0xFF, 0xFF, 0xFF],
                 (&[0xFF, 0xFF, 0xFF][..], FilesNextStates::Parent(_DocumentState))
             ),
@@ -484,7 +550,7 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                FilesState{bytes_left: 5, parent_state: _DocumentState, _phantom: PhantomData},
+                FilesState{bytes_left: 5, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData},
                 &[0x61, 0x4E, 0x82, 0xFF, 0xFF, 0xFF],
                 (&[0xFF][..], _DocumentState)
             ),
@@ -503,29 +569,29 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                FileState{bytes_left: 5, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
+                FileState{bytes_left: 5, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0x61, 0x4E, 0x82, 0xFF, 0xFF],
-                (&[0xFF, 0xFF][..], FileNextStates::FileName(FileNameState{bytes_left: 2, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData}))
+                (&[0xFF, 0xFF][..], FileNextStates::FileName(FileNameState{bytes_left: 2, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData}))
             ),
             case(
-                FileState{bytes_left: 5, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
+                FileState{bytes_left: 5, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0x46, 0x4D, 0x82, 0xFF, 0xFF],
-                (&[0xFF, 0xFF][..], FileNextStates::MimeType(MimeTypeState{bytes_left: 2, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData}))
+                (&[0xFF, 0xFF][..], FileNextStates::MimeType(MimeTypeState{bytes_left: 2, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData}))
             ),
             case(
-                FileState{bytes_left: 5, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
+                FileState{bytes_left: 5, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0x46, 0x54, 0x82, 0xFF, 0xFF],
-                (&[0xFF, 0xFF][..], FileNextStates::ModificationTimestamp(ModificationTimestampState{bytes_left: 2, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData}))
+                (&[0xFF, 0xFF][..], FileNextStates::ModificationTimestamp(ModificationTimestampState{bytes_left: 2, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData}))
             ),
             case(
-                FileState{bytes_left: 5, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
+                FileState{bytes_left: 5, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0x46, 0x64, 0x82, 0xFF, 0xFF],
-                (&[0xFF, 0xFF][..], FileNextStates::Data(DataState{bytes_left: 2, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData}))
+                (&[0xFF, 0xFF][..], FileNextStates::Data(DataState{bytes_left: 2, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData}))
             ),
             case(
-                FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
+                FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0xFF, 0xFF],
-                (&[0xFF, 0xFF][..], FileNextStates::Parent(FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}))
+                (&[0xFF, 0xFF][..], FileNextStates::Parent(FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}))
             ),
         )]
         fn state_next(
@@ -538,9 +604,9 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                FileState{bytes_left: 5, parent_state: FilesState{bytes_left: 1, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
+                FileState{bytes_left: 5, unknown_size: false, parent_state: FilesState{bytes_left: 1, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0x61, 0x4E, 0x82, 0xFF, 0xFF, 0xFF],
-                (&[0xFF][..], FilesState{bytes_left: 1, parent_state: _DocumentState, _phantom: PhantomData})
+                (&[0xFF][..], FilesState{bytes_left: 1, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData})
             ),
         )]
         fn state_skip(
@@ -557,9 +623,9 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                FileNameState{bytes_left: 3, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
+                FileNameState{bytes_left: 3, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0xFF, 0xFF, 0xFF, 0xFF],
-                (&[0xFF][..], FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}),
+                (&[0xFF][..], FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}),
             ),
         )]
         fn state_next(
@@ -572,9 +638,9 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                FileNameState{bytes_left: 3, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
+                FileNameState{bytes_left: 3, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0xFF, 0xFF, 0xFF, 0xFF],
-                (&[0xFF][..], FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData})
+                (&[0xFF][..], FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData})
             ),
         )]
         fn state_skip(
@@ -591,9 +657,9 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                MimeTypeState{bytes_left: 3, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
+                MimeTypeState{bytes_left: 3, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0xFF, 0xFF, 0xFF, 0xFF],
-                (&[0xFF][..], FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}),
+                (&[0xFF][..], FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}),
             ),
         )]
         fn state_next(
@@ -606,9 +672,9 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                MimeTypeState{bytes_left: 3, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
+                MimeTypeState{bytes_left: 3, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0xFF, 0xFF, 0xFF, 0xFF],
-                (&[0xFF][..], FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData})
+                (&[0xFF][..], FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData})
             ),
         )]
         fn state_skip(
@@ -625,9 +691,9 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                ModificationTimestampState{bytes_left: 3, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
+                ModificationTimestampState{bytes_left: 3, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0xFF, 0xFF, 0xFF, 0xFF],
-                (&[0xFF][..], FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}),
+                (&[0xFF][..], FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}),
             ),
         )]
         fn state_next(
@@ -640,9 +706,9 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                ModificationTimestampState{bytes_left: 3, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
+                ModificationTimestampState{bytes_left: 3, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0xFF, 0xFF, 0xFF, 0xFF],
-                (&[0xFF][..], FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData})
+                (&[0xFF][..], FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData})
             ),
         )]
         fn state_skip(
@@ -659,9 +725,9 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                DataState{bytes_left: 3, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
+                DataState{bytes_left: 3, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0xFF, 0xFF, 0xFF, 0xFF],
-                (&[0xFF][..], FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}),
+                (&[0xFF][..], FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}),
             ),
         )]
         fn state_next(
@@ -674,9 +740,9 @@ mod tests {
 
         #[rstest(element, source, expt_result,
             case(
-                DataState{bytes_left: 3, parent_state: FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
+                DataState{bytes_left: 3, unknown_size: false, parent_state: FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData}, _phantom: PhantomData},
                 &[0xFF, 0xFF, 0xFF, 0xFF],
-                (&[0xFF][..], FileState{bytes_left: 0, parent_state: FilesState{bytes_left: 0, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData})
+                (&[0xFF][..], FileState{bytes_left: 0, unknown_size: false, parent_state: FilesState{bytes_left: 0, unknown_size: false, parent_state: _DocumentState, _phantom: PhantomData}, _phantom: PhantomData})
             ),
         )]
         fn state_skip(
@@ -0,0 +1,104 @@
+#[allow(unused_imports)]
+use crate::base::date::Date;
+#[allow(unused_imports)]
+use crate::base::parser::{
+    NextReaderNavigation, ReaderDataParser, ReaderError, SkipReaderNavigation, Visit,
+};
+use crate::core::parser;
+
+use std::io::BufRead;
+
+/**
+Callbacks for the elements of a document, as called by `walk` in document order. Leaf elements
+are passed their decoded values; master elements are entered before, & exited after, their
+children. Returning `Visit::Skip` on entering a master element moves on past it, without calling
+its children's callbacks or its exit callback. The skipped data is still read from the reader (&
+discarded), just not decoded.
+
+All callbacks default to doing nothing.
+
+**/
+pub trait Visitor {
+    fn visit_data(&mut self, _value: &[u8]) {}
+    fn enter_file(&mut self) -> Visit {
+        Visit::Continue
+    }
+    fn exit_file(&mut self) {}
+    fn visit_file_name(&mut self, _value: &str) {}
+    fn enter_files(&mut self) -> Visit {
+        Visit::Continue
+    }
+    fn exit_files(&mut self) {}
+    fn visit_mime_type(&mut self, _value: &str) {}
+    fn visit_modification_timestamp(&mut self, _value: Date) {}
+    fn visit_void(&mut self, _value: &[u8]) {}
+}
+
+/// Reads a whole document, calling the visitor's callbacks for each element.
+pub fn walk<R: BufRead, V: Visitor + ?Sized>(
+    reader: parser::_DocumentReader<R>,
+    visitor: &mut V,
+) -> Result<(), ReaderError> {
+    let mut reader: parser::Readers<R> = reader.into();
+    // whether the reader just descended into its element, or returned to it from a child
+    let mut entering = true;
+
+    loop {
+        let (next_reader, next_entering) = match reader {
+            parser::Readers::_Document(mut r) => {
+                if r.reader.fill_buf()?.is_empty() {
+                    return Ok(());
+                }
+                (r.next()?.into(), true)
+            }
+            parser::Readers::Data(mut r) => {
+                visitor.visit_data(r.read()?);
+                (r.next()?.into(), false)
+            }
+            parser::Readers::File(r) => {
+                if entering && visitor.enter_file() == Visit::Skip {
+                    (r.skip()?.into(), false)
+                } else {
+                    match r.next()? {
+                        parser::FileNextReaders::Parent(r_parent) => {
+                            visitor.exit_file();
+                            (r_parent.into(), false)
+                        }
+                        r_next => (r_next.into(), true),
+                    }
+                }
+            }
+            parser::Readers::FileName(mut r) => {
+                visitor.visit_file_name(r.read()?);
+                (r.next()?.into(), false)
+            }
+            parser::Readers::Files(r) => {
+                if entering && visitor.enter_files() == Visit::Skip {
+                    (r.skip()?.into(), false)
+                } else {
+                    match r.next()? {
+                        parser::FilesNextReaders::Parent(r_parent) => {
+                            visitor.exit_files();
+                            (r_parent.into(), false)
+                        }
+                        r_next => (r_next.into(), true),
+                    }
+                }
+            }
+            parser::Readers::MimeType(mut r) => {
+                visitor.visit_mime_type(r.read()?);
+                (r.next()?.into(), false)
+            }
+            parser::Readers::ModificationTimestamp(mut r) => {
+                visitor.visit_modification_timestamp(r.read()?);
+                (r.next()?.into(), false)
+            }
+            parser::Readers::Void(mut r) => {
+                visitor.visit_void(r.read()?);
+                (r.next()?.into(), false)
+            }
+        };
+        reader = next_reader;
+        entering = next_entering;
+    }
+}
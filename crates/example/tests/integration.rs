@@ -1,7 +1,11 @@
+use example_ebml_parser::base::events::{Event, Value};
 use example_ebml_parser::base::parser::{
-    NextReaderNavigation, ReaderDataParser, ReaderError, SkipReaderNavigation,
+    NextReaderNavigation, ReaderDataParser, ReaderError, SkipReaderNavigation, Visit,
 };
-use example_ebml_parser::core::parser;
+use example_ebml_parser::core::element_defs::FileNameDef;
+use example_ebml_parser::core::{decoder, events, parser, select, visitor};
+
+use std::io::{BufRead, ErrorKind, Read};
 
 const BYTE_STREAM: [u8; 150] = [
     // ### Files 1 ###
@@ -66,6 +70,49 @@ const BYTE_STREAM: [u8; 150] = [
     0x01, 0x02, 0x03, 0x04, // Data data
 ];
 
+// A buffered reader over a stream that arrives in parts, whose first read is interrupted
+struct ArrivingReader<'a> {
+    stream: &'a [u8],
+    available: usize, // the length of the stream that arrived so far
+    position: usize,
+    interrupted: bool,
+}
+
+impl<'a> ArrivingReader<'a> {
+    fn new(stream: &'a [u8], available: usize) -> Self {
+        Self {
+            stream,
+            available,
+            position: 0,
+            interrupted: false,
+        }
+    }
+}
+
+impl Read for ArrivingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let stream = self.fill_buf()?;
+        let len = stream.len().min(buf.len());
+        buf[..len].copy_from_slice(&stream[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for ArrivingReader<'_> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if !self.interrupted {
+            self.interrupted = true;
+            return Err(ErrorKind::Interrupted.into());
+        }
+        Ok(&self.stream[self.position..self.available])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position += amt;
+    }
+}
+
 #[test]
 fn basic_traversal() {
     let mut reader: parser::Readers<_> = parser::_DocumentReader::new(&BYTE_STREAM[..]).into();
@@ -110,6 +157,28 @@ fn basic_traversal() {
     );
 }
 
+#[test]
+fn try_next_retries() {
+    let reader = parser::_DocumentReader::new(ArrivingReader::new(&BYTE_STREAM, 3));
+
+    let reader = match reader.try_next() {
+        Err((reader, ReaderError::Io(err))) if err.kind() == ErrorKind::Interrupted => reader,
+        _ => panic!("expected an interrupted read"),
+    };
+    // the Files header is incomplete
+    let mut reader = match reader.try_next() {
+        Err((reader, ReaderError::Parse(nom::Err::Incomplete(_)))) => reader,
+        _ => panic!("expected incomplete data"),
+    };
+    assert_eq!(reader.reader.position, 0);
+
+    reader.reader.available = BYTE_STREAM.len();
+    match reader.try_next() {
+        Ok(parser::_DocumentNextReaders::Files(reader)) => assert_eq!(reader.reader.position, 5),
+        _ => panic!("expected the Files element"),
+    }
+}
+
 #[test]
 fn find_all_element_instances() {
     let mut reader: parser::Readers<_> = parser::_DocumentReader::new(&BYTE_STREAM[..]).into();
@@ -148,3 +217,93 @@ fn find_all_element_instances() {
         ]
     );
 }
+
+#[derive(Default)]
+struct FileNameVisitor {
+    depth: usize,
+    names: Vec<String>,
+    skipped_files: usize,
+}
+
+impl visitor::Visitor for FileNameVisitor {
+    fn enter_files(&mut self) -> Visit {
+        self.depth += 1;
+        Visit::Continue
+    }
+    fn exit_files(&mut self) {
+        self.depth -= 1;
+    }
+    fn enter_file(&mut self) -> Visit {
+        // skip over the second file ("file2.csv")
+        if self.names.len() == 1 && self.skipped_files == 0 {
+            self.skipped_files += 1;
+            return Visit::Skip;
+        }
+        Visit::Continue
+    }
+    fn visit_file_name(&mut self, value: &str) {
+        assert_eq!(self.depth, 1);
+        self.names.push(value.to_string());
+    }
+}
+
+#[test]
+fn visitor_walk() {
+    let mut visitor = FileNameVisitor::default();
+    visitor::walk(parser::_DocumentReader::new(&BYTE_STREAM[..]), &mut visitor).unwrap();
+
+    assert_eq!(visitor.depth, 0);
+    assert_eq!(visitor.names, vec!["file3.html", "file1.txt"]);
+    assert_eq!(visitor.skipped_files, 1);
+}
+
+#[test]
+fn event_iterator() {
+    let file_names = events::Events::new(parser::_DocumentReader::new(&BYTE_STREAM[..]))
+        .filter_map(|event| match event.unwrap() {
+            Event::Value {
+                id: 0x614E,
+                value: Value::Utf8(name),
+            } => Some(name),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(file_names, vec!["file3.html", "file2.csv", "file1.txt"]);
+}
+
+#[test]
+fn select_file_names() {
+    let file_names =
+        select::select::<FileNameDef, _>(parser::_DocumentReader::new(&BYTE_STREAM[..]))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+    assert_eq!(
+        file_names,
+        vec![
+            Value::Utf8("file3.html".to_string()),
+            Value::Utf8("file2.csv".to_string()),
+            Value::Utf8("file1.txt".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn push_decoder() {
+    let mut decoder = decoder::Decoder::new();
+    let mut file_names = Vec::new();
+    for chunk in BYTE_STREAM.chunks(4) {
+        decoder.push(chunk).unwrap();
+        file_names.extend(decoder.events().filter_map(|event| match event {
+            Event::Value {
+                id: 0x614E,
+                value: Value::Utf8(name),
+            } => Some(name),
+            _ => None,
+        }));
+    }
+
+    assert_eq!(file_names, vec!["file3.html", "file2.csv", "file1.txt"]);
+    assert!(decoder.is_complete());
+}
@@ -4,15 +4,57 @@ fn main() {
     let cargo_path = std::env::var("CARGO_MANIFEST_DIR")
         .map(std::path::PathBuf::from)
         .expect("no env variable 'CARGO_MANIFEST_DIR'");
+    let out_dir = std::env::var("OUT_DIR")
+        .map(std::path::PathBuf::from)
+        .expect("no env variable 'OUT_DIR'");
 
-    let schema_file = std::io::BufReader::new(
-        std::fs::File::open(cargo_path.join("eg_schema.xml")).expect("couldn't open schema file"),
-    );
-
-    Builder::new(schema_file)
+    let parsers = Builder::from_path(cargo_path.join("eg_schema.xml"))
         .expect("couldn't parse schema file")
+        .owned(true)
+        .writers(true)
+        .validation(true)
         .generate()
-        .expect("couldn't make parser writer")
+        .expect("couldn't make parser writer");
+    parsers
         .write_package(cargo_path.join("parser"))
         .expect("couldn't write parser crate");
+    parsers
+        .write_module(&out_dir)
+        .expect("couldn't write parser module");
+
+    // a parser that skips over the (excluded) file data
+    let filtered_dir = out_dir.join("filtered");
+    std::fs::create_dir_all(&filtered_dir).expect("couldn't create module directory");
+    Builder::from_path(cargo_path.join("eg_schema.xml"))
+        .expect("couldn't parse schema file")
+        .blocklist_path(r"\Files\File\Data")
+        .generate()
+        .expect("couldn't make parser writer")
+        .write_module(filtered_dir)
+        .expect("couldn't write parser module");
+
+    // a parser that rejects the (excluded) file data
+    let rejected_dir = out_dir.join("rejected");
+    std::fs::create_dir_all(&rejected_dir).expect("couldn't create module directory");
+    Builder::from_path(cargo_path.join("eg_schema.xml"))
+        .expect("couldn't parse schema file")
+        .blocklist_path(r"\Files\File\Data")
+        .reject_excluded(true)
+        .generate()
+        .expect("couldn't make parser writer")
+        .write_module(rejected_dir)
+        .expect("couldn't write parser module");
+
+    // elements named after the generated & imported identifiers
+    let reserved_dir = out_dir.join("reserved");
+    std::fs::create_dir_all(&reserved_dir).expect("couldn't create module directory");
+    Builder::from_path(cargo_path.join("reserved_schema.xml"))
+        .expect("couldn't parse schema file")
+        .owned(true)
+        .writers(true)
+        .validation(true)
+        .generate()
+        .expect("couldn't make parser writer")
+        .write_module(reserved_dir)
+        .expect("couldn't write parser module");
 }
@@ -0,0 +1,346 @@
+use core::fmt;
+use core::str::FromStr;
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime};
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+const SECS_PER_DAY: i64 = 86_400;
+
+/**
+A timestamp as stored in EBML `date` elements, i.e. the number of nanoseconds relative to the
+EBML epoch, 2001-01-01T00:00:00 UTC.
+
+`Date` values convert to/from `std::time::SystemTime`, as well as to/from `chrono::DateTime<Utc>`
+and `time::OffsetDateTime` with the `chrono` and `time` features enabled, respectively. They are
+displayed and parsed as ISO 8601 timestamps (e.g., `2001-01-01T00:00:00Z`).
+
+**/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date(i64);
+
+impl Date {
+    pub const EPOCH: Self = Self(0);
+    // the number of seconds between 1970-01-01T00:00:00 UTC and the EBML epoch
+    pub const UNIX_EPOCH_OFFSET_SECS: i64 = 978_307_200;
+
+    pub const fn from_nanos(nanos: i64) -> Self {
+        Self(nanos)
+    }
+
+    pub const fn nanos(self) -> i64 {
+        self.0
+    }
+
+    pub fn from_unix_nanos(nanos: i128) -> Result<Self, DateError> {
+        let offset = i128::from(Self::UNIX_EPOCH_OFFSET_SECS * NANOS_PER_SEC);
+        i64::try_from(nanos - offset)
+            .map(Self)
+            .map_err(|_| DateError::OutOfRange)
+    }
+
+    pub fn unix_nanos(self) -> i128 {
+        i128::from(self.0) + i128::from(Self::UNIX_EPOCH_OFFSET_SECS * NANOS_PER_SEC)
+    }
+
+    // (seconds since the Unix epoch, subsecond nanoseconds)
+    fn unix_secs(self) -> (i64, u32) {
+        (
+            self.0.div_euclid(NANOS_PER_SEC) + Self::UNIX_EPOCH_OFFSET_SECS,
+            self.0.rem_euclid(NANOS_PER_SEC) as u32,
+        )
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum DateError {
+    #[error("timestamp is out of range for an EBML date")]
+    OutOfRange,
+    #[error("invalid ISO 8601 timestamp: {0:?}")]
+    InvalidFormat(String),
+}
+
+impl From<Date> for SystemTime {
+    fn from(date: Date) -> Self {
+        let (secs, subsec_nanos) = date.unix_secs();
+        if secs >= 0 {
+            Self::UNIX_EPOCH + Duration::new(secs as u64, subsec_nanos)
+        } else {
+            Self::UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+                + Duration::from_nanos(subsec_nanos.into())
+        }
+    }
+}
+
+impl TryFrom<SystemTime> for Date {
+    type Error = DateError;
+
+    fn try_from(time: SystemTime) -> Result<Self, Self::Error> {
+        let unix_nanos = match time.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(duration) => i128::try_from(duration.as_nanos()),
+            Err(error) => i128::try_from(error.duration().as_nanos()).map(|nanos| -nanos),
+        };
+        Self::from_unix_nanos(unix_nanos.map_err(|_| DateError::OutOfRange)?)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Date> for chrono::DateTime<chrono::Utc> {
+    fn from(date: Date) -> Self {
+        let (secs, subsec_nanos) = date.unix_secs();
+        Self::from_timestamp(secs, subsec_nanos).expect("all EBML dates are within chrono's range")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::DateTime<chrono::Utc>> for Date {
+    type Error = DateError;
+
+    fn try_from(time: chrono::DateTime<chrono::Utc>) -> Result<Self, Self::Error> {
+        Self::from_unix_nanos(
+            i128::from(time.timestamp()) * i128::from(NANOS_PER_SEC)
+                + i128::from(time.timestamp_subsec_nanos()),
+        )
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<Date> for time::OffsetDateTime {
+    fn from(date: Date) -> Self {
+        Self::from_unix_timestamp_nanos(date.unix_nanos())
+            .expect("all EBML dates are within time's range")
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::OffsetDateTime> for Date {
+    type Error = DateError;
+
+    fn try_from(time: time::OffsetDateTime) -> Result<Self, Self::Error> {
+        Self::from_unix_nanos(time.unix_timestamp_nanos())
+    }
+}
+
+// Converts days since the Unix epoch into a proleptic Gregorian (year, month, day); see
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153; // March = 0
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+// The inverse of `civil_from_days`; see
+// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_index = (month + 9) % 12; // March = 0
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (secs, subsec_nanos) = self.unix_secs();
+        let (days, secs_of_day) = (secs.div_euclid(SECS_PER_DAY), secs.rem_euclid(SECS_PER_DAY));
+        let (year, month, day) = civil_from_days(days);
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60,
+        )?;
+        if subsec_nanos != 0 {
+            write!(f, ".{:09}", subsec_nanos)?;
+        }
+        write!(f, "Z")
+    }
+}
+
+/**
+Parses ISO 8601 timestamps of the forms `YYYY-MM-DD` (i.e., midnight UTC) and
+`YYYY-MM-DDThh:mm:ss[.fff...](Z|+hh:mm|-hh:mm)`.
+
+**/
+impl FromStr for Date {
+    type Err = DateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || DateError::InvalidFormat(s.to_string());
+        let number = |digits: &str| -> Result<i64, DateError> {
+            if !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(error());
+            }
+            digits.parse().map_err(|_| error())
+        };
+
+        if !s.is_ascii() || s.len() < 10 || &s[4..5] != "-" || &s[7..8] != "-" {
+            return Err(error());
+        }
+        let (year, month, day) = (number(&s[0..4])?, number(&s[5..7])?, number(&s[8..10])?);
+        let days_in_month = match month {
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return Err(error()),
+        };
+        if !(1..=days_in_month).contains(&day) {
+            return Err(error());
+        }
+        let mut secs = days_from_civil(year, month, day) * SECS_PER_DAY;
+        let mut subsec_nanos = 0;
+
+        let time = &s[10..];
+        if !time.is_empty() {
+            if time.len() < 10 || !time.starts_with('T') || &time[3..4] != ":" || &time[6..7] != ":"
+            {
+                return Err(error());
+            }
+            let (hour, minute, second) = (
+                number(&time[1..3])?,
+                number(&time[4..6])?,
+                number(&time[7..9])?,
+            );
+            if hour > 23 || minute > 59 || second > 59 {
+                return Err(error());
+            }
+            secs += hour * 3600 + minute * 60 + second;
+
+            let mut rest = &time[9..];
+            if let Some(fraction) = rest.strip_prefix('.') {
+                let len = fraction
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(fraction.len());
+                if len == 0 || len > 9 {
+                    return Err(error());
+                }
+                subsec_nanos = number(&fraction[..len])? * 10_i64.pow(9 - len as u32);
+                rest = &fraction[len..];
+            }
+
+            secs -= match rest {
+                "Z" => 0,
+                _ if rest.len() == 6 && &rest[3..4] == ":" => {
+                    let sign = match &rest[..1] {
+                        "+" => 1,
+                        "-" => -1,
+                        _ => return Err(error()),
+                    };
+                    let (hours, minutes) = (number(&rest[1..3])?, number(&rest[4..6])?);
+                    sign * (hours * 3600 + minutes * 60)
+                }
+                _ => return Err(error()),
+            };
+        }
+
+        Self::from_unix_nanos(
+            i128::from(secs) * i128::from(NANOS_PER_SEC) + i128::from(subsec_nanos),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(0, "2001-01-01T00:00:00Z")]
+    #[case(1, "2001-01-01T00:00:00.000000001Z")]
+    #[case(-1, "2000-12-31T23:59:59.999999999Z")]
+    #[case(-978_307_200_000_000_000, "1970-01-01T00:00:00Z")]
+    #[case(36_633_600_000_000_000, "2002-03-01T00:00:00Z")]
+    #[case(i64::MAX, "2293-04-11T23:47:16.854775807Z")]
+    #[case(i64::MIN, "1708-09-22T00:12:43.145224192Z")]
+    fn display(#[case] nanos: i64, #[case] expt_result: &str) {
+        assert_eq!(Date::from_nanos(nanos).to_string(), expt_result);
+    }
+
+    #[rstest]
+    #[case("2001-01-01", 0)]
+    #[case("2001-01-01T00:00:00Z", 0)]
+    #[case("2001-01-01T00:00:00.5Z", 500_000_000)]
+    #[case("2001-01-01T01:00:00+01:00", 0)]
+    #[case("2000-12-31T23:00:00-01:00", 0)]
+    #[case("2000-02-29T00:00:00Z", -26_524_800_000_000_000)]
+    fn from_str(#[case] s: &str, #[case] expt_nanos: i64) {
+        assert_eq!(s.parse(), Ok(Date::from_nanos(expt_nanos)));
+    }
+
+    #[rstest]
+    #[case("2001-01-01T00:00:00", DateError::InvalidFormat("2001-01-01T00:00:00".to_string()))]
+    #[case("2001-02-29", DateError::InvalidFormat("2001-02-29".to_string()))]
+    #[case("2001-01-01T24:00:00Z", DateError::InvalidFormat("2001-01-01T24:00:00Z".to_string()))]
+    #[case("2001-01-01T00:00:00.Z", DateError::InvalidFormat("2001-01-01T00:00:00.Z".to_string()))]
+    #[case("2001/01/01", DateError::InvalidFormat("2001/01/01".to_string()))]
+    #[case("3000-01-01", DateError::OutOfRange)]
+    fn from_str_err(#[case] s: &str, #[case] expt_error: DateError) {
+        assert_eq!(s.parse::<Date>(), Err(expt_error));
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(-1)]
+    #[case(-978_307_200_000_000_001)]
+    fn system_time(#[case] nanos: i64) {
+        let date = Date::from_nanos(nanos);
+        let time = SystemTime::from(date);
+
+        assert_eq!(
+            time.duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as i128)
+                .unwrap_or_else(|e| -(e.duration().as_nanos() as i128)),
+            date.unix_nanos()
+        );
+        assert_eq!(Date::try_from(time), Ok(date));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_date_time() {
+        let date = Date::from_nanos(-1);
+        let time = chrono::DateTime::<chrono::Utc>::from(date);
+
+        assert_eq!(time.timestamp_nanos_opt(), Some(978_307_199_999_999_999));
+        assert_eq!(Date::try_from(time), Ok(date));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_offset_date_time() {
+        let date = Date::from_nanos(-1);
+        let time = time::OffsetDateTime::from(date);
+
+        assert_eq!(time.unix_timestamp_nanos(), 978_307_199_999_999_999);
+        assert_eq!(Date::try_from(time), Ok(date));
+    }
+
+    proptest! {
+        #[test]
+        fn display_from_str_eq(nanos in any::<i64>()) {
+            let date = Date::from_nanos(nanos);
+            prop_assert_eq!(date.to_string().parse(), Ok(date));
+        }
+    }
+}
@@ -1,3 +1,4 @@
+pub use crate::base::date::Date;
 pub use core::ops::Bound;
 
 pub enum Range<T> {
@@ -6,6 +7,29 @@ pub enum Range<T> {
     IsWithin(Bound<T>, Bound<T>),
 }
 
+impl<T: PartialOrd> Range<T> {
+    /// Whether `value` satisfies the range.
+    pub fn contains(&self, value: &T) -> bool {
+        match self {
+            Range::IsExactly(x) => value == x,
+            Range::Excludes(x) => value != x,
+            Range::IsWithin(lower, upper) => {
+                let above = match lower {
+                    Bound::Included(x) => value >= x,
+                    Bound::Excluded(x) => value > x,
+                    Bound::Unbounded => true,
+                };
+                let below = match upper {
+                    Bound::Included(x) => value <= x,
+                    Bound::Excluded(x) => value < x,
+                    Bound::Unbounded => true,
+                };
+                above && below
+            }
+        }
+    }
+}
+
 pub trait ElementDef {
     // name
     const ID: u32;
@@ -17,6 +41,9 @@ pub trait ElementDef {
     const RECURRING: bool; // defaults to false
     const MIN_VERSION: u64; // defaults to 1
     const MAX_VERSION: Option<u64>; // defaults to "EBMLSchema"'s "version" attribute
+
+    // the schema's `<extension>`s of the element, as `(type, [(attribute, value), ...])`
+    const EXTENSIONS: &'static [(&'static str, &'static [(&'static str, &'static str)])] = &[];
 }
 
 pub trait MasterElementDef: ElementDef {
@@ -40,8 +67,8 @@ pub trait FloatElementDef: ElementDef {
 }
 
 pub trait DateElementDef: ElementDef {
-    const RANGE: Range<i64>; // defaults to (Unbounded, Unbounded)
-    const DEFAULT: Option<i64>;
+    const RANGE: Range<Date>; // defaults to (Unbounded, Unbounded)
+    const DEFAULT: Option<Date>;
 }
 
 pub trait StringElementDef: ElementDef {
@@ -55,3 +82,69 @@ pub trait Utf8ElementDef: ElementDef {
 pub trait BinaryElementDef: ElementDef {
     const DEFAULT: Option<&'static [u8]>;
 }
+
+// Whether `path` is nested in `prefix`, or is the same path
+fn path_within(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('\\'))
+}
+
+/**
+Whether elements with the schema path `path` can occur (at any depth) within the master element
+with the schema path `master_path`, with both paths in the syntax of `ElementDef::PATH`.
+
+Global elements (e.g., `\(-\)Void`) can occur within any master element nested in the path
+preceding their global placeholder; their level bounds aren't checked.
+
+**/
+pub fn path_contains(master_path: &str, path: &str) -> bool {
+    match path.find("\\(") {
+        Some(placeholder) => {
+            let scope = &path[..placeholder];
+            path_within(master_path, scope) || path_within(scope, master_path)
+        }
+        None => path.len() > master_path.len() && path_within(path, master_path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(r"\Files", r"\Files\File\FileName", true)]
+    #[case(r"\Files\File", r"\Files\File\FileName", true)]
+    #[case(r"\Files\File", r"\Files\File", false)]
+    #[case(r"\Files\File\FileName", r"\Files\File", false)]
+    #[case(r"\Files\Fil", r"\Files\File\FileName", false)]
+    #[case(
+        r"\Segment\Tags\Tag\+SimpleTag",
+        r"\Segment\Tags\Tag\+SimpleTag\TagName",
+        true
+    )]
+    #[case(r"\Files\File", r"\(-\)Void", true)]
+    #[case(r"\Segment", r"\Segment\Cluster\(1-\)CRC-32", true)]
+    #[case(r"\Segment\Cluster\BlockGroup", r"\Segment\Cluster\(1-\)CRC-32", true)]
+    #[case(r"\EBML", r"\Segment\Cluster\(1-\)CRC-32", false)]
+    fn path_contains_cases(
+        #[case] master_path: &str,
+        #[case] path: &str,
+        #[case] expt_result: bool,
+    ) {
+        assert_eq!(path_contains(master_path, path), expt_result);
+    }
+
+    #[rstest]
+    #[case(Range::IsExactly(1), 1, true)]
+    #[case(Range::IsExactly(1), 2, false)]
+    #[case(Range::Excludes(0), 0, false)]
+    #[case(Range::Excludes(0), 1, true)]
+    #[case(Range::IsWithin(Bound::Included(1), Bound::Excluded(3)), 1, true)]
+    #[case(Range::IsWithin(Bound::Included(1), Bound::Excluded(3)), 3, false)]
+    #[case(Range::IsWithin(Bound::Excluded(1), Bound::Unbounded), 1, false)]
+    #[case(Range::IsWithin(Bound::Unbounded, Bound::Included(3)), 3, true)]
+    fn range_contains(#[case] range: Range<i64>, #[case] value: i64, #[case] expt_result: bool) {
+        assert_eq!(range.contains(&value), expt_result);
+    }
+}
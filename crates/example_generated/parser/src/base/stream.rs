@@ -16,8 +16,11 @@ pub mod parse {
         error::ParseError, Err, IResult, InputIter, InputLength, Needed, Slice, ToUsize,
     };
 
-    fn take_rem<I, E: ParseError<(I, usize)>>(
-    ) -> impl Fn((I, usize)) -> IResult<(I, usize), (u8, usize), E>
+    // a bit-level input: the byte input & the bit offset into its first byte
+    type BitInput<I> = (I, usize);
+
+    fn take_rem<I, E: ParseError<BitInput<I>>>(
+    ) -> impl Fn(BitInput<I>) -> IResult<BitInput<I>, (u8, usize), E>
     where
         I: Slice<RangeFrom<usize>> + InputIter<Item = u8> + InputLength,
     {
@@ -88,7 +91,6 @@ pub mod parse {
         };
     }
 
-    make_vlen_parser!(vlen_to_u32, u32);
     make_vlen_parser!(vlen_to_u64, u64);
 
     pub fn element_id(input: &[u8]) -> IResult<&[u8], u32, ()> {
@@ -104,14 +106,15 @@ pub mod parse {
         let result = u32::from_be_bytes(buffer);
 
         let result_data = result ^ (1u32 << (7 * bytelen));
-        if result_data == 0 || result_data.count_ones() == 7 * (bytelen as u32) {
-            // if all non-length bits are 0's or 1's
+        if result_data.count_ones() == 7 * (bytelen as u32) {
+            // if all non-length bits are 1's
             // corner-case: reserved ID's
             return Err(nom::Err::Error(()));
         }
-        let sig_bits = 8 * size_of::<u32>() - ((result_data + 1).leading_zeros() as usize);
+        let sig_bits = (u32::BITS - (result_data + 1).leading_zeros()) as usize;
         if sig_bits <= 7 * bytelen_m1 {
-            // element ID's must use the smallest representation possible
+            // element ID's must use the smallest representation possible (which also rejects wider
+            // ID's whose non-length bits are all 0's; 0x80 is kept for Matroska's ChapterDisplay)
             return Err(nom::Err::Error(()));
         }
 
@@ -329,14 +332,15 @@ pub mod parse {
         #[rstest(source, expt_result,
             case(&[0x40, 0x7F, 0xFF], (&source[2..], 0x407F)),
             case(&[0xDF, 0xFF], (&source[1..], 0xDF)),
+            case(&[0x80, 0xFF], (&source[1..], 0x80)),
         )]
         fn test_element_id(source: &'static [u8], expt_result: (&'static [u8], u32)) {
             assert_eq!(element_id(source), Ok(expt_result));
         }
 
         #[rstest(source,
-            case(&[0x80]),
             case(&[0xFF]),
+            case(&[0x40, 0x00]),
             case(&[0x40, 0x7E]),
             case(&[0x7F, 0xFF]),
             case(&[0x20, 0x3F, 0xFE]),
@@ -464,7 +468,7 @@ pub mod serialize {
         min_length: Option<usize>,
         max_length: Option<usize>,
     ) -> IResult<&mut [u8], usize, ()> {
-        let bitlen = 8 * size_of::<u64>() - value.leading_zeros() as usize;
+        let bitlen = (u64::BITS - value.leading_zeros()) as usize;
         let mut vint_len = bitlen.saturating_sub(1) / 7 + 1;
 
         if let Some(length) = min_length {
@@ -497,7 +501,7 @@ pub mod serialize {
         let value = value.get();
 
         let bytelen = match value {
-            0x81..=0xFE => 1,
+            0x80..=0xFE => 1,
             0x407F..=0x7FFE => 2,
             0x203FFF..=0x3FFFFE => 3,
             0x101FFFFF..=0x1FFFFFFE => 4,
@@ -534,6 +538,49 @@ pub mod serialize {
         }
     }
 
+    /**
+    How wide the data size VINTs of written elements are, either for all of them or per element.
+
+    Element IDs are always written in their canonical (shortest) encoding, as parsers reject any
+    other: an ID whose value isn't canonical fails to serialize, whatever the size width.
+
+    **/
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum SizeWidth {
+        // the canonical (shortest) encoding
+        #[default]
+        Minimal,
+        // exactly this many bytes (1-8), e.g. 8 bytes to backpatch sizes after writing the data
+        Fixed(usize),
+        // the shortest encoding plus this many extra bytes (up to 8 in total), to allow later growth
+        Reserved(usize),
+    }
+
+    // Writes an element ID & data size (`None` -> unknown), with the size as wide as given
+    pub fn element_header(
+        output: &mut [u8],
+        id: NonZeroU32,
+        len: Option<u64>,
+        width: SizeWidth,
+    ) -> IResult<&mut [u8], usize, ()> {
+        let (_, id_len) = element_id(output, id)?;
+        let bytelen = match width {
+            SizeWidth::Minimal => None,
+            SizeWidth::Fixed(bytelen) => Some(bytelen),
+            SizeWidth::Reserved(extra) => {
+                let (_, min_bytelen) = element_len(&mut [0u8; 8], len, None)?;
+                Some((min_bytelen + extra).min(8))
+            }
+        };
+        let (_, len_len) = element_len(&mut output[id_len..], len, bytelen)?;
+        // fixed widths can't be widened to fit the data size
+        if matches!(width, SizeWidth::Fixed(bytelen) if bytelen != len_len) {
+            return Err(nom::Err::Error(()));
+        }
+
+        Ok((&mut output[id_len + len_len..], id_len + len_len))
+    }
+
     pub fn uint(output: &mut [u8], value: u64, length: usize) -> IResult<&mut [u8], (), ()> {
         let byte_offset = size_of::<u64>()
             .checked_sub(length)
@@ -633,6 +680,7 @@ pub mod serialize {
         }
 
         #[rstest(value, expt_output,
+            case(0x80, &[0x80, 0x00, 0x00, 0x00, 0x00]),
             case(0x81, &[0x81, 0x00, 0x00, 0x00, 0x00]),
             case(0x6345, &[0x63, 0x45, 0x00, 0x00, 0x00]),
             case(0x407F, &[0x40, 0x7F, 0x00, 0x00, 0x00]),
@@ -662,6 +710,47 @@ pub mod serialize {
             assert_eq!(output, expt_output);
         }
 
+        #[rstest(len, width, expt_output,
+            case(Some(0x2F), SizeWidth::Minimal, &[0x42, 0x86, 0xAF]),
+            case(Some(0x2F), SizeWidth::Fixed(2), &[0x42, 0x86, 0x40, 0x2F]),
+            case(Some(0x2F), SizeWidth::Fixed(8), &[0x42, 0x86, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2F]),
+            case(Some(0x2F), SizeWidth::Reserved(1), &[0x42, 0x86, 0x40, 0x2F]),
+            case(Some(0x2F), SizeWidth::Reserved(9), &[0x42, 0x86, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2F]),
+            case(None, SizeWidth::Fixed(2), &[0x42, 0x86, 0x7F, 0xFF]),
+        )]
+        fn test_element_header(len: Option<u64>, width: SizeWidth, expt_output: &[u8]) {
+            let mut output = [0x00u8; 12];
+            let result = element_header(
+                &mut output[..],
+                NonZeroU32::new(0x4286).unwrap(),
+                len,
+                width,
+            );
+            assert_eq!(
+                result.map(|(_, header_len)| header_len),
+                Ok(expt_output.len())
+            );
+            assert_eq!(output[..expt_output.len()], *expt_output);
+        }
+
+        #[rstest(id, len, width,
+            case(0x4286, 0x80, SizeWidth::Fixed(1)), // too large for a 1-byte VINT
+            case(0x4286, 0, SizeWidth::Fixed(0)),
+            case(0x4286, 0, SizeWidth::Fixed(9)),
+            case(0x4001, 0, SizeWidth::Minimal), // a non-canonical ID
+            case(0x4001, 0, SizeWidth::Reserved(1)),
+        )]
+        fn test_element_header_err(id: u32, len: u64, width: SizeWidth) {
+            let mut output = [0x00u8; 12];
+            let result = element_header(
+                &mut output[..],
+                NonZeroU32::new(id).unwrap(),
+                Some(len),
+                width,
+            );
+            assert!(result.is_err());
+        }
+
         #[rstest(value, length, expt_output,
             case(0x01, 1, &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
             case(0x01, 2, &[0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
@@ -735,17 +824,17 @@ mod tests {
 
     proptest! {
         #[test]
-        fn write_read_eq_element_id_1byte(value in 0x81u32..0xFE) {
+        fn write_read_eq_element_id_1byte(value in 0x80u32..0xFF) {
             let mut buffer = [0x00u8; 5];
 
             let (_output, _bytelen) = serialize::element_id(
                 &mut buffer[..],
                 NonZeroU32::new(value).expect("`NonZeroU32::new` failed"),
             ).expect("failed to write value");
-            let (_input, result) = parse::element_id(&buffer[..]).expect(&format!(
+            let (_input, result) = parse::element_id(&buffer[..]).unwrap_or_else(|_| panic!(
                 "failed to read value from [{}, {}, {}, {}, {}]",
                 buffer[0], buffer[1], buffer[2], buffer[3], buffer[4],
-            )[..]);
+            ));
 
             prop_assert_eq!(result, value);
         }
@@ -758,10 +847,10 @@ mod tests {
                 &mut buffer[..],
                 NonZeroU32::new(value).expect("`NonZeroU32::new` failed"),
             ).expect("failed to write value");
-            let (_input, result) = parse::element_id(&buffer[..]).expect(&format!(
+            let (_input, result) = parse::element_id(&buffer[..]).unwrap_or_else(|_| panic!(
                 "failed to read value from [{}, {}, {}, {}, {}]",
                 buffer[0], buffer[1], buffer[2], buffer[3], buffer[4],
-            )[..]);
+            ));
 
             prop_assert_eq!(result, value);
         }
@@ -774,10 +863,10 @@ mod tests {
                 &mut buffer[..],
                 NonZeroU32::new(value).expect("`NonZeroU32::new` failed"),
             ).expect("failed to write value");
-            let (_input, result) = parse::element_id(&buffer[..]).expect(&format!(
+            let (_input, result) = parse::element_id(&buffer[..]).unwrap_or_else(|_| panic!(
                 "failed to read value from [{}, {}, {}, {}, {}]",
                 buffer[0], buffer[1], buffer[2], buffer[3], buffer[4],
-            )[..]);
+            ));
 
             prop_assert_eq!(result, value);
         }
@@ -790,10 +879,10 @@ mod tests {
                 &mut buffer[..],
                 NonZeroU32::new(value).expect("`NonZeroU32::new` failed"),
             ).expect("failed to write value");
-            let (_input, result) = parse::element_id(&buffer[..]).expect(&format!(
+            let (_input, result) = parse::element_id(&buffer[..]).unwrap_or_else(|_| panic!(
                 "failed to read value from [{}, {}, {}, {}, {}]",
                 buffer[0], buffer[1], buffer[2], buffer[3], buffer[4],
-            )[..]);
+            ));
 
             prop_assert_eq!(result, value);
         }
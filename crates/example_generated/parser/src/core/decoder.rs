@@ -0,0 +1,240 @@
+
+#[allow(unused_imports)]
+use crate::base::date::Date;
+use crate::base::element_defs::ElementDef;
+use crate::base::events::{scan_headers, Event, Value};
+#[allow(unused_imports)]
+use crate::base::parser::{NextStateNavigation, StateDataParser, StateError};
+use crate::base::stream::stream_diff;
+use crate::core::{element_defs, parser};
+
+use std::collections::vec_deque::{Drain, VecDeque};
+
+// the IDs of the elements skipped over by the states, under any parent
+const SKIPPED_IDS: &[u32] = &[];
+
+/**
+A push-based decoder of a document that arrives in chunks of any size, e.g. from network packets.
+
+Pushed bytes are buffered until they complete the next element header (or leaf element), at which
+point its `Start`, `Value` & `End` events are queued, in document order. An incomplete header or
+leaf element never loses the decoder's state; decoding resumes once the next chunk is pushed.
+
+Leaf elements are decoded as a whole, so their data is buffered in full. An unknown-sized element
+ends at the first element that isn't its child, or at the end of the stream, as marked by `finish`.
+
+**/
+#[derive(Debug)]
+pub struct Decoder {
+    state: parser::States,
+    buffer: Vec<u8>,         // the pushed bytes that aren't decoded yet
+    offset: u64,             // the stream position of the first buffered byte
+    open: Vec<(u32, usize)>, // the IDs of the started elements, & the path lengths before them
+    path: String,
+    pending: VecDeque<Event>,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self {
+            state: parser::_DocumentState.into(),
+            buffer: Vec::new(),
+            offset: 0,
+            open: Vec::new(),
+            path: String::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Pushes the next chunk of the stream, decoding as many elements as it completes. On an
+    /// error, the decoder stays at the last element that was decoded.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), StateError> {
+        self.buffer.extend_from_slice(chunk);
+        let buffer = std::mem::take(&mut self.buffer);
+
+        let mut stream = &buffer[..];
+        let result = loop {
+            // steps from a copy of the state, so that it's kept if the step is incomplete
+            match self.step(self.state.clone(), stream) {
+                Ok((next_stream, next_state)) => {
+                    self.offset += stream_diff(stream, next_stream) as u64;
+                    self.state = next_state;
+                    stream = next_stream;
+                }
+                Err(nom::Err::Incomplete(_)) => break Ok(()),
+                Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => break Err(err),
+            }
+        };
+        let consumed = buffer.len() - stream.len();
+
+        self.buffer = buffer;
+        self.buffer.drain(..consumed);
+        result
+    }
+
+    /// Removes & returns the queued events, in document order.
+    pub fn events(&mut self) -> Drain<'_, Event> {
+        self.pending.drain(..)
+    }
+
+    /// Whether the pushed bytes end between top-level elements, i.e. form a complete document.
+    pub fn is_complete(&self) -> bool {
+        matches!(self.state, parser::States::_Document(_)) && self.buffer.is_empty()
+    }
+
+    /// Ends the stream, & with it any open unknown-sized elements (queueing their `End` events).
+    /// Returns whether the pushed bytes then form a complete document.
+    pub fn finish(&mut self) -> bool {
+        while self.buffer.is_empty() {
+            match Self::end_of_stream(self.state.clone()) {
+                Some(next_state) => {
+                    self.end();
+                    self.state = next_state;
+                }
+                None => break,
+            }
+        }
+        self.is_complete()
+    }
+
+    // Ends the unknown-sized element of the state at the end of the stream, moving on to its parent
+    fn end_of_stream(state: parser::States) -> Option<parser::States> {
+        match state {
+            parser::States::_Document(_) => None,
+            parser::States::Data(s) => s.end_of_stream().ok().map(Into::into),
+            parser::States::File(s) => s.end_of_stream().ok().map(Into::into),
+            parser::States::FileName(s) => s.end_of_stream().ok().map(Into::into),
+            parser::States::Files(s) => s.end_of_stream().ok().map(Into::into),
+            parser::States::MimeType(s) => s.end_of_stream().ok().map(Into::into),
+            parser::States::ModificationTimestamp(s) => s.end_of_stream().ok().map(Into::into),
+            parser::States::Void(s) => s.end_of_stream().ok().map(Into::into),
+        }
+    }
+
+    // Moves the state by a single step over the buffered stream, queueing the resulting events
+    fn step<'a>(
+        &mut self,
+        state: parser::States,
+        stream: &'a [u8],
+    ) -> nom::IResult<&'a [u8], parser::States, StateError> {
+        Ok(match state {
+            parser::States::_Document(s) => {
+                let headers = scan_headers(stream, self.offset, SKIPPED_IDS);
+                let (next_stream, next) = s.next(stream)?;
+                let next = next.into();
+                let data_offset = self.offset + stream_diff(stream, next_stream) as u64;
+                self.start(&next, &headers, data_offset);
+                (next_stream, next)
+            }
+            parser::States::Data(s) => {
+                let (next_stream, (parent, value)): (_, (_, &[u8])) = s.read(stream)?;
+                self.value(Value::Binary(value.to_vec()));
+                self.end();
+                (next_stream, parent.into())
+            }
+            parser::States::File(s) => {
+                let headers = scan_headers(stream, self.offset, SKIPPED_IDS);
+                match s.next(stream)? {
+                    (next_stream, parser::FileNextStates::Parent(parent)) => {
+                        self.end();
+                        (next_stream, parent.into())
+                    }
+                    (next_stream, next) => {
+                        let next = next.into();
+                        let data_offset = self.offset + stream_diff(stream, next_stream) as u64;
+                        self.start(&next, &headers, data_offset);
+                        (next_stream, next)
+                    }
+                }
+            }
+            parser::States::FileName(s) => {
+                let (next_stream, (parent, value)): (_, (_, &str)) = s.read(stream)?;
+                self.value(Value::Utf8(value.to_owned()));
+                self.end();
+                (next_stream, parent.into())
+            }
+            parser::States::Files(s) => {
+                let headers = scan_headers(stream, self.offset, SKIPPED_IDS);
+                match s.next(stream)? {
+                    (next_stream, parser::FilesNextStates::Parent(parent)) => {
+                        self.end();
+                        (next_stream, parent.into())
+                    }
+                    (next_stream, next) => {
+                        let next = next.into();
+                        let data_offset = self.offset + stream_diff(stream, next_stream) as u64;
+                        self.start(&next, &headers, data_offset);
+                        (next_stream, next)
+                    }
+                }
+            }
+            parser::States::MimeType(s) => {
+                let (next_stream, (parent, value)): (_, (_, &str)) = s.read(stream)?;
+                self.value(Value::String(value.to_owned()));
+                self.end();
+                (next_stream, parent.into())
+            }
+            parser::States::ModificationTimestamp(s) => {
+                let (next_stream, (parent, value)): (_, (_, Date)) = s.read(stream)?;
+                self.value(Value::Date(value));
+                self.end();
+                (next_stream, parent.into())
+            }
+            parser::States::Void(s) => {
+                let (next_stream, (parent, value)): (_, (_, &[u8])) = s.read(stream)?;
+                self.value(Value::Binary(value.to_vec()));
+                self.end();
+                (next_stream, parent.into())
+            }
+        })
+    }
+
+    // Starts the element that the state just descended into, given the headers scanned beforehand
+    fn start(&mut self, state: &parser::States, headers: &[(u64, u64)], data_offset: u64) {
+        let (id, name, size) = match state {
+            parser::States::_Document(_) => unreachable!("the document isn't a child element"),
+            parser::States::Data(s) => (element_defs::DataDef::ID, "Data", s.size()),
+            parser::States::File(s) => (element_defs::FileDef::ID, "File", s.size()),
+            parser::States::FileName(s) => (element_defs::FileNameDef::ID, "FileName", s.size()),
+            parser::States::Files(s) => (element_defs::FilesDef::ID, "Files", s.size()),
+            parser::States::MimeType(s) => (element_defs::MimeTypeDef::ID, "MimeType", s.size()),
+            parser::States::ModificationTimestamp(s) => (element_defs::ModificationTimestampDef::ID, "ModificationTimestamp", s.size()),
+            parser::States::Void(s) => (element_defs::VoidDef::ID, "Void", s.size()),
+        };
+        let offset = headers
+            .iter()
+            .find(|(_, header_end)| *header_end == data_offset)
+            .map(|(header_start, _)| *header_start)
+            .expect("the states only descend into scanned headers");
+
+        self.open.push((id, self.path.len()));
+        self.path.push('\\');
+        self.path.push_str(name);
+        self.pending.push_back(Event::Start {
+            id,
+            path: self.path.clone(),
+            offset,
+            size: size.map(|size| size as u64),
+        });
+    }
+
+    fn value(&mut self, value: Value) {
+        let (id, _) = *self
+            .open
+            .last()
+            .expect("values are read from started elements");
+        self.pending.push_back(Event::Value { id, value });
+    }
+
+    fn end(&mut self) {
+        let (id, path_len) = self.open.pop().expect("only started elements are ended");
+        self.path.truncate(path_len);
+        self.pending.push_back(Event::End { id });
+    }
+}
@@ -0,0 +1,193 @@
+
+#[allow(unused_imports)]
+use crate::base::element_defs::{
+    DateElementDef as _, ElementDef as _, FloatElementDef as _, IntElementDef as _,
+    UIntElementDef as _,
+};
+use crate::base::{events, owned as base};
+use crate::core::element_defs;
+
+/**
+A document's top-level elements. Reads all of the `Start`, `Value` & `End` events of a document,
+e.g. as yielded by the `Events` iterator or `Decoder`.
+
+**/
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct _Document {
+    pub void: Vec<Vec<u8>>, // Void
+    pub files: Vec<Files>, // Files
+}
+
+impl _Document {
+    pub fn from_events<I: IntoIterator<Item = events::Event>>(
+        events: I,
+    ) -> Result<Self, base::OwnedError> {
+        let iter = &mut events.into_iter();
+        let mut owned = Self::default();
+        while let Some(event) = iter.next() {
+            match event {
+                events::Event::Start { id: element_defs::VoidDef::ID, .. } => owned.void.push(base::read_value(iter, element_defs::VoidDef::ID)?),
+                events::Event::Start { id: element_defs::FilesDef::ID, .. } => owned.files.push(Files::from_events(iter)?),
+                event => return Err(base::OwnedError::Unexpected(event)),
+            }
+        }
+        Ok(owned)
+    }
+}
+
+impl _Document {
+    /// Appends the element's children to `buffer`.
+    pub fn write_data(&self, buffer: &mut Vec<u8>) -> Result<(), base::WriteError> {
+        for value in self.void.iter() {
+            base::write_element(buffer, element_defs::VoidDef::ID, Some(value.as_slice()))?;
+        }
+        for value in self.files.iter() {
+            let mut data = Vec::new();
+            value.write_data(&mut data)?;
+            base::write_element(buffer, element_defs::FilesDef::ID, Some(&data))?;
+        }
+        Ok(())
+    }
+}
+
+impl _Document {
+    pub fn write<W: std::io::Write>(&self, writer: &mut W) -> Result<(), base::WriteError> {
+        let mut buffer = Vec::new();
+        self.write_data(&mut buffer)?;
+        writer.write_all(&buffer)?;
+        Ok(())
+    }
+}
+
+impl _Document {
+    /// Checks the occurrences & ranges of the element's children against the schema,
+    /// recursively.
+    pub fn validate(&self) -> Result<(), base::ValidationError> {
+        base::check_occurrences::<element_defs::VoidDef>(self.void.len(), false)?;
+        base::check_occurrences::<element_defs::FilesDef>(self.files.len(), false)?;
+        for value in self.files.iter() {
+            value.validate()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct File {
+    pub void: Vec<Vec<u8>>, // Void
+    pub mime_type: Vec<String>, // MimeType
+    pub modification_timestamp: Vec<crate::base::date::Date>, // ModificationTimestamp
+    pub data: Vec<Vec<u8>>, // Data
+    pub file_name: Vec<String>, // FileName
+}
+
+impl File {
+    /// Reads the element from the events following its `Start` event, up to & including its
+    /// `End` event.
+    pub fn from_events<I: Iterator<Item = events::Event>>(
+        iter: &mut I,
+    ) -> Result<Self, base::OwnedError> {
+        let mut owned = Self::default();
+        loop {
+            match iter.next().ok_or(base::OwnedError::Incomplete)? {
+                events::Event::End { id: element_defs::FileDef::ID } => return Ok(owned),
+                events::Event::Start { id: element_defs::VoidDef::ID, .. } => owned.void.push(base::read_value(iter, element_defs::VoidDef::ID)?),
+                events::Event::Start { id: element_defs::MimeTypeDef::ID, .. } => owned.mime_type.push(base::read_value(iter, element_defs::MimeTypeDef::ID)?),
+                events::Event::Start { id: element_defs::ModificationTimestampDef::ID, .. } => owned.modification_timestamp.push(base::read_value(iter, element_defs::ModificationTimestampDef::ID)?),
+                events::Event::Start { id: element_defs::DataDef::ID, .. } => owned.data.push(base::read_value(iter, element_defs::DataDef::ID)?),
+                events::Event::Start { id: element_defs::FileNameDef::ID, .. } => owned.file_name.push(base::read_value(iter, element_defs::FileNameDef::ID)?),
+                event => return Err(base::OwnedError::Unexpected(event)),
+            }
+        }
+    }
+}
+
+impl File {
+    /// Appends the element's children to `buffer`.
+    pub fn write_data(&self, buffer: &mut Vec<u8>) -> Result<(), base::WriteError> {
+        for value in self.void.iter() {
+            base::write_element(buffer, element_defs::VoidDef::ID, Some(value.as_slice()))?;
+        }
+        for value in self.mime_type.iter() {
+            base::write_element(buffer, element_defs::MimeTypeDef::ID, base::ascii_data(value))?;
+        }
+        for value in self.modification_timestamp.iter() {
+            base::write_element(buffer, element_defs::ModificationTimestampDef::ID, base::date_data(*value).as_deref())?;
+        }
+        for value in self.data.iter() {
+            base::write_element(buffer, element_defs::DataDef::ID, Some(value.as_slice()))?;
+        }
+        for value in self.file_name.iter() {
+            base::write_element(buffer, element_defs::FileNameDef::ID, Some(value.as_bytes()))?;
+        }
+        Ok(())
+    }
+}
+
+impl File {
+    /// Checks the occurrences & ranges of the element's children against the schema,
+    /// recursively.
+    pub fn validate(&self) -> Result<(), base::ValidationError> {
+        base::check_occurrences::<element_defs::VoidDef>(self.void.len(), false)?;
+        base::check_occurrences::<element_defs::MimeTypeDef>(self.mime_type.len(), false)?;
+        base::check_occurrences::<element_defs::ModificationTimestampDef>(self.modification_timestamp.len(), false)?;
+        for value in self.modification_timestamp.iter() {
+            base::check_range(&element_defs::ModificationTimestampDef::RANGE, value, element_defs::ModificationTimestampDef::ID)?;
+        }
+        base::check_occurrences::<element_defs::DataDef>(self.data.len(), false)?;
+        base::check_occurrences::<element_defs::FileNameDef>(self.file_name.len(), false)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Files {
+    pub void: Vec<Vec<u8>>, // Void
+    pub file: Vec<File>, // File
+}
+
+impl Files {
+    /// Reads the element from the events following its `Start` event, up to & including its
+    /// `End` event.
+    pub fn from_events<I: Iterator<Item = events::Event>>(
+        iter: &mut I,
+    ) -> Result<Self, base::OwnedError> {
+        let mut owned = Self::default();
+        loop {
+            match iter.next().ok_or(base::OwnedError::Incomplete)? {
+                events::Event::End { id: element_defs::FilesDef::ID } => return Ok(owned),
+                events::Event::Start { id: element_defs::VoidDef::ID, .. } => owned.void.push(base::read_value(iter, element_defs::VoidDef::ID)?),
+                events::Event::Start { id: element_defs::FileDef::ID, .. } => owned.file.push(File::from_events(iter)?),
+                event => return Err(base::OwnedError::Unexpected(event)),
+            }
+        }
+    }
+}
+
+impl Files {
+    /// Appends the element's children to `buffer`.
+    pub fn write_data(&self, buffer: &mut Vec<u8>) -> Result<(), base::WriteError> {
+        for value in self.void.iter() {
+            base::write_element(buffer, element_defs::VoidDef::ID, Some(value.as_slice()))?;
+        }
+        for value in self.file.iter() {
+            let mut data = Vec::new();
+            value.write_data(&mut data)?;
+            base::write_element(buffer, element_defs::FileDef::ID, Some(&data))?;
+        }
+        Ok(())
+    }
+}
+
+impl Files {
+    /// Checks the occurrences & ranges of the element's children against the schema,
+    /// recursively.
+    pub fn validate(&self) -> Result<(), base::ValidationError> {
+        base::check_occurrences::<element_defs::VoidDef>(self.void.len(), false)?;
+        base::check_occurrences::<element_defs::FileDef>(self.file.len(), false)?;
+        for value in self.file.iter() {
+            value.validate()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+
+#[allow(unused_imports)]
+use crate::base::date::Date;
+use crate::base::element_defs::{path_contains, ElementDef};
+use crate::base::events::Value;
+#[allow(unused_imports)]
+use crate::base::parser::{
+    NextReaderNavigation, ReaderDataParser, ReaderError, SkipReaderNavigation,
+};
+use crate::core::{element_defs, parser};
+
+use std::io::BufRead;
+
+/**
+An iterator over the decoded values of the leaf elements with a given schema path, in document
+order.
+
+Paths use the syntax of `ElementDef::PATH` (e.g., `\Files\File\FileName`); master elements whose
+path can't contain the selected elements are skipped over without reading their children. Paths
+of master elements, or paths not in the schema, select nothing. Iteration ends at the end of the
+document, or after the first error.
+
+**/
+pub struct Select<'a, R> {
+    reader: Option<parser::Readers<R>>,
+    entering: bool, // whether the reader just descended into its element
+    path: &'a str,
+}
+
+/// Selects the values of the given element, e.g. `select::<element_defs::FileNameDef, _>(reader)`.
+pub fn select<E: ElementDef, R: BufRead>(reader: parser::_DocumentReader<R>) -> Select<'static, R> {
+    select_path(reader, E::PATH)
+}
+
+/// Selects the values of the elements with the given schema path.
+pub fn select_path<R: BufRead>(reader: parser::_DocumentReader<R>, path: &str) -> Select<'_, R> {
+    Select {
+        reader: Some(reader.into()),
+        entering: true,
+        path,
+    }
+}
+
+impl<'a, R: BufRead> Select<'a, R> {
+    // Moves the reader by a single step, reading the value of a selected element
+    #[allow(clippy::type_complexity)]
+    fn step(
+        &mut self,
+        reader: parser::Readers<R>,
+    ) -> Result<(Option<parser::Readers<R>>, Option<Value>), ReaderError> {
+        let (next_reader, next_entering, value) = match reader {
+            parser::Readers::_Document(mut r) => {
+                if r.reader.fill_buf()?.is_empty() {
+                    return Ok((None, None));
+                }
+                (r.next()?.into(), true, None)
+            }
+            parser::Readers::Data(mut r) => {
+                let value = if element_defs::DataDef::PATH == self.path {
+                    let value: &[u8] = r.read()?;
+                    Some(Value::Binary(value.to_vec()))
+                } else {
+                    None
+                };
+                (r.next()?.into(), false, value)
+            }
+            parser::Readers::File(r) => {
+                if self.entering && !path_contains(element_defs::FileDef::PATH, self.path) {
+                    (r.skip()?.into(), false, None)
+                } else {
+                    match r.next()? {
+                        parser::FileNextReaders::Parent(r_parent) => {
+                            (r_parent.into(), false, None)
+                        }
+                        r_next => (r_next.into(), true, None),
+                    }
+                }
+            }
+            parser::Readers::FileName(mut r) => {
+                let value = if element_defs::FileNameDef::PATH == self.path {
+                    let value: &str = r.read()?;
+                    Some(Value::Utf8(value.to_owned()))
+                } else {
+                    None
+                };
+                (r.next()?.into(), false, value)
+            }
+            parser::Readers::Files(r) => {
+                if self.entering && !path_contains(element_defs::FilesDef::PATH, self.path) {
+                    (r.skip()?.into(), false, None)
+                } else {
+                    match r.next()? {
+                        parser::FilesNextReaders::Parent(r_parent) => {
+                            (r_parent.into(), false, None)
+                        }
+                        r_next => (r_next.into(), true, None),
+                    }
+                }
+            }
+            parser::Readers::MimeType(mut r) => {
+                let value = if element_defs::MimeTypeDef::PATH == self.path {
+                    let value: &str = r.read()?;
+                    Some(Value::String(value.to_owned()))
+                } else {
+                    None
+                };
+                (r.next()?.into(), false, value)
+            }
+            parser::Readers::ModificationTimestamp(mut r) => {
+                let value = if element_defs::ModificationTimestampDef::PATH == self.path {
+                    let value: Date = r.read()?;
+                    Some(Value::Date(value))
+                } else {
+                    None
+                };
+                (r.next()?.into(), false, value)
+            }
+            parser::Readers::Void(mut r) => {
+                let value = if element_defs::VoidDef::PATH == self.path {
+                    let value: &[u8] = r.read()?;
+                    Some(Value::Binary(value.to_vec()))
+                } else {
+                    None
+                };
+                (r.next()?.into(), false, value)
+            }
+        };
+        self.entering = next_entering;
+        Ok((Some(next_reader), value))
+    }
+}
+
+impl<'a, R: BufRead> Iterator for Select<'a, R> {
+    type Item = Result<Value, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let reader = self.reader.take()?;
+            match self.step(reader) {
+                Ok((next_reader, value)) => {
+                    self.reader = next_reader;
+                    if value.is_some() {
+                        return value.map(Ok);
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
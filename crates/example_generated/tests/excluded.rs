@@ -0,0 +1,131 @@
+#[allow(dead_code)]
+mod ebml {
+    // generated with `\Files\File\Data` blocklisted (see build.rs)
+    include!(concat!(env!("OUT_DIR"), "/filtered/ebml.rs"));
+}
+
+use ebml::base::events::{Event, Value};
+use ebml::base::parser::{NextReaderNavigation, ReaderDataParser, ReaderError};
+use ebml::core::{events, parser};
+
+use std::io::BufRead;
+
+// A file whose (excluded) data is larger than the reader buffers in these tests
+fn file_stream() -> Vec<u8> {
+    let mut stream = vec![
+        0x19, 0x46, 0x69, 0x6C, // Files element ID
+        0xF2, // Files length = 114
+        0x61, 0x46, // File element ID
+        0xEF, // File length = 111
+        0x46, 0x64, // Data element ID
+        0xE4, // Data length = 100
+    ];
+    stream.extend([0xAB; 100]);
+    stream.extend([
+        0x61, 0x4E, // FileName element ID
+        0x85, // FileName length = 5
+        0x61, 0x2e, 0x74, 0x78, 0x74, // FileName data = "a.txt"
+    ]);
+    stream
+}
+
+#[test]
+fn skip_excluded_unbuffered() {
+    let stream = file_stream();
+
+    // the reader buffer is smaller than the Data element
+    let reader = std::io::BufReader::with_capacity(16, &stream[..]);
+    let reader = match parser::_DocumentReader::new(reader).next().unwrap() {
+        parser::_DocumentNextReaders::Files(r) => r,
+        _ => panic!("expected the Files element"),
+    };
+    let reader = match reader.next().unwrap() {
+        parser::FilesNextReaders::File(r) => r,
+        _ => panic!("expected the File element"),
+    };
+
+    // the excluded Data element is skipped over
+    let mut reader = match reader.next().unwrap() {
+        parser::FileNextReaders::FileName(r) => r,
+        _ => panic!("expected the FileName element"),
+    };
+    assert_eq!(reader.read().unwrap(), "a.txt");
+
+    // ...& the document ends along with the Files element
+    let reader = match reader.next().unwrap().next().unwrap() {
+        parser::FileNextReaders::Parent(r) => r,
+        _ => panic!("expected the end of the File element"),
+    };
+    let mut reader = match reader.next().unwrap() {
+        parser::FilesNextReaders::Parent(r) => r,
+        _ => panic!("expected the end of the Files element"),
+    };
+    assert!(reader.reader.fill_buf().unwrap().is_empty());
+}
+
+#[test]
+fn events_skip_excluded_unbuffered() {
+    let stream = file_stream();
+    let reader = std::io::BufReader::with_capacity(16, &stream[..]);
+    let events = events::Events::new(parser::_DocumentReader::new(reader))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    // the FileName header follows the skipped Data element, which was never buffered in full
+    let start = |id: u32, path: &str, offset: u64, size: u64| Event::Start {
+        id,
+        path: path.to_string(),
+        offset,
+        size: Some(size),
+    };
+    assert_eq!(
+        events,
+        vec![
+            start(0x1946696C, r"\Files", 0, 114),
+            start(0x6146, r"\Files\File", 5, 111),
+            start(0x614E, r"\Files\File\FileName", 111, 5),
+            Event::Value {
+                id: 0x614E,
+                value: Value::Utf8("a.txt".to_string()),
+            },
+            Event::End { id: 0x614E },
+            Event::End { id: 0x6146 },
+            Event::End { id: 0x1946696C },
+        ]
+    );
+}
+
+#[test]
+fn try_next_excluded_retries() {
+    let stream = file_stream();
+
+    // the reader buffer is smaller than the Data element, & the stream arrives in parts
+    let arrived = std::io::Cursor::new(stream[..40].to_vec());
+    let reader = std::io::BufReader::with_capacity(16, arrived);
+    let reader = match parser::_DocumentReader::new(reader).next().unwrap() {
+        parser::_DocumentNextReaders::Files(r) => r,
+        _ => panic!("expected the Files element"),
+    };
+    let mut reader = match reader.next().unwrap() {
+        parser::FilesNextReaders::File(r) => r,
+        _ => panic!("expected the File element"),
+    };
+
+    // each retry resumes skipping over the Data element where the last one left off
+    let mut retries = 0;
+    let mut reader = loop {
+        reader = match reader.try_next() {
+            Ok(parser::FileNextReaders::FileName(r)) => break r,
+            Err((mut reader, ReaderError::Parse(nom::Err::Incomplete(_)))) => {
+                let arrived = reader.reader.get_mut().get_mut();
+                let len = arrived.len();
+                arrived.extend(&stream[len..stream.len().min(len + 40)]);
+                retries += 1;
+                reader
+            }
+            _ => panic!("expected the FileName element"),
+        };
+    };
+    assert_eq!(retries, 2);
+    assert_eq!(reader.read().unwrap(), "a.txt");
+}
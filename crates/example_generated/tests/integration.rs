@@ -1,7 +1,28 @@
+use iron_ebmlem_parser::base::codec::{CodecError, EventEncoder};
+use iron_ebmlem_parser::base::element_defs::ElementDef;
+use iron_ebmlem_parser::base::events::{Event, Value};
+use iron_ebmlem_parser::base::owned::{ValidationError, WriteError};
 use iron_ebmlem_parser::base::parser::{
-    NextReaderNavigation, ReaderDataParser, ReaderError, SkipReaderNavigation,
+    NextReaderNavigation, ReaderDataParser, ReaderError, SkipReaderNavigation, StateError, Visit,
 };
-use iron_ebmlem_parser::core::parser;
+use iron_ebmlem_parser::base::stream::serialize::SizeWidth;
+use iron_ebmlem_parser::core::element_defs::{FileNameDef, VoidDef};
+use iron_ebmlem_parser::core::{codec, decoder, events, owned, parser, select, visitor};
+
+use iron_ebmlem::convert::Converter;
+use iron_ebmlem::parser_gen::Builder;
+
+use futures::{SinkExt, TryStreamExt};
+use rstest::*;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+use std::io::{BufRead, ErrorKind, Read};
+
+#[allow(dead_code)]
+mod ebml {
+    include!(concat!(env!("OUT_DIR"), "/ebml.rs"));
+}
 
 const BYTE_STREAM: [u8; 150] = [
     // ### Files 1 ###
@@ -66,6 +87,49 @@ const BYTE_STREAM: [u8; 150] = [
     0x01, 0x02, 0x03, 0x04, // Data data
 ];
 
+// A buffered reader over a stream that arrives in parts, whose first read is interrupted
+struct ArrivingReader<'a> {
+    stream: &'a [u8],
+    available: usize, // the length of the stream that arrived so far
+    position: usize,
+    interrupted: bool,
+}
+
+impl<'a> ArrivingReader<'a> {
+    fn new(stream: &'a [u8], available: usize) -> Self {
+        Self {
+            stream,
+            available,
+            position: 0,
+            interrupted: false,
+        }
+    }
+}
+
+impl Read for ArrivingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let stream = self.fill_buf()?;
+        let len = stream.len().min(buf.len());
+        buf[..len].copy_from_slice(&stream[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for ArrivingReader<'_> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if !self.interrupted {
+            self.interrupted = true;
+            return Err(ErrorKind::Interrupted.into());
+        }
+        Ok(&self.stream[self.position..self.available])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position += amt;
+    }
+}
+
 #[test]
 fn basic_traversal() {
     let mut reader: parser::Readers<_> = parser::_DocumentReader::new(&BYTE_STREAM[..]).into();
@@ -110,6 +174,98 @@ fn basic_traversal() {
     );
 }
 
+#[test]
+fn try_next_retries() {
+    let reader = parser::_DocumentReader::new(ArrivingReader::new(&BYTE_STREAM, 3));
+
+    let reader = match reader.try_next() {
+        Err((reader, ReaderError::Io(err))) if err.kind() == ErrorKind::Interrupted => reader,
+        _ => panic!("expected an interrupted read"),
+    };
+    // the Files header is incomplete
+    let mut reader = match reader.try_next() {
+        Err((reader, ReaderError::Parse(nom::Err::Incomplete(_)))) => reader,
+        _ => panic!("expected incomplete data"),
+    };
+    assert_eq!(reader.reader.position, 0);
+
+    reader.reader.available = BYTE_STREAM.len();
+    match reader.try_next() {
+        Ok(parser::_DocumentNextReaders::Files(reader)) => assert_eq!(reader.reader.position, 5),
+        _ => panic!("expected the Files element"),
+    }
+}
+
+#[test]
+fn try_skip_retries() {
+    // up to the first byte of the first FileName's data
+    let mut reader = ArrivingReader::new(&BYTE_STREAM, 12);
+    reader.interrupted = true;
+    let reader = match parser::_DocumentReader::new(reader).next().unwrap() {
+        parser::_DocumentNextReaders::Files(r) => r,
+        _ => panic!("expected the Files element"),
+    };
+    let reader = match reader.next().unwrap() {
+        parser::FilesNextReaders::File(r) => r,
+        _ => panic!("expected the File element"),
+    };
+    let reader = match reader.next().unwrap() {
+        parser::FileNextReaders::FileName(r) => r,
+        _ => panic!("expected the FileName element"),
+    };
+
+    let mut reader = match reader.try_skip() {
+        Err((reader, ReaderError::Parse(nom::Err::Incomplete(_)))) => reader,
+        _ => panic!("expected incomplete data"),
+    };
+    reader.reader.available = BYTE_STREAM.len();
+    let reader = match reader.try_skip() {
+        Ok(reader) => reader,
+        Err(_) => panic!("expected the File element"),
+    };
+    assert!(matches!(
+        reader.try_next(),
+        Ok(parser::FileNextReaders::MimeType(_))
+    ));
+}
+
+#[test]
+fn try_skip_retries_unbuffered() {
+    // the reader buffer is smaller than the Files element, which arrives in parts
+    let mut reader = ArrivingReader::new(&BYTE_STREAM, 20);
+    reader.interrupted = true;
+    let reader = std::io::BufReader::with_capacity(8, reader);
+    let mut reader = match parser::_DocumentReader::new(reader).next().unwrap() {
+        parser::_DocumentNextReaders::Files(r) => r,
+        _ => panic!("expected the Files element"),
+    };
+
+    // each retry resumes the skip where the last one left off
+    let mut retries = 0;
+    let mut reader = loop {
+        reader = match reader.try_skip() {
+            Ok(reader) => break reader,
+            Err((mut reader, ReaderError::Parse(nom::Err::Incomplete(_)))) => {
+                reader.reader.get_mut().available += 20;
+                retries += 1;
+                reader
+            }
+            Err(_) => panic!("expected incomplete data"),
+        };
+    };
+    assert_eq!(retries, 4);
+
+    reader.reader.get_mut().available = BYTE_STREAM.len();
+    let reader = match reader.try_next() {
+        Ok(parser::_DocumentNextReaders::Files(r)) => r,
+        _ => panic!("expected the second Files element"),
+    };
+    assert!(matches!(
+        reader.try_next(),
+        Ok(parser::FilesNextReaders::Void(_))
+    ));
+}
+
 #[test]
 fn find_all_element_instances() {
     let mut reader: parser::Readers<_> = parser::_DocumentReader::new(&BYTE_STREAM[..]).into();
@@ -148,3 +304,454 @@ fn find_all_element_instances() {
         ]
     );
 }
+
+#[test]
+fn included_module_traversal() {
+    use ebml::base::parser::{NextReaderNavigation, ReaderDataParser, ReaderError};
+    use ebml::core::parser;
+
+    let mut reader: parser::Readers<_> = parser::_DocumentReader::new(&BYTE_STREAM[..]).into();
+    let mut result = Vec::new();
+
+    loop {
+        reader = match reader {
+            parser::Readers::_Document(r) => match r.next() {
+                Ok(r_next) => r_next.into(),
+                Err(ReaderError::Parse(nom::Err::Incomplete(_))) => break,
+                Err(_) => panic!(), // in an actual function, this should return the error
+            },
+            parser::Readers::FileName(mut r) => {
+                result.push(r.read().unwrap().to_string());
+                r.next().unwrap().into()
+            }
+            parser::Readers::Void(r) => r.next().unwrap().into(),
+            parser::Readers::Files(r) => r.next().unwrap().into(),
+            parser::Readers::File(r) => r.next().unwrap().into(),
+            parser::Readers::MimeType(r) => r.next().unwrap().into(),
+            parser::Readers::ModificationTimestamp(r) => r.next().unwrap().into(),
+            parser::Readers::Data(r) => r.next().unwrap().into(),
+        };
+    }
+
+    assert_eq!(result, vec!["file3.html", "file2.csv", "file1.txt"]);
+}
+
+// A File whose Data is larger than the reader buffers in the tests below
+fn large_data_stream() -> Vec<u8> {
+    let mut stream = vec![
+        0x19, 0x46, 0x69, 0x6C, // Files element ID
+        0xF3, // Files length = 115
+        0x61, 0x46, // File element ID
+        0xF0, // File length = 112
+        0x46, 0x64, // Data element ID
+        0xE4, // Data length = 100
+    ];
+    stream.extend([0xAB; 100]);
+    stream.extend([
+        0x61, 0x4E, // FileName element ID
+        0x85, // FileName length = 5
+        0x61, 0x2e, 0x74, 0x78, 0x74, // FileName data = "a.txt"
+    ]);
+    stream
+}
+
+#[test]
+fn skip_unbuffered() {
+    let stream = large_data_stream();
+    let reader = std::io::BufReader::with_capacity(16, &stream[..]);
+    let reader = match parser::_DocumentReader::new(reader).next().unwrap() {
+        parser::_DocumentNextReaders::Files(r) => r,
+        _ => panic!("expected the Files element"),
+    };
+    let reader = match reader.next().unwrap() {
+        parser::FilesNextReaders::File(r) => r,
+        _ => panic!("expected the File element"),
+    };
+    let reader = match reader.next().unwrap() {
+        parser::FileNextReaders::Data(r) => r,
+        _ => panic!("expected the Data element"),
+    };
+
+    let reader = reader.skip().unwrap();
+    let mut reader = match reader.next().unwrap() {
+        parser::FileNextReaders::FileName(r) => r,
+        _ => panic!("expected the FileName element"),
+    };
+    assert_eq!(reader.read().unwrap(), "a.txt");
+}
+
+#[test]
+fn compile_xml_fixture() {
+    let cargo_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let schema_file = std::io::BufReader::new(
+        std::fs::File::open(cargo_path.join("eg_schema.xml")).expect("couldn't open schema file"),
+    );
+    let fixture_file = std::io::BufReader::new(
+        std::fs::File::open(cargo_path.join("tests/fixtures/byte_stream.xml"))
+            .expect("couldn't open fixture file"),
+    );
+
+    let parser = Builder::new(schema_file).unwrap().build_dynamic().unwrap();
+    let converter = Converter::new(&parser);
+    let elements = converter.read_xml(fixture_file).unwrap();
+
+    assert_eq!(converter.to_ebml(&elements).unwrap(), BYTE_STREAM);
+}
+
+#[derive(Default)]
+struct FileNameVisitor {
+    depth: usize,
+    names: Vec<String>,
+    skipped_files: usize,
+}
+
+impl visitor::Visitor for FileNameVisitor {
+    fn enter_files(&mut self) -> Visit {
+        self.depth += 1;
+        Visit::Continue
+    }
+    fn exit_files(&mut self) {
+        self.depth -= 1;
+    }
+    fn enter_file(&mut self) -> Visit {
+        // skip over the second file ("file2.csv")
+        if self.names.len() == 1 && self.skipped_files == 0 {
+            self.skipped_files += 1;
+            return Visit::Skip;
+        }
+        Visit::Continue
+    }
+    fn visit_file_name(&mut self, value: &str) {
+        assert_eq!(self.depth, 1);
+        self.names.push(value.to_string());
+    }
+}
+
+#[test]
+fn visitor_walk() {
+    let mut visitor = FileNameVisitor::default();
+    visitor::walk(parser::_DocumentReader::new(&BYTE_STREAM[..]), &mut visitor).unwrap();
+
+    assert_eq!(visitor.depth, 0);
+    assert_eq!(visitor.names, vec!["file3.html", "file1.txt"]);
+    assert_eq!(visitor.skipped_files, 1);
+}
+
+#[test]
+fn event_iterator() {
+    let events = events::Events::new(parser::_DocumentReader::new(&BYTE_STREAM[..]))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    let start = |id: u32, path: &str, offset: u64, size: u64| Event::Start {
+        id,
+        path: path.to_string(),
+        offset,
+        size: Some(size),
+    };
+    assert_eq!(
+        events[..5],
+        [
+            start(0x1946696C, r"\Files", 0, 90),
+            start(0x6146, r"\Files\File", 5, 43),
+            start(0x614E, r"\Files\File\FileName", 8, 10),
+            Event::Value {
+                id: 0x614E,
+                value: Value::Utf8("file3.html".to_string()),
+            },
+            Event::End { id: 0x614E },
+        ]
+    );
+
+    let void = events
+        .iter()
+        .position(|event| matches!(event, Event::Start { id: 0xEC, .. }))
+        .unwrap();
+    assert_eq!(
+        events[void - 2..void + 4],
+        [
+            Event::End { id: 0x1946696C },
+            start(0x1946696C, r"\Files", 95, 50),
+            start(0xEC, r"\Files\Void", 100, 2),
+            Event::Value {
+                id: 0xEC,
+                value: Value::Binary(vec![0xFF, 0xFF]),
+            },
+            Event::End { id: 0xEC },
+            start(0x6146, r"\Files\File", 104, 43),
+        ]
+    );
+    assert_eq!(events.last(), Some(&Event::End { id: 0x1946696C }));
+
+    // 18 elements, 13 of which are leaves
+    assert_eq!(events.len(), 18 * 2 + 13);
+}
+
+#[test]
+fn owned_document() {
+    let read = |stream: &[u8]| {
+        let events = events::Events::new(parser::_DocumentReader::new(stream))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        owned::_Document::from_events(events).unwrap()
+    };
+
+    let mut document = read(&BYTE_STREAM[..]);
+    assert_eq!(document.files.len(), 2);
+    assert_eq!(document.files[1].void, vec![vec![0xFF, 0xFF]]);
+    assert_eq!(
+        document.files[0].file[0].file_name,
+        vec!["file3.html".to_string()]
+    );
+    assert_eq!(document.validate(), Ok(()));
+
+    // children are written in a fixed order, with the shortest data sizes
+    let mut stream = Vec::new();
+    document.write(&mut stream).unwrap();
+    assert_eq!(stream.len(), BYTE_STREAM.len());
+    assert_eq!(read(&stream), document);
+
+    document.files[0].file[0].file_name.clear();
+    assert_eq!(
+        document.validate(),
+        Err(ValidationError::Occurrences(0x614E, 0))
+    );
+    document.files[0].file[0].mime_type = vec!["тип".to_string()];
+    assert!(matches!(
+        document.write(&mut Vec::new()),
+        Err(WriteError::Serialize(0x464D))
+    ));
+}
+
+#[rstest]
+#[case(1)]
+#[case(3)]
+#[case(7)]
+#[case(BYTE_STREAM.len())]
+fn push_decoder_chunks(#[case] chunk_len: usize) {
+    let expt_events = events::Events::new(parser::_DocumentReader::new(&BYTE_STREAM[..]))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    let mut decoder = decoder::Decoder::new();
+    let mut events = Vec::new();
+    for chunk in BYTE_STREAM.chunks(chunk_len) {
+        decoder.push(chunk).unwrap();
+        events.extend(decoder.events());
+    }
+
+    assert_eq!(events, expt_events);
+    assert!(decoder.is_complete());
+}
+
+#[test]
+fn push_decoder_incomplete() {
+    let mut decoder = decoder::Decoder::new();
+
+    // the Files & File headers, & part of the FileName header
+    decoder.push(&BYTE_STREAM[..9]).unwrap();
+    assert_eq!(decoder.events().count(), 2);
+    assert!(!decoder.is_complete());
+
+    // the rest of the FileName header, & part of its data
+    decoder.push(&BYTE_STREAM[9..15]).unwrap();
+    assert_eq!(decoder.events().count(), 1);
+
+    decoder.push(&BYTE_STREAM[15..21]).unwrap();
+    assert_eq!(
+        decoder.events().collect::<Vec<_>>(),
+        [
+            Event::Value {
+                id: 0x614E,
+                value: Value::Utf8("file3.html".to_string()),
+            },
+            Event::End { id: 0x614E },
+        ]
+    );
+}
+
+#[test]
+fn push_decoder_error() {
+    let mut decoder = decoder::Decoder::new();
+    decoder.push(&BYTE_STREAM[..5]).unwrap();
+    assert_eq!(decoder.events().count(), 1);
+
+    // an invalid File ID, which stays buffered; later chunks only queue up behind it
+    let error = decoder.push(&[0x61, 0x47, 0x80]).unwrap_err();
+    assert!(matches!(
+        error,
+        StateError::InvalidChildId(Some(0x1946696C), 0x6147)
+    ));
+    assert!(decoder.push(&BYTE_STREAM[5..]).is_err());
+    assert_eq!(decoder.events().count(), 0);
+}
+
+#[tokio::test]
+async fn codec_decode_duplex() {
+    let expt_events = events::Events::new(parser::_DocumentReader::new(&BYTE_STREAM[..]))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    // a small buffer splits the stream into small reads
+    let (mut client, server) = tokio::io::duplex(16);
+    let writer = tokio::spawn(async move { client.write_all(&BYTE_STREAM).await.unwrap() });
+    let events = FramedRead::new(server, codec::EventCodec::new())
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    writer.await.unwrap();
+
+    assert_eq!(events, expt_events);
+}
+
+#[tokio::test]
+async fn codec_encode_duplex() {
+    let events = events::Events::new(parser::_DocumentReader::new(&BYTE_STREAM[..]))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    let (client, mut server) = tokio::io::duplex(16);
+    let writer = tokio::spawn(async move {
+        let mut framed = FramedWrite::new(client, codec::EventCodec::new());
+        for event in events {
+            framed.send(event).await.unwrap();
+        }
+        framed.close().await.unwrap();
+    });
+    let mut stream = Vec::new();
+    server.read_to_end(&mut stream).await.unwrap();
+    writer.await.unwrap();
+
+    assert_eq!(stream, BYTE_STREAM);
+}
+
+#[tokio::test]
+async fn codec_encode_size_widths() {
+    let events = events::Events::new(parser::_DocumentReader::new(&BYTE_STREAM[..]))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    // 8-byte data sizes, to be backpatched, except for the FileNames
+    let encoder = EventEncoder::new()
+        .size_width(SizeWidth::Fixed(8))
+        .element_size_width(FileNameDef::ID, SizeWidth::Minimal);
+    let (client, mut server) = tokio::io::duplex(16);
+    let writer = {
+        let events = events.clone();
+        tokio::spawn(async move {
+            let mut framed = FramedWrite::new(client, codec::EventCodec::with_encoder(encoder));
+            for event in events {
+                framed.send(event).await.unwrap();
+            }
+            framed.close().await.unwrap();
+        })
+    };
+    let mut stream = Vec::new();
+    server.read_to_end(&mut stream).await.unwrap();
+    writer.await.unwrap();
+
+    assert_eq!(
+        stream[..12],
+        [0x19, 0x46, 0x69, 0x6C, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x92]
+    );
+    let widened = events
+        .iter()
+        .filter(|event| matches!(event, Event::Start { id, .. } if *id != FileNameDef::ID))
+        .count();
+    assert_eq!(stream.len(), BYTE_STREAM.len() + 7 * widened);
+
+    // the events are the same, bar the offsets & sizes
+    let strip = |event: Event| match event {
+        Event::Start { id, path, .. } => Event::Start {
+            id,
+            path,
+            offset: 0,
+            size: None,
+        },
+        event => event,
+    };
+    let reread = events::Events::new(parser::_DocumentReader::new(&stream[..]))
+        .map(|event| event.map(strip))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(reread, events.into_iter().map(strip).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn codec_decode_incomplete() {
+    let (mut client, server) = tokio::io::duplex(16);
+    // ends within the first FileName element
+    client.write_all(&BYTE_STREAM[..15]).await.unwrap();
+    drop(client);
+    let mut framed = FramedRead::new(server, codec::EventCodec::new());
+
+    for _ in 0..3 {
+        assert!(matches!(
+            framed.try_next().await,
+            Ok(Some(Event::Start { .. }))
+        ));
+    }
+    assert!(matches!(
+        framed.try_next().await,
+        Err(CodecError::Incomplete)
+    ));
+}
+
+#[tokio::test]
+async fn codec_decode_invalid() {
+    let (mut client, server) = tokio::io::duplex(16);
+    // an invalid File ID, after the Files header
+    client.write_all(&BYTE_STREAM[..5]).await.unwrap();
+    client.write_all(&[0x61, 0x47, 0x80]).await.unwrap();
+    drop(client);
+    let mut framed = FramedRead::new(server, codec::EventCodec::new());
+
+    assert!(matches!(
+        framed.try_next().await,
+        Ok(Some(Event::Start { .. }))
+    ));
+    assert!(matches!(
+        framed.try_next().await,
+        Err(CodecError::Parse(StateError::InvalidChildId(
+            Some(0x1946696C),
+            0x6147
+        )))
+    ));
+}
+
+#[test]
+fn select_values() {
+    let select = |path: &str| {
+        select::select_path(parser::_DocumentReader::new(&BYTE_STREAM[..]), path)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    };
+    let utf8 = |s: &str| Value::Utf8(s.to_string());
+
+    assert_eq!(
+        select(r"\Files\File\FileName"),
+        vec![utf8("file3.html"), utf8("file2.csv"), utf8("file1.txt")]
+    );
+    assert_eq!(
+        select(r"\Files\File\MimeType"),
+        vec![
+            Value::String("text/html".to_string()),
+            Value::String("text/csv".to_string()),
+            Value::String("text/plain".to_string()),
+        ]
+    );
+    // master elements & paths not in the schema select nothing
+    assert!(select(r"\Files\File").is_empty());
+    assert!(select(r"\Files\FileName").is_empty());
+
+    let names = select::select::<FileNameDef, _>(parser::_DocumentReader::new(&BYTE_STREAM[..]))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(names.len(), 3);
+
+    // global elements are selected at any level
+    let voids = select::select::<VoidDef, _>(parser::_DocumentReader::new(&BYTE_STREAM[..]))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(voids, vec![Value::Binary(vec![0xFF, 0xFF])]);
+}
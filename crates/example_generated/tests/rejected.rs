@@ -0,0 +1,40 @@
+#[allow(dead_code)]
+mod ebml {
+    // generated with `\Files\File\Data` blocklisted & rejected (see build.rs)
+    include!(concat!(env!("OUT_DIR"), "/rejected/ebml.rs"));
+}
+
+use ebml::base::parser::{NextReaderNavigation, ReaderError, StateError};
+use ebml::core::parser;
+
+#[test]
+fn reject_excluded() {
+    let stream = [
+        0x19, 0x46, 0x69, 0x6C, // Files element ID
+        0x88, // Files length = 8
+        0x61, 0x46, // File element ID
+        0x85, // File length = 5
+        0x46, 0x64, // Data element ID
+        0x82, // Data length = 2
+        0xAB, 0xAB, // Data data
+    ];
+
+    let reader = match parser::_DocumentReader::new(&stream[..]).next().unwrap() {
+        parser::_DocumentNextReaders::Files(r) => r,
+        _ => panic!("expected the Files element"),
+    };
+    let reader = match reader.next().unwrap() {
+        parser::FilesNextReaders::File(r) => r,
+        _ => panic!("expected the File element"),
+    };
+
+    // the excluded Data element is rejected, rather than skipped over
+    let error = reader.next().err().unwrap();
+    assert!(matches!(
+        error,
+        ReaderError::Parse(nom::Err::Failure(StateError::ExcludedChildId(
+            Some(0x6146),
+            0x4664
+        )))
+    ));
+}
@@ -0,0 +1,39 @@
+#[allow(dead_code)]
+mod ebml {
+    // generated from reserved_schema.xml, whose elements are named after the generated & imported
+    // identifiers (see build.rs)
+    include!(concat!(env!("OUT_DIR"), "/reserved/ebml.rs"));
+}
+
+use ebml::core::{events, owned, parser};
+
+#[test]
+fn reserved_names_round_trip() {
+    let stream = [
+        0x1A, 0x45, 0xDF, 0xA3, // States element ID
+        0x8E, // States length = 14
+        0x52, 0x81, // Parent element ID
+        0x84, // Parent length = 4
+        0x42, 0x81, // Readers element ID
+        0x81, // Readers length = 1
+        0x01, // Readers data = 1
+        0x52, 0x82, // Iterator element ID
+        0x84, // Iterator length = 4
+        0x42, 0x95, // R element ID
+        0x81, // R length = 1
+        0x02, // R data = 2
+    ];
+
+    let events = events::Events::new(parser::_DocumentReader::new(&stream[..]))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let document = owned::_Document::from_events(events).unwrap();
+    let states = &document.states_element[0];
+    assert_eq!(states.parent_element[0].readers_element, Some(1));
+    assert_eq!(states.iterator_element.as_ref().unwrap().r_element, vec![2]);
+    assert_eq!(document.validate(), Ok(()));
+
+    let mut written = Vec::new();
+    document.write(&mut written).unwrap();
+    assert_eq!(written, stream);
+}
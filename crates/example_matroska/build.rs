@@ -0,0 +1,21 @@
+use iron_ebmlem::parser_gen::{Builder, SchemaStrictness};
+
+fn main() {
+    let cargo_path = std::env::var("CARGO_MANIFEST_DIR")
+        .map(std::path::PathBuf::from)
+        .expect("no env variable 'CARGO_MANIFEST_DIR'");
+    let out_dir = std::env::var("OUT_DIR")
+        .map(std::path::PathBuf::from)
+        .expect("no env variable 'OUT_DIR'");
+
+    Builder::from_path(cargo_path.join("ebml_matroska.xml"))
+        .expect("couldn't parse schema file")
+        .module_name("matroska")
+        .ebml_header(true)
+        // ChapterDisplay keeps the ID 0x80, whose VINT_DATA is all 0's against RFC 8794
+        .schema_strictness(SchemaStrictness::Lenient)
+        .generate()
+        .expect("couldn't make parser writer")
+        .write_module(out_dir)
+        .expect("couldn't write parser module");
+}
@@ -0,0 +1,3 @@
+// The parsers generated from `ebml_matroska.xml` by the build script: `base` holds the schema-agnostic
+// code, and `matroska` the element definitions & typestate parsers.
+include!(concat!(env!("OUT_DIR"), "/ebml.rs"));
@@ -0,0 +1,103 @@
+// Remuxes a Matroska file into a live WebM stream: a non-seekable writer leaves the Segment & the
+// Clusters unknown-sized, as when muxing to a pipe.
+#include <cstdio>
+#include <vector>
+
+#include "mkvmuxer/mkvmuxer.h"
+#include "mkvparser/mkvparser.h"
+#include "mkvparser/mkvreader.h"
+
+class PipeWriter : public mkvmuxer::IMkvWriter {
+ public:
+  explicit PipeWriter(FILE* file) : file_(file), position_(0) {}
+  mkvmuxer::int32 Write(const void* buf, mkvmuxer::uint32 len) override {
+    position_ += len;
+    return fwrite(buf, 1, len, file_) == len ? 0 : -1;
+  }
+  mkvmuxer::int64 Position() const override { return position_; }
+  mkvmuxer::int32 Position(mkvmuxer::int64) override { return -1; }
+  bool Seekable() const override { return false; }
+  void ElementStartNotify(mkvmuxer::uint64, mkvmuxer::int64) override {}
+
+ private:
+  FILE* file_;
+  mkvmuxer::int64 position_;
+};
+
+int main(int argc, char** argv) {
+  if (argc != 3) {
+    fprintf(stderr, "usage: %s <input.mkv> <output.webm>\n", argv[0]);
+    return 1;
+  }
+  mkvparser::MkvReader reader;
+  if (reader.Open(argv[1])) return 1;
+  long long pos = 0;
+  mkvparser::EBMLHeader ebml_header;
+  if (ebml_header.Parse(&reader, pos) < 0) return 1;
+  mkvparser::Segment* parser_segment = nullptr;
+  if (mkvparser::Segment::CreateInstance(&reader, pos, parser_segment)) return 1;
+  if (parser_segment->Load() < 0) return 1;
+
+  FILE* output = fopen(argv[2], "wb");
+  PipeWriter writer(output);
+  mkvmuxer::Segment segment;
+  if (!segment.Init(&writer)) return 1;
+  segment.set_mode(mkvmuxer::Segment::kLive);
+  segment.set_max_cluster_duration(250000000ULL);
+  segment.OutputCues(false);
+
+  const mkvparser::Tracks* tracks = parser_segment->GetTracks();
+  std::vector<unsigned long long> track_numbers(tracks->GetTracksCount() + 1, 0);
+  for (unsigned long i = 0; i < tracks->GetTracksCount(); ++i) {
+    const mkvparser::Track* track = tracks->GetTrackByIndex(i);
+    size_t private_size = 0;
+    const unsigned char* codec_private = track->GetCodecPrivate(private_size);
+    mkvmuxer::Track* muxer_track = nullptr;
+    if (track->GetType() == mkvparser::Track::kVideo) {
+      const mkvparser::VideoTrack* video = static_cast<const mkvparser::VideoTrack*>(track);
+      const unsigned long long number = segment.AddVideoTrack(
+          static_cast<int>(video->GetWidth()), static_cast<int>(video->GetHeight()), 0);
+      muxer_track = segment.GetTrackByNumber(number);
+      track_numbers[track->GetNumber()] = number;
+    } else if (track->GetType() == mkvparser::Track::kAudio) {
+      const mkvparser::AudioTrack* audio = static_cast<const mkvparser::AudioTrack*>(track);
+      const unsigned long long number = segment.AddAudioTrack(
+          static_cast<int>(audio->GetSamplingRate()), static_cast<int>(audio->GetChannels()), 0);
+      mkvmuxer::AudioTrack* muxer_audio =
+          static_cast<mkvmuxer::AudioTrack*>(segment.GetTrackByNumber(number));
+      muxer_audio->set_bit_depth(audio->GetBitDepth());
+      muxer_track = muxer_audio;
+      track_numbers[track->GetNumber()] = number;
+    } else {
+      continue;
+    }
+    muxer_track->set_codec_id(track->GetCodecId());
+    if (codec_private && !muxer_track->SetCodecPrivate(codec_private, private_size)) return 1;
+  }
+
+  std::vector<unsigned char> buffer;
+  const mkvparser::Cluster* cluster = parser_segment->GetFirst();
+  while (cluster && !cluster->EOS()) {
+    const mkvparser::BlockEntry* entry = nullptr;
+    if (cluster->GetFirst(entry) < 0) return 1;
+    while (entry && !entry->EOS()) {
+      const mkvparser::Block* block = entry->GetBlock();
+      const unsigned long long number = track_numbers[block->GetTrackNumber()];
+      const long long time_ns = block->GetTime(cluster);
+      for (int i = 0; i < block->GetFrameCount(); ++i) {
+        const mkvparser::Block::Frame& frame = block->GetFrame(i);
+        buffer.resize(frame.len);
+        if (frame.Read(&reader, buffer.data())) return 1;
+        if (number && !segment.AddFrame(buffer.data(), buffer.size(), number, time_ns,
+                                        block->IsKey()))
+          return 1;
+      }
+      if (cluster->GetNext(entry, entry) < 0) return 1;
+    }
+    cluster = parser_segment->GetNext(cluster);
+  }
+  if (!segment.Finalize()) return 1;
+  fclose(output);
+  delete parser_segment;
+  return 0;
+}
@@ -0,0 +1,703 @@
+use example_matroska_parser::base::edit::ElementEditor;
+use example_matroska_parser::base::element_defs::{
+    FloatElementDef, MasterElementDef, StringElementDef, UIntElementDef,
+};
+use example_matroska_parser::base::events::{Event, Value};
+use example_matroska_parser::base::parser::{BoundTo, ReaderError, StateError, Visit};
+use example_matroska_parser::base::raw::{RawElement, RawElementReader};
+use example_matroska_parser::matroska::{decoder, events, parser, select, visitor};
+
+use iron_ebmlem::dynamic::{DynElement, DynParser, DynValue};
+use iron_ebmlem::parser_gen::{Builder, SchemaStrictness};
+use iron_ebmlem::serde_schema::{EbmlSchema, ElementValue};
+
+// A two-track (VP9 + Vorbis) Matroska file muxed by mkvmerge, with chapters, tags & Void elements
+const SIMPLE: &[u8] = include_bytes!("fixtures/simple.mkv");
+// A two-track Matroska file muxed by FFmpeg, with a CRC-32 in every top-level master element
+const HDR: &[u8] = include_bytes!("fixtures/hdr.mkv");
+// `SIMPLE`, remuxed by libwebm as a live stream: its Segment & Clusters are all unknown-sized
+const LIVE: &[u8] = include_bytes!("fixtures/live.webm");
+
+fn schema_path() -> std::path::PathBuf {
+    std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("ebml_matroska.xml")
+}
+
+fn dyn_parser() -> DynParser {
+    Builder::from_path(schema_path())
+        .unwrap()
+        .ebml_header(true)
+        .schema_strictness(SchemaStrictness::Lenient)
+        .build_dynamic()
+        .unwrap()
+}
+
+// the paths & (formatted) values of leaf elements, in document order
+type Leaves = Vec<(String, String)>;
+
+// Steps through a whole document with the typestate readers (by way of their event iterator),
+// recording the path & value of every leaf element read
+fn walk_leaves(stream: &[u8]) -> Result<Leaves, (Leaves, ReaderError)> {
+    let mut path = Vec::new();
+    let mut result = Vec::new();
+
+    for event in events::Events::new(parser::_DocumentReader::new(stream)) {
+        match event {
+            Ok(Event::Start {
+                path: start_path, ..
+            }) => path.push(start_path),
+            Ok(Event::Value { value, .. }) => {
+                let value = match value {
+                    Value::UInt(value) => value.to_string(),
+                    Value::Int(value) => value.to_string(),
+                    Value::Float(value) => format!("{:?}", value),
+                    Value::Date(value) => format!("{:?}", value),
+                    Value::String(value) | Value::Utf8(value) => format!("{:?}", value),
+                    Value::Binary(value) => format!("{:?}", value),
+                };
+                result.push((path.last().unwrap().clone(), value));
+            }
+            Ok(Event::End { .. }) => {
+                path.pop();
+            }
+            Err(e) => return Err((result, e)),
+        }
+    }
+    Ok(result)
+}
+
+// the formatted values of the leaves at a path
+fn leaf_values<'a>(leaves: &'a Leaves, path: &str) -> Vec<&'a str> {
+    leaves
+        .iter()
+        .filter(|(leaf_path, _)| leaf_path == path)
+        .map(|(_, value)| value.as_str())
+        .collect()
+}
+
+#[test]
+fn walk_simple() {
+    let result = walk_leaves(SIMPLE).map_err(|(_, e)| e).unwrap();
+
+    assert_eq!(
+        result[..7],
+        vec![
+            (r"\EBML\EBMLVersion", "1"),
+            (r"\EBML\EBMLReadVersion", "1"),
+            (r"\EBML\EBMLMaxIDLength", "4"),
+            (r"\EBML\EBMLMaxSizeLength", "8"),
+            (r"\EBML\DocType", r#""matroska""#),
+            (r"\EBML\DocTypeVersion", "4"),
+            (r"\EBML\DocTypeReadVersion", "2"),
+        ]
+        .into_iter()
+        .map(|(path, value)| (path.to_string(), value.to_string()))
+        .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        leaf_values(&result, r"\Segment\Info\WritingApp"),
+        vec![r#""mkvmerge v56.1.0 ('My Friend') 64-bit""#]
+    );
+    assert_eq!(
+        leaf_values(&result, r"\Segment\Tracks\TrackEntry\CodecID"),
+        vec![r#""V_VP9""#, r#""A_VORBIS""#]
+    );
+    assert_eq!(
+        leaf_values(
+            &result,
+            r"\Segment\Chapters\EditionEntry\ChapterAtom\ChapterDisplay\ChapString"
+        ),
+        vec![
+            r#""Intro""#,
+            r#""Act 1""#,
+            r#""Act 2""#,
+            r#""Credits""#,
+            r#""A hidden and not enabled chapter.""#,
+        ]
+    );
+    assert_eq!(leaf_values(&result, r"\Segment\Void").len(), 3);
+    assert_eq!(
+        leaf_values(&result, r"\Segment\Cluster\SimpleBlock").len(),
+        31
+    );
+    assert_eq!(
+        leaf_values(&result, r"\Segment\Tags\Tag\SimpleTag\TagName").len(),
+        19
+    );
+}
+
+#[test]
+fn walk_live_unknown_size() {
+    // unknown-sized elements end at the first element that isn't a valid child, or along with the
+    // stream (here, within the last Cluster)
+    let result = walk_leaves(LIVE).map_err(|(_, e)| e).unwrap();
+    assert_eq!(
+        leaf_values(&result, r"\Segment\Cluster\Timestamp"),
+        vec!["0", "253", "464", "753"]
+    );
+    // libwebm doesn't lace the frames of `SIMPLE` (24 video frames & 50 audio frames)
+    assert_eq!(
+        leaf_values(&result, r"\Segment\Cluster\SimpleBlock").len(),
+        74
+    );
+    assert!(result
+        .iter()
+        .skip_while(|(path, _)| !path.starts_with(r"\Segment\Cluster"))
+        .all(|(path, _)| path.starts_with(r"\Segment\Cluster\")));
+
+    // as with the dynamic parser
+    let parser = dyn_parser();
+    let entries = parser
+        .reader(LIVE)
+        .map(|entry| entry.unwrap())
+        .filter(|entry| {
+            entry.path == r"\Segment\Cluster" || entry.path == r"\Segment\Cluster\Timestamp"
+        })
+        .map(|entry| (entry.path, entry.element.depth, entry.value))
+        .collect::<Vec<_>>();
+
+    let cluster = |timestamp: u64| {
+        vec![
+            (r"\Segment\Cluster".to_string(), 1, None),
+            (
+                r"\Segment\Cluster\Timestamp".to_string(),
+                2,
+                Some(DynValue::UInt(timestamp)),
+            ),
+        ]
+    };
+    assert_eq!(
+        entries,
+        [cluster(0), cluster(253), cluster(464), cluster(753)].concat()
+    );
+}
+
+#[test]
+fn unknown_size_not_allowed() {
+    let stream = [
+        0x18, 0x53, 0x80, 0x67, 0xFF, // Segment, length = unknown
+        0x15, 0x49, 0xA9, 0x66, 0xFF, // Info, length = unknown
+        0x2A, 0xD7, 0xB1, 0x83, 0x0F, 0x42, 0x40, // TimestampScale = 1000000
+    ];
+    let (result, error) = walk_leaves(&stream).unwrap_err();
+
+    assert!(result.is_empty());
+    assert!(matches!(
+        error,
+        ReaderError::Parse(nom::Err::Failure(StateError::UnknownSizeNotAllowed(
+            0x1549A966
+        )))
+    ));
+}
+
+// An element with a 1-octet data size
+fn element(id: &[u8], data: &[u8]) -> Vec<u8> {
+    [id, &[0x80 | data.len() as u8], data].concat()
+}
+
+// A Segment whose only tag nests another, which none of the fixtures do
+fn nested_tags() -> Vec<u8> {
+    let simple_tag = |name: &str, value: &str, nested: &[u8]| {
+        element(
+            &[0x67, 0xC8],
+            &[
+                element(&[0x45, 0xA3], name.as_bytes()),
+                element(&[0x44, 0x87], value.as_bytes()),
+                nested.to_vec(),
+            ]
+            .concat(),
+        )
+    };
+    let tag = element(
+        &[0x73, 0x73],
+        &simple_tag(
+            "ENCODER",
+            "iron-ebmlem",
+            &simple_tag("VERSION", "0.0.0", &[]),
+        ),
+    );
+    element(
+        &[0x18, 0x53, 0x80, 0x67],
+        &element(&[0x12, 0x54, 0xC3, 0x67], &tag),
+    )
+}
+
+#[test]
+fn recursive_tags() {
+    let stream = nested_tags();
+    let result = walk_leaves(&stream).map_err(|(_, e)| e).unwrap();
+    assert_eq!(
+        result,
+        vec![
+            (r"\Segment\Tags\Tag\SimpleTag\TagName", r#""ENCODER""#),
+            (r"\Segment\Tags\Tag\SimpleTag\TagString", r#""iron-ebmlem""#),
+            (
+                r"\Segment\Tags\Tag\SimpleTag\SimpleTag\TagName",
+                r#""VERSION""#
+            ),
+            (
+                r"\Segment\Tags\Tag\SimpleTag\SimpleTag\TagString",
+                r#""0.0.0""#
+            ),
+        ]
+        .into_iter()
+        .map(|(path, value)| (path.to_string(), value.to_string()))
+        .collect::<Vec<_>>()
+    );
+
+    // as with the dynamic parser
+    let parser = dyn_parser();
+    let entries = parser
+        .reader(&stream[..])
+        .map(|entry| entry.unwrap())
+        .filter(|entry| entry.path.starts_with(r"\Segment\Tags"))
+        .map(|entry| (entry.path, entry.value))
+        .collect::<Vec<_>>();
+
+    let utf8 = |s: &str| Some(DynValue::Utf8(s.to_string()));
+    assert_eq!(
+        entries,
+        vec![
+            (r"\Segment\Tags".to_string(), None),
+            (r"\Segment\Tags\Tag".to_string(), None),
+            (r"\Segment\Tags\Tag\SimpleTag".to_string(), None),
+            (
+                r"\Segment\Tags\Tag\SimpleTag\TagName".to_string(),
+                utf8("ENCODER")
+            ),
+            (
+                r"\Segment\Tags\Tag\SimpleTag\TagString".to_string(),
+                utf8("iron-ebmlem")
+            ),
+            (r"\Segment\Tags\Tag\SimpleTag\SimpleTag".to_string(), None),
+            (
+                r"\Segment\Tags\Tag\SimpleTag\SimpleTag\TagName".to_string(),
+                utf8("VERSION")
+            ),
+            (
+                r"\Segment\Tags\Tag\SimpleTag\SimpleTag\TagString".to_string(),
+                utf8("0.0.0")
+            ),
+        ]
+    );
+
+    let select = select::select_path(
+        parser::_DocumentReader::new(&stream[..]),
+        r"\Segment\Tags\Tag\+SimpleTag\TagName",
+    )
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    assert_eq!(
+        select,
+        vec![
+            Value::Utf8("ENCODER".to_string()),
+            Value::Utf8("VERSION".to_string())
+        ]
+    );
+
+    type SimpleTagDef = <parser::SimpleTagState as BoundTo>::Element;
+    const { assert!(<SimpleTagDef as MasterElementDef>::RECURSIVE) };
+}
+
+#[test]
+fn element_defaults() {
+    type Def<S> = <S as BoundTo>::Element;
+
+    assert_eq!(
+        <Def<parser::TimestampScaleState> as UIntElementDef>::DEFAULT,
+        Some(1_000_000)
+    );
+    assert_eq!(
+        <Def<parser::FlagEnabledState> as UIntElementDef>::DEFAULT,
+        Some(1)
+    );
+    assert_eq!(
+        <Def<parser::TargetTypeValueState> as UIntElementDef>::DEFAULT,
+        Some(50)
+    );
+    assert_eq!(
+        <Def<parser::LanguageState> as StringElementDef>::DEFAULT,
+        Some("eng")
+    );
+    assert_eq!(
+        <Def<parser::SamplingFrequencyState> as FloatElementDef>::DEFAULT,
+        Some(8000.0)
+    );
+    assert_eq!(
+        <Def<parser::TrackNumberState> as UIntElementDef>::DEFAULT,
+        None
+    );
+
+    // neither track has a (legacy) Language element, only a LanguageBCP47 one -> both default to
+    // English
+    let document = dyn_parser().parse(SIMPLE).unwrap();
+    let children = |element: &DynElement| match &element.value {
+        DynValue::Master(children) => children.clone(),
+        _ => panic!("{} isn't a master element", element.name),
+    };
+    let child = |element: &DynElement, name: &str| {
+        children(element)
+            .into_iter()
+            .find(|child| child.name == name)
+    };
+    let segment = document.iter().find(|elem| elem.name == "Segment").unwrap();
+    let languages = children(&child(segment, "Tracks").unwrap())
+        .iter()
+        .map(
+            |track| match child(track, "Language").map(|elem| elem.value) {
+                Some(DynValue::String(language)) => language,
+                _ => <Def<parser::LanguageState> as StringElementDef>::DEFAULT
+                    .unwrap()
+                    .to_string(),
+            },
+        )
+        .collect::<Vec<_>>();
+    assert_eq!(languages, vec!["eng", "eng"]);
+}
+
+#[test]
+fn track_type_labels() {
+    let schema = EbmlSchema::from_reader(std::fs::File::open(schema_path()).unwrap()).unwrap();
+    let track_type = schema
+        .elements
+        .unwrap()
+        .into_iter()
+        .find(|elem| elem.name == "TrackType")
+        .unwrap();
+    let enums = track_type
+        .metadata
+        .unwrap()
+        .into_iter()
+        .find_map(|value| match value {
+            ElementValue::Restriction(restriction) => Some(restriction.enums),
+            _ => None,
+        })
+        .unwrap();
+
+    let labels = parser_track_types(SIMPLE)
+        .into_iter()
+        .map(|value| {
+            enums
+                .iter()
+                .find(|enumeration| enumeration.value == value.to_string())
+                .and_then(|enumeration| enumeration.label.clone())
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(labels, vec!["video", "audio"]);
+}
+
+fn parser_track_types(stream: &[u8]) -> Vec<u64> {
+    walk_leaves(stream)
+        .map_err(|(_, e)| e)
+        .unwrap()
+        .into_iter()
+        .filter(|(path, _value)| path.ends_with(r"\TrackType"))
+        .map(|(_path, value)| value.parse().unwrap())
+        .collect()
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Track {
+    number: u64,
+    codec_id: String,
+    language: String,
+}
+
+#[derive(Default)]
+struct TrackVisitor {
+    tracks: Vec<Track>,
+    clusters: usize,
+}
+
+impl visitor::Visitor for TrackVisitor {
+    fn enter_track_entry(&mut self) -> Visit {
+        self.tracks.push(Track {
+            language: <<parser::LanguageState as BoundTo>::Element as StringElementDef>::DEFAULT
+                .unwrap()
+                .to_string(),
+            ..Track::default()
+        });
+        Visit::Continue
+    }
+    fn visit_track_number(&mut self, value: u64) {
+        self.tracks.last_mut().unwrap().number = value;
+    }
+    fn visit_codec_id(&mut self, value: &str) {
+        self.tracks.last_mut().unwrap().codec_id = value.to_string();
+    }
+    fn visit_language(&mut self, value: &str) {
+        self.tracks.last_mut().unwrap().language = value.to_string();
+    }
+    fn enter_cluster(&mut self) -> Visit {
+        self.clusters += 1;
+        Visit::Skip
+    }
+    fn visit_simple_block(&mut self, _value: &[u8]) {
+        panic!("clusters should be skipped");
+    }
+}
+
+#[test]
+fn visitor_tracks() {
+    let mut visitor = TrackVisitor::default();
+    visitor::walk(parser::_DocumentReader::new(SIMPLE), &mut visitor).unwrap();
+
+    assert_eq!(visitor.tracks, simple_tracks());
+    assert_eq!(visitor.clusters, 1);
+}
+
+fn simple_tracks() -> Vec<Track> {
+    vec![
+        Track {
+            number: 1,
+            codec_id: "V_VP9".to_string(),
+            language: "eng".to_string(),
+        },
+        Track {
+            number: 2,
+            codec_id: "A_VORBIS".to_string(),
+            language: "eng".to_string(),
+        },
+    ]
+}
+
+#[test]
+fn visitor_live_unknown_size() {
+    // the unknown-sized Clusters are skipped over child by child
+    let mut visitor = TrackVisitor::default();
+    visitor::walk(parser::_DocumentReader::new(LIVE), &mut visitor).unwrap();
+
+    assert_eq!(visitor.tracks, simple_tracks());
+    assert_eq!(visitor.clusters, 4);
+}
+
+// Reads a whole document as events, checking that their starts match the elements of the dynamic
+// reader & that every element is ended
+fn events_matching_dynamic_reader(stream: &[u8]) -> Vec<Event> {
+    let events = events::Events::new(parser::_DocumentReader::new(stream))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let starts = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Start {
+                id,
+                path,
+                offset,
+                size,
+            } => Some((*id, path.clone(), *offset, *size)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let parser = dyn_parser();
+    let entries = parser
+        .reader(stream)
+        .map(|entry| entry.unwrap())
+        .map(|entry| {
+            let element = entry.element;
+            (element.id, entry.path, element.offset, element.data_len)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(starts, entries);
+
+    let ends = events
+        .iter()
+        .filter(|event| matches!(event, Event::End { .. }))
+        .count();
+    assert_eq!(ends, starts.len());
+    events
+}
+
+#[test]
+fn events_match_dynamic_reader() {
+    let events = events_matching_dynamic_reader(SIMPLE);
+    assert!(events.contains(&Event::Value {
+        id: 0x86,
+        value: Value::String("A_VORBIS".to_string()),
+    }));
+
+    let events = events_matching_dynamic_reader(HDR);
+    assert!(events.contains(&Event::Value {
+        id: 0x86,
+        value: Value::String("V_MPEG4/ISO/ASP".to_string()),
+    }));
+}
+
+#[test]
+fn events_live_unknown_size() {
+    let events = events_matching_dynamic_reader(LIVE);
+    let sizes = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Start { path, size, .. }
+                if path == r"\EBML"
+                    || path.starts_with(r"\Segment") && path.matches('\\').count() <= 2 =>
+            {
+                Some((path.as_str(), *size))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        sizes,
+        vec![
+            (r"\EBML", Some(31)),
+            (r"\Segment", None),
+            (r"\Segment\Info", Some(43)),
+            (r"\Segment\Tracks", Some(7430)),
+            (r"\Segment\Cluster", None),
+            (r"\Segment\Cluster", None),
+            (r"\Segment\Cluster", None),
+            (r"\Segment\Cluster", None),
+        ]
+    );
+}
+
+#[test]
+fn decoder_matches_events() {
+    let expt_events = events::Events::new(parser::_DocumentReader::new(SIMPLE))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    // e.g. the payloads of network packets
+    for chunk_len in [1, 188, 1200] {
+        let mut decoder = decoder::Decoder::new();
+        let mut events = Vec::new();
+        for chunk in SIMPLE.chunks(chunk_len) {
+            decoder.push(chunk).unwrap();
+            events.extend(decoder.events());
+        }
+
+        assert_eq!(events, expt_events);
+        assert!(decoder.is_complete());
+    }
+}
+
+#[test]
+fn decoder_live_unknown_size() {
+    let expt_events = events::Events::new(parser::_DocumentReader::new(LIVE))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    for chunk_len in [1, 188, 1200] {
+        let mut decoder = decoder::Decoder::new();
+        let mut events = Vec::new();
+        for chunk in LIVE.chunks(chunk_len) {
+            decoder.push(chunk).unwrap();
+            events.extend(decoder.events());
+        }
+        // the stream ends within the unknown-sized Segment & Cluster
+        assert!(!decoder.is_complete());
+        assert!(decoder.finish());
+        events.extend(decoder.events());
+
+        assert_eq!(events, expt_events);
+    }
+}
+
+#[test]
+fn select_paths() {
+    let select = |stream, path: &str| {
+        select::select_path(parser::_DocumentReader::new(stream), path)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    };
+    let string = |s: &str| Value::String(s.to_string());
+
+    assert_eq!(
+        select(SIMPLE, r"\Segment\Tracks\TrackEntry\CodecID"),
+        vec![string("V_VP9"), string("A_VORBIS")]
+    );
+    assert_eq!(
+        select(SIMPLE, r"\Segment\Cluster\Timestamp"),
+        vec![Value::UInt(0)]
+    );
+    assert_eq!(
+        select(
+            SIMPLE,
+            r"\Segment\Chapters\EditionEntry\+ChapterAtom\ChapterTimeEnd"
+        ),
+        vec![Value::UInt(775_000_000_000), Value::UInt(240_000_000_000)]
+    );
+    assert!(select(SIMPLE, r"\(1-\)CRC-32").is_empty());
+    assert_eq!(select(HDR, r"\(1-\)CRC-32").len(), 6);
+}
+
+#[test]
+fn select_live_unknown_size() {
+    let select = |path: &str| {
+        select::select_path(parser::_DocumentReader::new(LIVE), path)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    };
+
+    // skips over the unknown-sized Clusters, & the Segment along with them
+    assert_eq!(
+        select(r"\Segment\Tracks\TrackEntry\CodecID"),
+        vec![
+            Value::String("V_VP9".to_string()),
+            Value::String("A_VORBIS".to_string())
+        ]
+    );
+    assert_eq!(
+        select(r"\EBML\DocType"),
+        vec![Value::String("webm".to_string())]
+    );
+    assert_eq!(
+        select(r"\Segment\Cluster\Timestamp"),
+        vec![
+            Value::UInt(0),
+            Value::UInt(253),
+            Value::UInt(464),
+            Value::UInt(753)
+        ]
+    );
+}
+
+#[test]
+fn void_simple_block() {
+    // find the first Cluster (with its CRC-32) & its first SimpleBlock
+    let mut reader = RawElementReader::new(HDR);
+    let mut cluster: Option<RawElement> = None;
+    let simple_block = loop {
+        let element = reader.next().unwrap().unwrap();
+        match element.id {
+            0x18538067 => reader.descend().unwrap(), // Segment
+            0x1F43B675 => {
+                cluster = Some(element);
+                reader.descend().unwrap();
+            }
+            0xA3 => break element,
+            _ => {}
+        }
+    };
+    let cluster = cluster.unwrap();
+
+    // the file's CRC-32 is already up to date
+    let mut editor = ElementEditor::new(std::io::Cursor::new(HDR.to_vec()));
+    assert!(editor.update_crc(&cluster).unwrap());
+    assert_eq!(editor.into_inner().get_ref(), HDR);
+
+    let mut editor = ElementEditor::new(std::io::Cursor::new(HDR.to_vec()));
+    let void = editor
+        .replace_with_void(&simple_block, Some(&cluster))
+        .unwrap();
+    let edited = editor.into_inner().into_inner();
+    assert_eq!(edited.len(), HDR.len());
+    assert_eq!(void.end_offset(), simple_block.end_offset());
+
+    let entries = dyn_parser()
+        .reader(&edited[..])
+        .map(|entry| entry.unwrap())
+        .filter(|entry| entry.element.offset == simple_block.offset)
+        .map(|entry| entry.path)
+        .collect::<Vec<_>>();
+    assert_eq!(entries, vec![r"\Segment\Cluster\Void"]);
+
+    // the updated CRC-32 validates the edited Cluster
+    let mut editor = ElementEditor::new(std::io::Cursor::new(edited.clone()));
+    assert!(editor.update_crc(&cluster).unwrap());
+    assert_eq!(editor.into_inner().into_inner(), edited);
+    assert_ne!(edited, HDR);
+}
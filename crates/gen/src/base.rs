@@ -0,0 +1,30 @@
+// The schema-independent parts of the generated crates' `base` module, for use at runtime
+#[path = "../template/src/base/date.rs"]
+pub mod date;
+#[path = "../template/src/base/edit.rs"]
+pub mod edit;
+#[path = "../template/src/base/raw.rs"]
+pub mod raw;
+#[path = "../template/src/base/stream.rs"]
+pub mod stream;
+
+// The template sources written out by `Parsers::write_package`, embedded so that generation
+// doesn't depend on where the calling crate lives. They're kept within this package (with the
+// manifest renamed, so that cargo doesn't take it for a nested package) for it to be publishable.
+pub(crate) const TEMPLATE_BASE_FILES: &[(&str, &str)] = &[
+    ("codec.rs", include_str!("../template/src/base/codec.rs")),
+    ("date.rs", include_str!("../template/src/base/date.rs")),
+    ("edit.rs", include_str!("../template/src/base/edit.rs")),
+    (
+        "element_defs.rs",
+        include_str!("../template/src/base/element_defs.rs"),
+    ),
+    ("events.rs", include_str!("../template/src/base/events.rs")),
+    ("mod.rs", include_str!("../template/src/base/mod.rs")),
+    ("owned.rs", include_str!("../template/src/base/owned.rs")),
+    ("parser.rs", include_str!("../template/src/base/parser.rs")),
+    ("raw.rs", include_str!("../template/src/base/raw.rs")),
+    ("stream.rs", include_str!("../template/src/base/stream.rs")),
+];
+pub(crate) const TEMPLATE_CORE_MOD: &str = include_str!("../template/src/core/mod.rs");
+pub(crate) const TEMPLATE_MANIFEST: &str = include_str!("../template/Cargo.toml.in");
@@ -0,0 +1,725 @@
+use crate::base::stream::serialize;
+pub use crate::base::stream::serialize::SizeWidth;
+use crate::dynamic::{DynElement, DynParser, DynValue};
+use crate::serde_schema::ElementType;
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::num::NonZeroU32;
+
+use serde_json::{Map, Value};
+use xml::reader::{EventReader, ParserConfig, XmlEvent};
+
+// the root tag that wraps the top-level elements of a document in XML
+const XML_ROOT: &str = "EBMLDocument";
+// the longest possible element header: a 4-byte element ID + an 8-byte data size
+const MAX_HEADER_LEN: usize = 12;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConvertError {
+    #[error("IOError: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("XMLError: {0}")]
+    Xml(#[from] xml::reader::Error),
+    #[error("unknown element {1} in {0}")]
+    UnknownElement(String, String),
+    #[error("invalid value for element {0}: {1}")]
+    InvalidValue(String, String),
+    #[error("invalid document structure: {0}")]
+    InvalidStructure(String),
+    #[error("cannot serialize element {0}")]
+    Serialize(String),
+}
+
+/**
+Converts between EBML documents and equivalent XML/JSON trees keyed by element names.
+
+In both formats, master elements hold their children in document order:
+- in JSON, a document (or master element) is an array of single-key objects, e.g.
+  `[{"Files": [{"File": [{"FileName": "file1.txt"}]}]}]`;
+- in XML, the top-level elements are wrapped in a single `<EBMLDocument>` tag, e.g.
+  `<EBMLDocument><Files><File><FileName>file1.txt</FileName></File></Files></EBMLDocument>`.
+
+Dates are written as ISO 8601 timestamps, and binary data as hex strings. Elements that are not in
+the schema (i.e., `DynValue::Unknown`) are left out of both formats, and cannot be written to EBML.
+
+**/
+#[derive(Debug, Clone)]
+pub struct Converter<'a> {
+    parser: &'a DynParser,
+    size_width: SizeWidth,
+    element_size_widths: BTreeMap<u32, SizeWidth>,
+}
+
+impl<'a> Converter<'a> {
+    pub fn new(parser: &'a DynParser) -> Self {
+        Self {
+            parser,
+            size_width: SizeWidth::default(),
+            element_size_widths: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the data size width of all written EBML elements (by default, `SizeWidth::Minimal`).
+    pub fn size_width(mut self, width: SizeWidth) -> Self {
+        self.size_width = width;
+        self
+    }
+
+    /// Sets the data size width of written EBML elements with the given ID, overriding
+    /// `size_width`.
+    pub fn element_size_width(mut self, id: u32, width: SizeWidth) -> Self {
+        self.element_size_widths.insert(id, width);
+        self
+    }
+
+    pub fn to_json(&self, elements: &[DynElement]) -> Value {
+        Value::Array(
+            elements
+                .iter()
+                .filter(|element| element.value != DynValue::Unknown)
+                .map(|element| {
+                    let mut object = Map::new();
+                    object.insert(element.name.clone(), Self::json_value(&element.value));
+                    Value::Object(object)
+                })
+                .collect(),
+        )
+    }
+
+    fn json_value(value: &DynValue) -> Value {
+        match value {
+            DynValue::Master(children) => Value::Array(
+                children
+                    .iter()
+                    .filter(|child| child.value != DynValue::Unknown)
+                    .map(|child| {
+                        let mut object = Map::new();
+                        object.insert(child.name.clone(), Self::json_value(&child.value));
+                        Value::Object(object)
+                    })
+                    .collect(),
+            ),
+            DynValue::UInt(x) => Value::from(*x),
+            DynValue::Int(x) => Value::from(*x),
+            DynValue::Date(x) => Value::from(x.to_string()),
+            DynValue::Float(x) => Value::from(*x),
+            DynValue::String(x) | DynValue::Utf8(x) => Value::from(x.as_str()),
+            DynValue::Binary(x) => Value::from(hex_encode(x)),
+            DynValue::Unknown => unreachable!("unknown elements are left out"),
+        }
+    }
+
+    pub fn from_json(&self, value: &Value) -> Result<Vec<DynElement>, ConvertError> {
+        self.json_children(None, "", value)
+    }
+
+    fn json_children(
+        &self,
+        parent_id: Option<u32>,
+        parent_path: &str,
+        value: &Value,
+    ) -> Result<Vec<DynElement>, ConvertError> {
+        let entries = value.as_array().ok_or_else(|| {
+            ConvertError::InvalidStructure(format!("expected an array in {}", display(parent_path)))
+        })?;
+
+        entries
+            .iter()
+            .map(|entry| {
+                let (name, value) = match entry.as_object() {
+                    Some(object) if object.len() == 1 => object.iter().next().unwrap(),
+                    _ => {
+                        return Err(ConvertError::InvalidStructure(format!(
+                            "expected single-key objects in {}",
+                            display(parent_path)
+                        )))
+                    }
+                };
+                let (id, path) = self.resolve(parent_id, parent_path, name)?;
+
+                let value = match self.element_type(id) {
+                    ElementType::Master => {
+                        DynValue::Master(self.json_children(Some(id), &path, value)?)
+                    }
+                    r#type => Self::json_leaf(r#type, value).ok_or_else(|| {
+                        ConvertError::InvalidValue(path.clone(), value.to_string())
+                    })?,
+                };
+                Ok(DynElement {
+                    name: name.clone(),
+                    id,
+                    path,
+                    value,
+                })
+            })
+            .collect()
+    }
+
+    fn json_leaf(r#type: ElementType, value: &Value) -> Option<DynValue> {
+        Some(match r#type {
+            ElementType::UnsignedInteger => DynValue::UInt(value.as_u64()?),
+            ElementType::SignedInteger => DynValue::Int(value.as_i64()?),
+            ElementType::Date => DynValue::Date(value.as_str()?.parse().ok()?),
+            ElementType::Float => DynValue::Float(value.as_f64()?),
+            ElementType::String => DynValue::String(value.as_str()?.to_string()),
+            ElementType::Utf8 => DynValue::Utf8(value.as_str()?.to_string()),
+            ElementType::Binary => DynValue::Binary(hex_decode(value.as_str()?)?),
+            ElementType::Master => return None,
+        })
+    }
+
+    pub fn write_xml<W: Write>(
+        &self,
+        elements: &[DynElement],
+        mut writer: W,
+    ) -> std::io::Result<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        writeln!(writer, "<{}>", XML_ROOT)?;
+        for element in elements {
+            Self::write_xml_element(element, 1, &mut writer)?;
+        }
+        writeln!(writer, "</{}>", XML_ROOT)
+    }
+
+    fn write_xml_element<W: Write>(
+        element: &DynElement,
+        depth: usize,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        let indent = "  ".repeat(depth);
+        let text = match &element.value {
+            DynValue::Master(children) if children.is_empty() => {
+                return writeln!(writer, "{}<{}/>", indent, element.name);
+            }
+            DynValue::Master(children) => {
+                writeln!(writer, "{}<{}>", indent, element.name)?;
+                for child in children {
+                    Self::write_xml_element(child, depth + 1, writer)?;
+                }
+                return writeln!(writer, "{}</{}>", indent, element.name);
+            }
+            DynValue::UInt(x) => x.to_string(),
+            DynValue::Int(x) => x.to_string(),
+            DynValue::Date(x) => x.to_string(),
+            DynValue::Float(x) => x.to_string(),
+            DynValue::String(x) | DynValue::Utf8(x) => xml_escape(x),
+            DynValue::Binary(x) => hex_encode(x),
+            DynValue::Unknown => return Ok(()),
+        };
+
+        writeln!(
+            writer,
+            "{}<{}>{}</{}>",
+            indent, element.name, text, element.name
+        )
+    }
+
+    pub fn read_xml<R: Read>(&self, reader: R) -> Result<Vec<DynElement>, ConvertError> {
+        let config = ParserConfig::new().trim_whitespace(false);
+        let mut stack: Vec<OpenTag> = Vec::new();
+        let mut result = None;
+
+        for event in EventReader::new_with_config(reader, config) {
+            match event? {
+                XmlEvent::StartElement { name, .. } => {
+                    let name = name.local_name;
+                    let (id, path) = match stack.last() {
+                        None if name == XML_ROOT => (None, String::new()),
+                        None => {
+                            return Err(ConvertError::InvalidStructure(format!(
+                                "expected a root <{}> tag, found <{}>",
+                                XML_ROOT, name
+                            )))
+                        }
+                        Some(parent) => {
+                            if parent.id.is_some_and(|id| !self.parser.is_master(id)) {
+                                return Err(ConvertError::InvalidStructure(format!(
+                                    "non-master element {} holds subelements",
+                                    parent.path
+                                )));
+                            }
+                            let (id, path) = self.resolve(parent.id, &parent.path, &name)?;
+                            (Some(id), path)
+                        }
+                    };
+                    stack.push(OpenTag {
+                        id,
+                        name,
+                        path,
+                        children: Vec::new(),
+                        text: String::new(),
+                    });
+                }
+                XmlEvent::Characters(text) | XmlEvent::CData(text) | XmlEvent::Whitespace(text) => {
+                    if let Some(top) = stack.last_mut() {
+                        top.text.push_str(&text);
+                    }
+                }
+                XmlEvent::EndElement { .. } => {
+                    let OpenTag {
+                        id,
+                        name,
+                        path,
+                        children,
+                        text,
+                    } = stack.pop().expect("XML parser guarantees balanced tags");
+                    let id = match id {
+                        Some(id) => id,
+                        None => {
+                            result = Some(children);
+                            continue;
+                        }
+                    };
+
+                    let value = match self.element_type(id) {
+                        ElementType::Master if text.trim().is_empty() => DynValue::Master(children),
+                        ElementType::Master => {
+                            return Err(ConvertError::InvalidStructure(format!(
+                                "master element {} holds text",
+                                path
+                            )))
+                        }
+                        r#type => Self::text_leaf(r#type, &text)
+                            .ok_or_else(|| ConvertError::InvalidValue(path.clone(), text))?,
+                    };
+                    stack
+                        .last_mut()
+                        .expect("root tag was not closed")
+                        .children
+                        .push(DynElement {
+                            name,
+                            id,
+                            path,
+                            value,
+                        });
+                }
+                _ => (),
+            }
+        }
+
+        result.ok_or_else(|| ConvertError::InvalidStructure(format!("missing <{}> tag", XML_ROOT)))
+    }
+
+    fn text_leaf(r#type: ElementType, text: &str) -> Option<DynValue> {
+        Some(match r#type {
+            ElementType::UnsignedInteger => DynValue::UInt(text.trim().parse().ok()?),
+            ElementType::SignedInteger => DynValue::Int(text.trim().parse().ok()?),
+            ElementType::Date => DynValue::Date(text.trim().parse().ok()?),
+            ElementType::Float => DynValue::Float(text.trim().parse().ok()?),
+            ElementType::String => DynValue::String(text.to_string()),
+            ElementType::Utf8 => DynValue::Utf8(text.to_string()),
+            ElementType::Binary => DynValue::Binary(hex_decode(text)?),
+            ElementType::Master => return None,
+        })
+    }
+
+    fn resolve(
+        &self,
+        parent_id: Option<u32>,
+        parent_path: &str,
+        name: &str,
+    ) -> Result<(u32, String), ConvertError> {
+        let id = self
+            .parser
+            .child_ids(parent_id)
+            .find(|id| self.parser.name(*id) == Some(name))
+            .ok_or_else(|| ConvertError::UnknownElement(display(parent_path), name.to_string()))?;
+
+        Ok((id, format!("{}\\{}", parent_path, name)))
+    }
+
+    fn element_type(&self, id: u32) -> ElementType {
+        self.parser
+            .element_type(id)
+            .expect("resolved IDs are defined in the schema")
+    }
+
+    pub fn to_ebml(&self, elements: &[DynElement]) -> Result<Vec<u8>, ConvertError> {
+        let mut output = Vec::new();
+        for element in elements {
+            self.write_ebml_element(element, &mut output)?;
+        }
+
+        Ok(output)
+    }
+
+    fn write_ebml_element(
+        &self,
+        element: &DynElement,
+        output: &mut Vec<u8>,
+    ) -> Result<(), ConvertError> {
+        let error = || ConvertError::Serialize(element.path.clone());
+
+        let data = match &element.value {
+            DynValue::Master(children) => {
+                let mut data = Vec::new();
+                for child in children {
+                    self.write_ebml_element(child, &mut data)?;
+                }
+                data
+            }
+            // numbers use their shortest encoding
+            DynValue::UInt(x) => {
+                let length = (1..=8)
+                    .find(|&length| serialize::uint(&mut [0u8; 8], *x, length).is_ok())
+                    .expect("all u64 values fit in 8 bytes");
+                let mut data = vec![0u8; length];
+                serialize::uint(&mut data, *x, length).map_err(|_| error())?;
+                data
+            }
+            DynValue::Int(x) => {
+                let length = (1..=8)
+                    .find(|&length| serialize::int(&mut [0u8; 8], *x, length).is_ok())
+                    .expect("all i64 values fit in 8 bytes");
+                let mut data = vec![0u8; length];
+                serialize::int(&mut data, *x, length).map_err(|_| error())?;
+                data
+            }
+            DynValue::Float(x) => {
+                let mut data = vec![0u8; 8];
+                serialize::float64(&mut data, *x, 8).map_err(|_| error())?;
+                data
+            }
+            DynValue::Date(x) => {
+                let mut data = vec![0u8; 8];
+                serialize::date(&mut data, x.nanos(), 8).map_err(|_| error())?;
+                data
+            }
+            DynValue::String(x) if !x.is_ascii() => return Err(error()),
+            DynValue::String(x) | DynValue::Utf8(x) => {
+                let mut data = vec![0u8; x.len()];
+                serialize::string(&mut data, x, x.len()).map_err(|_| error())?;
+                data
+            }
+            DynValue::Binary(x) => {
+                let mut data = vec![0u8; x.len()];
+                serialize::binary(&mut data, x).map_err(|_| error())?;
+                data
+            }
+            DynValue::Unknown => return Err(error()),
+        };
+
+        let mut header = [0u8; MAX_HEADER_LEN];
+        let id = NonZeroU32::new(element.id).ok_or_else(error)?;
+        let width = *self
+            .element_size_widths
+            .get(&element.id)
+            .unwrap_or(&self.size_width);
+        let (_, header_len) =
+            serialize::element_header(&mut header, id, Some(data.len() as u64), width)
+                .map_err(|_| error())?;
+
+        output.extend_from_slice(&header[..header_len]);
+        output.extend_from_slice(&data);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct OpenTag {
+    id: Option<u32>, // `None` -> root tag
+    name: String,
+    path: String,
+    children: Vec<DynElement>,
+    text: String,
+}
+
+fn display(path: &str) -> String {
+    if path.is_empty() {
+        "the root document".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+fn hex_decode(text: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()?;
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| (pair[0] << 4) | pair[1])
+            .collect(),
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser_gen::Builder;
+    use rstest::*;
+
+    const SCHEMA: &str = r#"
+        <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="files-in-ebml-demo" version="1">
+          <element name="Void" path="\(-\)Void" id="0xEC" type="binary"/>
+          <element name="Files" path="\Files" id="0x1946696C" type="master"/>
+          <element name="File" path="\Files\File" id="0x6146" type="master"/>
+          <element name="FileName" path="\Files\File\FileName" id="0x614E" type="utf-8"/>
+          <element name="Size" path="\Files\File\Size" id="0x4653" type="uinteger"/>
+          <element name="Offset" path="\Files\File\Offset" id="0x4650" type="integer"/>
+          <element name="Ratio" path="\Files\File\Ratio" id="0x4652" type="float"/>
+          <element name="ModificationTimestamp" path="\Files\File\ModificationTimestamp" id="0x4654" type="date"/>
+        </EBMLSchema>
+    "#;
+
+    const STREAM: [u8; 44] = [
+        0x19, 0x46, 0x69, 0x6C, // Files element ID
+        0xA7, // Files length = 39
+        0xEC, 0x82, 0xFF, 0xFF, // Void element
+        0x61, 0x46, // File element ID
+        0x9D, // File length = 29
+        0x61, 0x4E, 0x83, 0x61, 0x3C, 0x62, // FileName element = "a<b"
+        0x46, 0x53, 0x82, 0x01, 0x00, // Size element = 256
+        0x46, 0x50, 0x81, 0xFF, // Offset element = -1
+        0x46, 0x52, 0x88, 0x3F, 0xE0, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, // Ratio element = 0.5
+        0x46, 0x54, 0x80, // ModificationTimestamp element = 0
+        0x61, 0x46, 0x80, // File element (empty)
+    ];
+
+    const JSON: &str = r#"[{"Files": [
+        {"Void": "FFFF"},
+        {"File": [
+            {"FileName": "a<b"},
+            {"Size": 256},
+            {"Offset": -1},
+            {"Ratio": 0.5},
+            {"ModificationTimestamp": "2001-01-01T00:00:00Z"}
+        ]},
+        {"File": []}
+    ]}]"#;
+
+    const XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<EBMLDocument>
+  <Files>
+    <Void>FFFF</Void>
+    <File>
+      <FileName>a&lt;b</FileName>
+      <Size>256</Size>
+      <Offset>-1</Offset>
+      <Ratio>0.5</Ratio>
+      <ModificationTimestamp>2001-01-01T00:00:00Z</ModificationTimestamp>
+    </File>
+    <File/>
+  </Files>
+</EBMLDocument>
+"#;
+
+    #[fixture]
+    fn parser() -> DynParser {
+        Builder::new(SCHEMA.as_bytes())
+            .unwrap()
+            .build_dynamic()
+            .unwrap()
+    }
+
+    #[rstest]
+    fn to_json(parser: DynParser) {
+        let converter = Converter::new(&parser);
+        let elements = parser.parse(&STREAM[..]).unwrap();
+
+        assert_eq!(
+            converter.to_json(&elements),
+            serde_json::from_str::<Value>(JSON).unwrap()
+        );
+    }
+
+    #[rstest]
+    fn write_xml(parser: DynParser) {
+        let converter = Converter::new(&parser);
+        let elements = parser.parse(&STREAM[..]).unwrap();
+        let mut output = Vec::new();
+
+        converter.write_xml(&elements, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), XML);
+    }
+
+    #[rstest]
+    fn json_to_ebml(parser: DynParser) {
+        let converter = Converter::new(&parser);
+        let elements = converter
+            .from_json(&serde_json::from_str(JSON).unwrap())
+            .unwrap();
+
+        assert_eq!(elements, parser.parse(&STREAM[..]).unwrap());
+        // zero-length dates are re-encoded with their full width
+        let mut expt_stream = STREAM.to_vec();
+        expt_stream.splice(38..41, [0x46, 0x54, 0x88, 0, 0, 0, 0, 0, 0, 0, 0]);
+        expt_stream[4] += 8;
+        expt_stream[11] += 8;
+        assert_eq!(converter.to_ebml(&elements).unwrap(), expt_stream);
+    }
+
+    #[rstest]
+    fn unknown_elements(parser: DynParser) {
+        let converter = Converter::new(&parser);
+        let stream = [
+            0x19, 0x46, 0x69, 0x6C, 0x84, // Files element header
+            0x5F, 0xFF, 0x81, 0x00, // unknown element
+            0x5F, 0xFF, 0x80, // unknown element
+        ];
+        let elements = parser.parse(&stream[..]).unwrap();
+        let mut output = Vec::new();
+
+        assert_eq!(
+            converter.to_json(&elements),
+            serde_json::json!([{"Files": []}])
+        );
+        converter.write_xml(&elements, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<EBMLDocument>\n  <Files>\n  </Files>\n</EBMLDocument>\n"
+        );
+        assert!(matches!(
+            converter.to_ebml(&elements),
+            Err(ConvertError::Serialize(path)) if path == r"\Files\0x5FFF"
+        ));
+    }
+
+    #[rstest]
+    fn xml_to_ebml(parser: DynParser) {
+        let converter = Converter::new(&parser);
+        let elements = converter.read_xml(XML.as_bytes()).unwrap();
+
+        assert_eq!(elements, parser.parse(&STREAM[..]).unwrap());
+        let stream = converter.to_ebml(&elements).unwrap();
+        assert_eq!(parser.parse(&stream[..]).unwrap(), elements);
+    }
+
+    #[rstest]
+    #[case(SizeWidth::Minimal, &[0xAF])]
+    #[case(SizeWidth::Fixed(2), &[0x40, 0x2F])]
+    #[case(SizeWidth::Fixed(8), &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2F])]
+    #[case(SizeWidth::Reserved(1), &[0x40, 0x2F])]
+    #[case(SizeWidth::Reserved(9), &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2F])]
+    fn to_ebml_element_size_width(
+        parser: DynParser,
+        #[case] width: SizeWidth,
+        #[case] expt_len: &[u8],
+    ) {
+        let elements = parser.parse(&STREAM[..]).unwrap();
+        let minimal = Converter::new(&parser).to_ebml(&elements).unwrap();
+        let converter = Converter::new(&parser).element_size_width(0x1946696C, width);
+        let stream = converter.to_ebml(&elements).unwrap();
+
+        assert_eq!(&stream[..4], &minimal[..4]);
+        assert_eq!(&stream[4..4 + expt_len.len()], expt_len);
+        assert_eq!(&stream[4 + expt_len.len()..], &minimal[5..]);
+        assert_eq!(parser.parse(&stream[..]).unwrap(), elements);
+    }
+
+    #[rstest]
+    fn to_ebml_fixed_size_width(parser: DynParser) {
+        let elements = parser.parse(&STREAM[..]).unwrap();
+        let minimal = Converter::new(&parser).to_ebml(&elements).unwrap();
+        let converter = Converter::new(&parser)
+            .size_width(SizeWidth::Fixed(8))
+            .element_size_width(0xEC, SizeWidth::Minimal);
+        let stream = converter.to_ebml(&elements).unwrap();
+
+        // all 9 elements but the Void element get 7 extra size bytes
+        assert_eq!(stream.len(), minimal.len() + 7 * 8);
+        assert_eq!(&stream[4..12], &[0x01, 0, 0, 0, 0, 0, 0, 0x2F + 7 * 7]);
+        assert_eq!(&stream[12..16], &[0xEC, 0x82, 0xFF, 0xFF]);
+        assert_eq!(parser.parse(&stream[..]).unwrap(), elements);
+    }
+
+    #[rstest]
+    #[case(SizeWidth::Fixed(0))]
+    #[case(SizeWidth::Fixed(9))]
+    fn to_ebml_invalid_size_width(parser: DynParser, #[case] width: SizeWidth) {
+        let elements = parser.parse(&STREAM[..]).unwrap();
+        let converter = Converter::new(&parser).element_size_width(0x614E, width);
+
+        assert_eq!(
+            converter.to_ebml(&elements).unwrap_err().to_string(),
+            r"cannot serialize element \Files\File\FileName"
+        );
+    }
+
+    #[rstest]
+    fn to_ebml_fixed_size_width_overflow(parser: DynParser) {
+        let json = format!(
+            r#"[{{"Files": [{{"File": [{{"FileName": "{}"}}]}}]}}]"#,
+            "x".repeat(200)
+        );
+        let elements = Converter::new(&parser)
+            .from_json(&serde_json::from_str(&json).unwrap())
+            .unwrap();
+        let converter = Converter::new(&parser).size_width(SizeWidth::Fixed(1));
+
+        assert!(matches!(
+            converter.to_ebml(&elements),
+            Err(ConvertError::Serialize(path)) if path == r"\Files\File\FileName"
+        ));
+    }
+
+    #[rstest]
+    #[case(
+        r#"{"Files": []}"#,
+        "invalid document structure: expected an array in the root document"
+    )]
+    #[case(r#"[{"File": []}]"#, "unknown element File in the root document")]
+    #[case(
+        r#"[{"Files": [{"File": [{"Size": -1}]}]}]"#,
+        r"invalid value for element \Files\File\Size: -1"
+    )]
+    #[case(
+        r#"[{"Files": [{"Void": "F"}]}]"#,
+        r#"invalid value for element \Files\Void: "F""#
+    )]
+    #[case(
+        r#"[{"Files": [], "Void": ""}]"#,
+        "invalid document structure: expected single-key objects in the root document"
+    )]
+    fn from_json_err(parser: DynParser, #[case] json: &str, #[case] expt_error: &str) {
+        let converter = Converter::new(&parser);
+        let error = converter
+            .from_json(&serde_json::from_str(json).unwrap())
+            .unwrap_err();
+
+        assert_eq!(format!("{}", error), expt_error);
+    }
+
+    #[rstest]
+    #[case(
+        "<Files/>",
+        "invalid document structure: expected a root <EBMLDocument> tag, found <Files>"
+    )]
+    #[case(
+        "<EBMLDocument><Files><Size>1</Size></Files></EBMLDocument>",
+        r"unknown element Size in \Files"
+    )]
+    #[case(
+        "<EBMLDocument><Files>text</Files></EBMLDocument>",
+        r"invalid document structure: master element \Files holds text"
+    )]
+    #[case(
+        "<EBMLDocument><Void><Files/></Void></EBMLDocument>",
+        r"invalid document structure: non-master element \Void holds subelements"
+    )]
+    fn read_xml_err(parser: DynParser, #[case] xml: &str, #[case] expt_error: &str) {
+        let converter = Converter::new(&parser);
+        let error = converter.read_xml(xml.as_bytes()).unwrap_err();
+
+        assert_eq!(format!("{}", error), expt_error);
+    }
+}
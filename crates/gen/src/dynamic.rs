@@ -0,0 +1,511 @@
+use crate::base::date::Date;
+use crate::base::raw::{RawElement, RawElementReader, RawReaderError};
+use crate::base::stream::parse;
+use crate::serde_schema::{Element, ElementType};
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::BufRead;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynElement {
+    pub name: String,
+    pub id: u32,
+    pub path: String,
+    pub value: DynValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynValue {
+    Master(Vec<DynElement>),
+    UInt(u64),
+    Int(i64),
+    Float(f64),
+    Date(Date),
+    String(String),
+    Utf8(String),
+    Binary(Vec<u8>),
+    Unknown, // an element that is not in the schema, whose data is skipped
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DynParseError {
+    #[error("{0}")]
+    Raw(#[from] RawReaderError),
+    #[error("invalid subelement id {1:#X} (parent id = {:?})", *.0)]
+    InvalidChildId(Option<u32>, u32),
+    #[error("invalid data for element {0} at offset {1}")]
+    InvalidData(String, u64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynEntry {
+    pub element: RawElement,
+    pub name: String,
+    pub path: String,
+    pub value: Option<DynValue>, // `None` -> master element, whose children follow
+}
+
+/**
+The `DynParser` object reads documents according to a schema loaded at runtime, without any code
+generation. It resolves the element hierarchy identically to the generated parsers (see
+`Builder::build_dynamic`), and reads every element of a document into a tree of `DynElement`'s.
+
+Elements with IDs that are not in the schema are read as `DynValue::Unknown`, named after their
+ID; their data is skipped.
+
+**/
+#[derive(Debug, Clone)]
+pub struct DynParser {
+    // ID = `None` -> root document
+    elements: BTreeMap<u32, Element>,
+    children: BTreeMap<Option<u32>, BTreeSet<u32>>,
+}
+
+impl DynParser {
+    pub(crate) fn new(
+        elements: BTreeMap<u32, Element>,
+        children: BTreeMap<Option<u32>, BTreeSet<u32>>,
+    ) -> Self {
+        Self { elements, children }
+    }
+
+    pub fn name(&self, id: u32) -> Option<&str> {
+        self.elements.get(&id).map(|elem| elem.name.as_str())
+    }
+
+    pub fn is_master(&self, id: u32) -> bool {
+        self.elements
+            .get(&id)
+            .is_some_and(|elem| elem.r#type == ElementType::Master)
+    }
+
+    pub(crate) fn element_type(&self, id: u32) -> Option<ElementType> {
+        self.elements.get(&id).map(|elem| elem.r#type.clone())
+    }
+
+    pub fn child_ids(&self, parent_id: Option<u32>) -> impl Iterator<Item = u32> + '_ {
+        self.children.get(&parent_id).into_iter().flatten().copied()
+    }
+
+    pub fn is_child(&self, parent_id: Option<u32>, id: u32) -> bool {
+        self.children
+            .get(&parent_id)
+            .is_some_and(|child_ids| child_ids.contains(&id))
+    }
+
+    pub fn reader<R: BufRead>(&self, reader: R) -> DynReader<'_, R> {
+        DynReader {
+            parser: self,
+            reader: RawElementReader::new(reader),
+            parents: vec![OpenMaster {
+                id: None,
+                path: String::new(),
+                unknown_sized: false,
+            }],
+            pending: None,
+        }
+    }
+
+    pub fn parse<R: BufRead>(&self, reader: R) -> Result<Vec<DynElement>, DynParseError> {
+        let mut stack = vec![(None, Vec::new())];
+
+        for entry in self.reader(reader) {
+            let entry = entry?;
+
+            while stack.len() > entry.element.depth + 1 {
+                Self::close_master(&mut stack);
+            }
+            match entry.value {
+                None => stack.push((Some(entry), Vec::new())),
+                Some(value) => stack
+                    .last_mut()
+                    .expect("stack always holds the root")
+                    .1
+                    .push(DynElement {
+                        name: entry.name,
+                        id: entry.element.id,
+                        path: entry.path,
+                        value,
+                    }),
+            }
+        }
+
+        while stack.len() > 1 {
+            Self::close_master(&mut stack);
+        }
+        Ok(stack.pop().expect("stack always holds the root").1)
+    }
+
+    fn close_master(stack: &mut Vec<(Option<DynEntry>, Vec<DynElement>)>) {
+        let (master, children) = stack.pop().expect("cannot close the root");
+        let master = master.expect("only the root has no entry");
+
+        stack
+            .last_mut()
+            .expect("stack always holds the root")
+            .1
+            .push(DynElement {
+                name: master.name,
+                id: master.element.id,
+                path: master.path,
+                value: DynValue::Master(children),
+            });
+    }
+
+    fn parse_value(
+        elem: &Element,
+        raw_elem: &RawElement,
+        data: &[u8],
+    ) -> Result<DynValue, DynParseError> {
+        let length = data.len();
+        let value = match elem.r#type {
+            ElementType::Master => unreachable!("master elements have no value"),
+            // numeric elements of zero length default to zero
+            ElementType::UnsignedInteger if length <= 8 => {
+                parse::uint(data, length).map(|(_, x)| DynValue::UInt(x))
+            }
+            ElementType::SignedInteger if length == 0 => Ok(DynValue::Int(0)),
+            ElementType::SignedInteger if length <= 8 => {
+                parse::int(data, length).map(|(_, x)| DynValue::Int(x))
+            }
+            ElementType::Date if length == 0 => Ok(DynValue::Date(Date::EPOCH)),
+            ElementType::Date if length == 8 => {
+                parse::date(data, length).map(|(_, x)| DynValue::Date(Date::from_nanos(x)))
+            }
+            ElementType::Float if length == 0 => Ok(DynValue::Float(0.0)),
+            ElementType::Float if length == 4 => {
+                parse::float32(data, length).map(|(_, x)| DynValue::Float(x.into()))
+            }
+            ElementType::Float if length == 8 => {
+                parse::float64(data, length).map(|(_, x)| DynValue::Float(x))
+            }
+            ElementType::String => {
+                parse::ascii_str(data, length).map(|(_, x)| DynValue::String(x.to_string()))
+            }
+            ElementType::Utf8 => {
+                parse::unicode_str(data, length).map(|(_, x)| DynValue::Utf8(x.to_string()))
+            }
+            ElementType::Binary => Ok(DynValue::Binary(data.to_vec())),
+            _ => Err(nom::Err::Error(())),
+        };
+
+        value.map_err(|_| DynParseError::InvalidData(elem.name.clone(), raw_elem.offset))
+    }
+}
+
+#[derive(Debug)]
+struct OpenMaster {
+    id: Option<u32>,
+    path: String,
+    unknown_sized: bool,
+}
+
+/**
+An iterator over the elements of a document in document order, as read by a `DynParser`.
+
+The children of a master element follow directly after its own entry, unless they are skipped
+with `skip_children` before the next entry is read.
+
+**/
+#[derive(Debug)]
+pub struct DynReader<'a, R> {
+    parser: &'a DynParser,
+    reader: RawElementReader<R>,
+    parents: Vec<OpenMaster>, // the first item is the root document
+    pending: Option<OpenMaster>,
+}
+
+impl<'a, R: BufRead> DynReader<'a, R> {
+    pub fn skip_children(&mut self) -> Result<(), DynParseError> {
+        if self.pending.take().is_some() {
+            self.reader.skip_data()?;
+        }
+        Ok(())
+    }
+
+    fn read_next(&mut self) -> Result<Option<DynEntry>, DynParseError> {
+        if let Some(master) = self.pending.take() {
+            self.reader.descend()?;
+            self.parents.push(master);
+        }
+        let mut raw_elem = match self.reader.next() {
+            Some(raw_elem) => raw_elem?,
+            None => return Ok(None),
+        };
+
+        // close all masters that ended before this element
+        self.parents.truncate(raw_elem.depth + 1);
+        // unknown-sized masters end at the first known element that is not a valid child
+        let is_known = self.parser.elements.contains_key(&raw_elem.id);
+        while self.parents.len() > 1 && is_known {
+            let parent = self.parents.last().expect("stack always holds the root");
+            if !parent.unknown_sized || self.parser.is_child(parent.id, raw_elem.id) {
+                break;
+            }
+            self.reader.ascend()?;
+            raw_elem.depth -= 1;
+            self.parents.pop();
+        }
+
+        let parent = self.parents.last().expect("stack always holds the root");
+        let elem = match self.parser.elements.get(&raw_elem.id) {
+            Some(elem) => elem,
+            None => {
+                // the data is skipped on the next read
+                let name = format!("{:#X}", raw_elem.id);
+                return Ok(Some(DynEntry {
+                    element: raw_elem,
+                    path: format!("{}\\{}", parent.path, name),
+                    name,
+                    value: Some(DynValue::Unknown),
+                }));
+            }
+        };
+        if !self.parser.is_child(parent.id, raw_elem.id) {
+            return Err(DynParseError::InvalidChildId(parent.id, raw_elem.id));
+        }
+        let path = format!("{}\\{}", parent.path, elem.name);
+
+        let value = if elem.r#type == ElementType::Master {
+            self.pending = Some(OpenMaster {
+                id: Some(elem.id),
+                path: path.clone(),
+                unknown_sized: raw_elem.data_len.is_none(),
+            });
+            None
+        } else {
+            let data = self.reader.read_data()?;
+            Some(DynParser::parse_value(elem, &raw_elem, &data)?)
+        };
+
+        Ok(Some(DynEntry {
+            element: raw_elem,
+            name: elem.name.clone(),
+            path,
+            value,
+        }))
+    }
+}
+
+impl<'a, R: BufRead> Iterator for DynReader<'a, R> {
+    type Item = Result<DynEntry, DynParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser_gen::Builder;
+    use rstest::*;
+
+    const SCHEMA: &str = r#"
+        <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="files-in-ebml-demo" version="1">
+          <element name="Void" path="\(-\)Void" id="0xEC" type="binary"/>
+          <element name="Files" path="\Files" id="0x1946696C" type="master" unknownsizeallowed="1"/>
+          <element name="File" path="\Files\File" id="0x6146" type="master" minOccurs="1"/>
+          <element name="FileName" path="\Files\File\FileName" id="0x614E" type="utf-8" minOccurs="1"/>
+          <element name="ModificationTimestamp" path="\Files\File\ModificationTimestamp" id="0x4654" type="date" minOccurs="1"/>
+        </EBMLSchema>
+    "#;
+
+    #[fixture]
+    fn parser() -> DynParser {
+        Builder::new(SCHEMA.as_bytes())
+            .unwrap()
+            .build_dynamic()
+            .unwrap()
+    }
+
+    fn file_name(path: &str, name: &str) -> DynElement {
+        DynElement {
+            name: "FileName".to_string(),
+            id: 0x614E,
+            path: path.to_string(),
+            value: DynValue::Utf8(name.to_string()),
+        }
+    }
+
+    #[rstest]
+    fn parse_document(parser: DynParser) {
+        let stream = [
+            0x19, 0x46, 0x69, 0x6C, // Files element ID
+            0x8D, // Files length = 13
+            0xEC, 0x81, 0x00, // Void element
+            0x61, 0x46, // File element ID
+            0x87, // File length = 7
+            0x61, 0x4E, 0x82, 0x61, 0x62, // FileName element = "ab"
+            0xEC, 0x80, // Void element
+            0xEC, 0x80, // Void element
+        ];
+
+        let void = |path: &str| DynElement {
+            name: "Void".to_string(),
+            id: 0xEC,
+            path: path.to_string(),
+            value: DynValue::Binary(Vec::new()),
+        };
+
+        assert_eq!(
+            parser.parse(&stream[..]).unwrap(),
+            vec![
+                DynElement {
+                    name: "Files".to_string(),
+                    id: 0x1946696C,
+                    path: r"\Files".to_string(),
+                    value: DynValue::Master(vec![
+                        DynElement {
+                            value: DynValue::Binary(vec![0x00]),
+                            ..void(r"\Files\Void")
+                        },
+                        DynElement {
+                            name: "File".to_string(),
+                            id: 0x6146,
+                            path: r"\Files\File".to_string(),
+                            value: DynValue::Master(vec![
+                                file_name(r"\Files\File\FileName", "ab"),
+                                void(r"\Files\File\Void"),
+                            ]),
+                        },
+                    ]),
+                },
+                void(r"\Void"),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn reader_skip_children(parser: DynParser) {
+        let stream = [
+            0x19, 0x46, 0x69, 0x6C, // Files element ID
+            0x88, // Files length = 8
+            0x61, 0x46, // File element ID
+            0x85, // File length = 5
+            0x61, 0x4E, 0x82, 0x61, 0x62, // FileName element = "ab"
+            0xEC, 0x80, // Void element
+        ];
+        let mut reader = parser.reader(&stream[..]);
+        let mut result = Vec::new();
+
+        while let Some(entry) = reader.next() {
+            let entry = entry.unwrap();
+            result.push((
+                entry.path.clone(),
+                entry.element.offset,
+                entry.element.depth,
+            ));
+            if entry.name == "File" {
+                reader.skip_children().unwrap();
+            }
+        }
+
+        assert_eq!(
+            result,
+            vec![
+                (r"\Files".to_string(), 0, 0),
+                (r"\Files\File".to_string(), 5, 1),
+                (r"\Void".to_string(), 13, 0),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn parse_unknown_size(parser: DynParser) {
+        let stream = [
+            0x19, 0x46, 0x69, 0x6C, // Files element ID
+            0xFF, // Files length = unknown
+            0x61, 0x46, // File element ID
+            0x85, // File length = 5
+            0x61, 0x4E, 0x82, 0x61, 0x62, // FileName element = "ab"
+            0x19, 0x46, 0x69, 0x6C, // Files element ID
+            0x80, // Files length = 0
+        ];
+
+        let result = parser.parse(&stream[..]).unwrap();
+        assert_eq!(
+            result.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(),
+            vec![r"\Files", r"\Files"]
+        );
+        assert_eq!(
+            result[0].value,
+            DynValue::Master(vec![DynElement {
+                name: "File".to_string(),
+                id: 0x6146,
+                path: r"\Files\File".to_string(),
+                value: DynValue::Master(vec![file_name(r"\Files\File\FileName", "ab")]),
+            }])
+        );
+    }
+
+    #[rstest]
+    fn parse_unknown_id(parser: DynParser) {
+        let stream = [
+            0x42, 0x86, 0x81, 0x01, // unknown element
+            0x19, 0x46, 0x69, 0x6C, // Files element ID
+            0xFF, // Files length = unknown
+            0x5F, 0xFF, 0x82, 0x00, 0x00, // unknown element
+            0x61, 0x46, 0x80, // File element
+        ];
+        let unknown = |path: &str, id: u32| DynElement {
+            name: format!("{:#X}", id),
+            id,
+            path: path.to_string(),
+            value: DynValue::Unknown,
+        };
+
+        assert_eq!(
+            parser.parse(&stream[..]).unwrap(),
+            vec![
+                unknown(r"\0x4286", 0x4286),
+                DynElement {
+                    name: "Files".to_string(),
+                    id: 0x1946696C,
+                    path: r"\Files".to_string(),
+                    // the unknown element doesn't end the unknown-sized parent
+                    value: DynValue::Master(vec![
+                        unknown(r"\Files\0x5FFF", 0x5FFF),
+                        DynElement {
+                            name: "File".to_string(),
+                            id: 0x6146,
+                            path: r"\Files\File".to_string(),
+                            value: DynValue::Master(vec![]),
+                        },
+                    ]),
+                },
+            ]
+        );
+    }
+
+    #[rstest]
+    fn reader_unknown_id(parser: DynParser) {
+        let stream = [
+            0x19, 0x46, 0x69, 0x6C, // Files element ID
+            0x84, // Files length = 4
+            0x5F, 0xFF, 0x81, 0x00, // unknown element
+        ];
+        let entries = parser
+            .reader(&stream[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let entry = &entries[1];
+        assert_eq!(
+            (
+                entry.element.id,
+                entry.element.data_len,
+                entry.element.offset
+            ),
+            (0x5FFF, Some(1), 5)
+        );
+        assert_eq!(entry.value, Some(DynValue::Unknown));
+    }
+
+    #[rstest]
+    #[case(&[0x61, 0x46, 0x80], "invalid subelement id 0x6146 (parent id = None)")]
+    #[case(&[0x19, 0x46, 0x69, 0x6C, 0x87, 0x61, 0x46, 0x84, 0x46, 0x54, 0x81, 0x00], "invalid data for element ModificationTimestamp at offset 8")]
+    fn parse_err(parser: DynParser, #[case] stream: &'static [u8], #[case] expt_error: &str) {
+        assert_eq!(format!("{}", parser.parse(stream).unwrap_err()), expt_error);
+    }
+}
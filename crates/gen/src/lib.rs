@@ -1,4 +1,8 @@
+pub mod base;
+pub mod convert;
+pub mod dynamic;
 #[macro_use]
 pub mod parser_gen;
 pub mod serde_schema;
+pub mod strict_schema;
 mod trie;
@@ -1,22 +1,18 @@
 // interface loosely based on that of bindgen: https://crates.io/crates/bindgen
 
-use crate::serde_schema::{from_reader, EbmlSchema, Element, ElementType};
+use crate::base::date::{Date, DateError};
+use crate::dynamic::DynParser;
+use crate::serde_schema::{from_reader, EbmlSchema, Element, ElementType, ElementValue, Extension};
+use crate::strict_schema::StrictSchemaError;
 use crate::trie::Trie;
 
 use std::collections::{BTreeMap, BTreeSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use core::ops::{Bound, RangeBounds};
 use core::str::FromStr;
 
-/**
-The `Builder` object has the following responsibilities:
-
-- validate the schema objects read directly from the schema
-- perform all pre-processing in advance required to write the source routines for parsing
-
-**/
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct GlobalPlaceholder {
     lower_bound: u64,
@@ -98,7 +94,7 @@ impl FromStr for PathAtoms {
             s.replace("\\)", ")") // global parent occurrence also uses '\' -> remove...
                 .strip_prefix('\\') // each path atom starts with \ -> remove the first...
                 .ok_or(Self::Err::MissingPathDivider)?
-                .split(|c| c == '\\') // ...then split on '\'
+                .split('\\') // ...then split on '\'
                 .map(|s| {
                     let divider = s.find(')').map_or(0, |i| i + 1);
                     let (s1, s2) = s.split_at(divider);
@@ -120,31 +116,655 @@ pub enum PathAtomsParserError {
     InvalidGlobalPlaceholder(<GlobalPlaceholder as FromStr>::Err),
 }
 
+// Recursive elements are flagged either by their `recursive` attribute, or by a '+' before their
+// name in the last path atom
+fn is_recursive(elem: &Element) -> bool {
+    elem.recursive == Some(true)
+        || elem
+            .path
+            .rsplit('\\')
+            .next()
+            .is_some_and(|name| name.starts_with('+'))
+}
+
+// Parses a date in a schema `default`/`range` attribute: either an ISO 8601 timestamp, or the raw
+// number of nanoseconds relative to the EBML epoch
+fn parse_schema_date(s: &str) -> Result<Date, DateError> {
+    match s.trim().parse::<i64>() {
+        Ok(nanos) => Ok(Date::from_nanos(nanos)),
+        Err(_) => s.trim().parse(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateRange {
+    IsExactly(Date),
+    Excludes(Date),
+    IsWithin(Bound<Date>, Bound<Date>),
+}
+
+impl FromStr for DateRange {
+    type Err = DateRangeParserError;
+
+    // Accepts `X`, `not X`, and up to two comparisons `>X`, `>=X`, `<X`, `<=X` joined by `,`.
+    // (The `X-Y` form is ambiguous for dates, which contain '-'.)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(s) = s.strip_prefix("not ") {
+            return Ok(Self::Excludes(parse_schema_date(s)?));
+        }
+        if !s.starts_with(['<', '>']) {
+            return Ok(Self::IsExactly(parse_schema_date(s)?));
+        }
+
+        let (mut lower, mut upper) = (Bound::Unbounded, Bound::Unbounded);
+        for part in s.split(',').map(str::trim) {
+            let (bound, s) = if let Some(s) = part.strip_prefix(">=") {
+                (&mut lower, Bound::Included(s))
+            } else if let Some(s) = part.strip_prefix('>') {
+                (&mut lower, Bound::Excluded(s))
+            } else if let Some(s) = part.strip_prefix("<=") {
+                (&mut upper, Bound::Included(s))
+            } else if let Some(s) = part.strip_prefix('<') {
+                (&mut upper, Bound::Excluded(s))
+            } else {
+                return Err(Self::Err::InvalidBound(part.to_string()));
+            };
+            if *bound != Bound::Unbounded {
+                return Err(Self::Err::DuplicateBound(part.to_string()));
+            }
+            *bound = match s {
+                Bound::Included(s) => Bound::Included(parse_schema_date(s)?),
+                Bound::Excluded(s) => Bound::Excluded(parse_schema_date(s)?),
+                Bound::Unbounded => unreachable!(),
+            };
+        }
+
+        Ok(Self::IsWithin(lower, upper))
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum DateRangeParserError {
+    #[error("invalid date: {0}")]
+    InvalidDate(#[from] DateError),
+    #[error("invalid bound {0}")]
+    InvalidBound(String),
+    #[error("duplicate bound {0}")]
+    DuplicateBound(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RustEdition {
+    #[default]
+    Edition2018,
+    Edition2021,
+}
+
+impl RustEdition {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Edition2018 => "2018",
+            Self::Edition2021 => "2021",
+        }
+    }
+}
+
+/**
+How `Builder::generate` handles the schema rule violations that it can work around, i.e. attributes
+on elements whose type doesn't allow them (e.g., a `range` on a binary element), a `default` on an
+element with a `minOccurs` above 1, and the ID 0x80, whose VINT_DATA is all 0's (but which Matroska
+keeps for ChapterDisplay). Other violations (e.g., duplicate IDs) always fail.
+
+**/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaStrictness {
+    /// Fails on any violation.
+    #[default]
+    Strict,
+    /// Ignores the offending attributes, printing each violation as a `cargo:warning` (i.e., as a
+    /// warning of the build script calling the builder).
+    Warn,
+    /// Ignores the offending attributes silently.
+    Lenient,
+}
+
+// whether `path` is `root` itself or lies in the subtree under `root`
+fn is_path_within(path: &str, root: &str) -> bool {
+    path.strip_prefix(root)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('\\'))
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !s.starts_with(|c: char| c.is_ascii_digit())
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+// The identifiers declared by the generated modules apart from the per-element types, & those
+// imported into them (including the prelude types & generic parameters that the generated code
+// refers to by name). Element type names must not collide with them, by themselves or with any of
+// the `GENERATED_SUFFIXES`.
+const RESERVED_IDENTIFIERS: &[&str] = &[
+    // generated
+    "_Document",
+    "Decoder",
+    "EventCodec",
+    "Events",
+    "Parent", // the `{Name}NextStates`/`{Name}NextReaders` variant of the parent
+    "Readers",
+    "Select",
+    "States",
+    "Visitor",
+    // imported
+    "BinaryElementDef",
+    "Bound",
+    "BoundTo",
+    "BufRead",
+    "BytesMut",
+    "CodecError",
+    "Date",
+    "DateElementDef",
+    "Drain",
+    "ElementDef",
+    "ElementReader",
+    "ElementState",
+    "Event",
+    "EventEncoder",
+    "FloatElementDef",
+    "From",
+    "IntElementDef",
+    "IntoReader",
+    "MasterElementDef",
+    "NextReaderNavigation",
+    "NextStateNavigation",
+    "OffsetReader",
+    "PhantomData",
+    "Range",
+    "ReaderDataParser",
+    "ReaderError",
+    "SkipReaderNavigation",
+    "SkipStateNavigation",
+    "StateDataParser",
+    "StateError",
+    "StringElementDef",
+    "TryInto",
+    "UIntElementDef",
+    "Utf8ElementDef",
+    "Value",
+    "VecDeque",
+    "Visit",
+    // prelude & generic parameters
+    "Box",
+    "Default",
+    "IntoIterator",
+    "Iterator",
+    "Option",
+    "Result",
+    "String",
+    "Vec",
+    "E",
+    "I",
+    "R",
+    "W",
+];
+
+// The suffixes of the types generated per element (e.g., `EBML`, `EBMLDef`, `EBMLState`, ...)
+const GENERATED_SUFFIXES: &[&str] = &[
+    "",
+    "Def",
+    "State",
+    "Reader",
+    "NextStates",
+    "NextReaders",
+    "PrevStates",
+    "PrevReaders",
+];
+
+fn is_reserved_type_name(name: &str) -> bool {
+    GENERATED_SUFFIXES
+        .iter()
+        .any(|suffix| RESERVED_IDENTIFIERS.contains(&format!("{}{}", name, suffix).as_str()))
+}
+
+/**
+Maps a schema element name to the stem of its generated Rust type names.
+
+Names that are already identifiers are kept as-is. Otherwise, the name is split on its `-`/`.`
+separators and the parts are joined in UpperCamelCase, with all-uppercase parts of three or more
+letters treated as acronyms (e.g., `CRC-32` -> `Crc32`). Names starting with a digit, & names that
+are keywords or reserved by the generated code, are disambiguated with `Element` (e.g., `3D` ->
+`Element3D`, `Self` -> `SelfElement`), repeatedly if need be (e.g., `Date` -> `DateElementElement`,
+since `DateElementDef` is reserved too).
+
+**/
+fn rust_type_name(prefix: &str, elem_name: &str) -> String {
+    let mut name = prefix.to_string();
+    if is_identifier(elem_name) {
+        name.push_str(elem_name);
+    } else {
+        for part in elem_name
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|part| !part.is_empty())
+        {
+            let is_acronym = part.len() > 2
+                && part.contains(|c: char| c.is_ascii_uppercase())
+                && !part.contains(|c: char| c.is_ascii_lowercase());
+            let mut chars = part.chars();
+            name.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+            if is_acronym {
+                name.extend(chars.map(|c| c.to_ascii_lowercase()));
+            } else {
+                name.extend(chars);
+            }
+        }
+    }
+
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert_str(0, "Element");
+    }
+    while RUST_KEYWORDS.contains(&name.as_str()) || is_reserved_type_name(&name) {
+        name.push_str("Element");
+    }
+    name
+}
+
+// The name of an owned type's field holding a child element (e.g., `DocType` -> `doc_type`), with
+// keywords suffixed by `_` (e.g., `Type` -> `type_`)
+fn rust_field_name(elem_name: &str) -> String {
+    let mut name = rust_method_name(elem_name);
+    if RUST_KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+    name
+}
+
+// The type that a leaf element's data is read as, the `Value` variant that holds it, & the
+// expression converting a read `value` into it (for the generated iterators)
+fn owned_value(elem_type: &ElementType) -> Option<(&'static str, &'static str, &'static str)> {
+    match elem_type {
+        ElementType::Master => None,
+        ElementType::UnsignedInteger => Some(("u64", "UInt", "value")),
+        ElementType::SignedInteger => Some(("i64", "Int", "value")),
+        ElementType::Float => Some(("f64", "Float", "value")),
+        ElementType::Date => Some(("Date", "Date", "value")),
+        ElementType::String => Some(("&str", "String", "value.to_owned()")),
+        ElementType::Utf8 => Some(("&str", "Utf8", "value.to_owned()")),
+        ElementType::Binary => Some(("&[u8]", "Binary", "value.to_vec()")),
+    }
+}
+
+// The snake_case form of an element's (unprefixed) Rust type name, for use in method names (e.g.,
+// `EBMLMaxIDLength` -> `ebml_max_id_length`)
+fn rust_method_name(elem_name: &str) -> String {
+    let type_name = rust_type_name("", elem_name).chars().collect::<Vec<_>>();
+    let mut name = String::new();
+    for (i, c) in type_name.iter().enumerate() {
+        if i > 0 && c.is_ascii_uppercase() {
+            let prev = type_name[i - 1];
+            let next_is_lower = type_name
+                .get(i + 1)
+                .is_some_and(|next| next.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_is_lower)
+            {
+                name.push('_');
+            }
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+#[derive(Debug, Clone)]
+struct Options {
+    allowlist: Vec<String>,
+    blocklist: Vec<String>,
+    module_name: String,
+    type_prefix: String,
+    derives: Vec<String>,
+    readers: bool,
+    strictness: SchemaStrictness,
+    owned: bool,
+    writers: bool,
+    validation: bool,
+    edition: RustEdition,
+    schema_path: Option<PathBuf>,
+    extension_filters: Vec<ExtensionFilter>,
+    reject_excluded: bool,
+    ebml_header: bool,
+}
+
+// A predicate on the `<extension>`s of an element, which decides whether the element is generated
+#[derive(Clone)]
+struct ExtensionFilter(Rc<ExtensionPredicate>);
+
+type ExtensionPredicate = dyn Fn(&[Extension]) -> bool;
+
+impl std::fmt::Debug for ExtensionFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ExtensionFilter")
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            allowlist: Vec::new(),
+            blocklist: Vec::new(),
+            module_name: "core".to_string(),
+            type_prefix: String::new(),
+            derives: Vec::new(),
+            readers: true,
+            strictness: SchemaStrictness::default(),
+            owned: false,
+            writers: false,
+            validation: false,
+            edition: RustEdition::default(),
+            schema_path: None,
+            extension_filters: Vec::new(),
+            reject_excluded: false,
+            ebml_header: false,
+        }
+    }
+}
+
+impl Options {
+    // ancestors of allowlisted elements are kept, so that the allowlisted elements stay reachable
+    fn keeps(&self, path: &str) -> bool {
+        (self.allowlist.is_empty()
+            || self
+                .allowlist
+                .iter()
+                .any(|root| is_path_within(path, root) || is_path_within(root, path)))
+            && !self.blocklist.iter().any(|root| is_path_within(path, root))
+    }
+
+    fn keeps_extensions(&self, elem: &Element) -> bool {
+        if self.extension_filters.is_empty() {
+            return true;
+        }
+        let extensions: Vec<Extension> = elem
+            .metadata
+            .iter()
+            .flatten()
+            .filter_map(|value| match value {
+                ElementValue::Extension(extension) => Some(extension.clone()),
+                _ => None,
+            })
+            .collect();
+        self.extension_filters
+            .iter()
+            .all(|filter| (filter.0)(&extensions))
+    }
+
+    fn validate(&self) -> Result<(), BuilderGenerateError> {
+        if self.module_name.is_empty()
+            || self.module_name == "base"
+            || !is_identifier(&self.module_name)
+        {
+            return Err(BuilderGenerateError::InvalidOption(
+                "module_name",
+                self.module_name.clone(),
+            ));
+        }
+        if !is_identifier(&self.type_prefix) {
+            return Err(BuilderGenerateError::InvalidOption(
+                "type_prefix",
+                self.type_prefix.clone(),
+            ));
+        }
+        // writers & validation are generated for the owned types
+        if !self.owned && self.writers {
+            return Err(BuilderGenerateError::InvalidOption(
+                "writers",
+                "requires the owned types".to_string(),
+            ));
+        }
+        if !self.owned && self.validation {
+            return Err(BuilderGenerateError::InvalidOption(
+                "validation",
+                "requires the owned types".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/**
+The `Builder` object has the following responsibilities:
+
+- validate the schema objects read directly from the schema
+- perform all pre-processing in advance required to write the source routines for parsing
+
+Code generation can be configured with the builder methods below, e.g.:
+
+```ignore
+Builder::new(schema_file)?
+    .allowlist_path(r"\Segment\Info")
+    .blocklist_path(r"\Segment\Info\ChapterTranslate")
+    .type_prefix("Mkv")
+    .derive("Hash")
+    .generate()?
+```
+
+**/
 #[derive(Debug)]
 pub struct Builder {
     schema: EbmlSchema,
+    options: Options,
 }
 
 impl Builder {
     pub fn new<R: std::io::Read>(schema: R) -> Result<Self, serde_xml_rs::Error> {
         Ok(Self {
             schema: from_reader(schema)?,
+            options: Options::default(),
+        })
+    }
+
+    /// Like `new`, but rejects schemas that deviate from the RFC 8794 schema file structure (e.g.,
+    /// with a misspelled attribute), listing each deviation's line & column.
+    pub fn new_strict<R: std::io::Read>(schema: R) -> Result<Self, StrictSchemaError> {
+        Ok(Self::from_schema(EbmlSchema::from_reader_strict(schema)?))
+    }
+
+    /// Uses an already-loaded (and possibly modified) schema.
+    pub fn from_schema(schema: EbmlSchema) -> Self {
+        Self {
+            schema,
+            options: Options::default(),
+        }
+    }
+
+    /// Reads the schema from the given file. The path is remembered, so that
+    /// `Parsers::write_module` can tell cargo to rerun the build script when the schema changes.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, serde_xml_rs::Error> {
+        let file = std::fs::File::open(path.as_ref())?;
+        let mut builder = Self::new(std::io::BufReader::new(file))?;
+        builder.options.schema_path = Some(path.as_ref().to_path_buf());
+        Ok(builder)
+    }
+
+    /// Only generates the elements within the given schema path (and their ancestors). Can be
+    /// called repeatedly; by default, all elements are generated.
+    pub fn allowlist_path<T: Into<String>>(mut self, path: T) -> Self {
+        self.options.allowlist.push(path.into());
+        self
+    }
+
+    /// Excludes the elements within the given schema path, even if they are allowlisted. Excluded
+    /// elements are skipped over by the generated parsers.
+    pub fn blocklist_path<T: Into<String>>(mut self, path: T) -> Self {
+        self.options.blocklist.push(path.into());
+        self
+    }
+
+    /// Sets the name of the generated schema-specific module (defaults to `core`).
+    pub fn module_name<T: Into<String>>(mut self, name: T) -> Self {
+        self.options.module_name = name.into();
+        self
+    }
+
+    /// Prepends a prefix to the names of all generated element types (e.g., `Mkv` -> `MkvEBMLDef`,
+    /// `MkvEBMLState`, ...).
+    pub fn type_prefix<T: Into<String>>(mut self, prefix: T) -> Self {
+        self.options.type_prefix = prefix.into();
+        self
+    }
+
+    /// Adds a derive to the generated element definition types (i.e., the `{Name}Def` structs) &
+    /// to the owned types (see `owned`).
+    pub fn derive<T: Into<String>>(mut self, derive: T) -> Self {
+        self.options.derives.push(derive.into());
+        self
+    }
+
+    /// Whether to generate the `BufRead`-based reader types in addition to the state types
+    /// (defaults to `true`).
+    pub fn readers(mut self, doit: bool) -> Self {
+        self.options.readers = doit;
+        self
+    }
+
+    /// Whether to generate owned types for the schema's master elements (defaults to `false`): a
+    /// struct per master element with a field per child element, readable from the `Events`
+    /// iterator's events (e.g., `owned::_Document::from_events`).
+    pub fn owned(mut self, doit: bool) -> Self {
+        self.options.owned = doit;
+        self
+    }
+
+    /// Whether to generate `write` methods for the owned types, serializing them into EBML
+    /// streams (defaults to `false`; requires `owned`).
+    pub fn writers(mut self, doit: bool) -> Self {
+        self.options.writers = doit;
+        self
+    }
+
+    /// Whether to generate `validate` methods for the owned types, checking element occurrences &
+    /// ranges against the schema (defaults to `false`; requires `owned`).
+    pub fn validation(mut self, doit: bool) -> Self {
+        self.options.validation = doit;
+        self
+    }
+
+    /**
+    Only generates the elements whose `<extension>`s satisfy the given predicate. Elements left
+    without any generated parent are excluded as well, and excluded elements are skipped over by
+    the generated parsers (or rejected, see `reject_excluded`). Can be called repeatedly; all
+    predicates must hold.
+
+    E.g., to generate only the WebM subset of Matroska:
+
+    ```ignore
+    builder.extension_filter(|extensions| {
+        extensions.iter().any(|extension| {
+            extension.r#type == "webmproject.org"
+                && extension.attributes.get("webm").map(String::as_str) == Some("1")
         })
+    })
+    ```
+    **/
+    pub fn extension_filter<F: Fn(&[Extension]) -> bool + 'static>(mut self, predicate: F) -> Self {
+        self.options
+            .extension_filters
+            .push(ExtensionFilter(Rc::new(predicate)));
+        self
+    }
+
+    /**
+    Adds the EBML header elements & the global CRC-32 & Void elements that RFC 8794 defines for
+    every document (sections 11.2 & 11.3) to the schema (defaults to `false`). Schemas such as
+    Matroska's only restate the header elements that they constrain; the schema's own definitions
+    take precedence.
+
+    **/
+    pub fn ebml_header(mut self, doit: bool) -> Self {
+        self.options.ebml_header = doit;
+        self
+    }
+
+    /// Makes the generated parsers fail with `StateError::ExcludedChildId` on the elements that
+    /// they'd otherwise skip over, i.e. excluded elements & recursive occurrences (defaults to
+    /// `false`).
+    pub fn reject_excluded(mut self, doit: bool) -> Self {
+        self.options.reject_excluded = doit;
+        self
+    }
+
+    /// Sets how violations of the schema rules that can be worked around are handled (defaults to
+    /// `SchemaStrictness::Strict`).
+    pub fn schema_strictness(mut self, strictness: SchemaStrictness) -> Self {
+        self.options.strictness = strictness;
+        self
+    }
+
+    /// Sets the Rust edition of the generated package (defaults to 2018).
+    pub fn rust_edition(mut self, edition: RustEdition) -> Self {
+        self.options.edition = edition;
+        self
     }
 
     pub fn generate(self) -> Result<Parsers, BuilderGenerateError> {
         // Validate inputs & configuration
-        // ...
+        self.options.validate()?;
+        let strictness = self.options.strictness;
+        let mut elements = self.schema.elements.unwrap_or_default();
+        if self.options.ebml_header {
+            let ids: BTreeSet<u32> = elements.iter().map(|elem| elem.id).collect();
+            elements.extend(
+                ebml_header_elements()
+                    .into_iter()
+                    .filter(|elem| !ids.contains(&elem.id)),
+            );
+        }
+        let (ignored, violations): (Vec<_>, Vec<_>) = validate_elements(&elements)
+            .into_iter()
+            .partition(|violation| {
+                strictness != SchemaStrictness::Strict && violation.is_ignorable()
+            });
+        if !violations.is_empty() {
+            return Err(BuilderGenerateError::InvalidSchema(violations));
+        }
+        if strictness == SchemaStrictness::Warn {
+            for violation in ignored.iter() {
+                println!("cargo:warning=ignoring schema violation: {}", violation);
+            }
+        }
+        for elem in elements.iter_mut() {
+            remove_disallowed_attributes(elem);
+        }
         // Return `Parsers` object
 
         //
-        let elems: BTreeMap<u32, Element> = self
-            .schema
-            .elements
-            .unwrap_or_else(Vec::new)
-            .into_iter()
-            .map(|elem| (elem.id, elem))
-            .collect();
+        let mut elems: BTreeMap<u32, Element> =
+            elements.into_iter().map(|elem| (elem.id, elem)).collect();
+
+        for elem in elems.values() {
+            if elem.r#type != ElementType::Date {
+                default_expr(elem)?;
+                continue;
+            }
+            if let Some(default) = elem.default.as_deref() {
+                parse_schema_date(default)
+                    .map_err(|e| BuilderGenerateError::InvalidDateDefault(elem.name.clone(), e))?;
+            }
+            if let Some(range) = elem.range.as_deref() {
+                range
+                    .parse::<DateRange>()
+                    .map_err(|e| BuilderGenerateError::InvalidDateRange(elem.name.clone(), e))?;
+            }
+        }
 
         let pathed_elems: Trie<(GlobalPlaceholder, String), &Element> = elems
             .values()
@@ -158,7 +778,7 @@ impl Builder {
             })
             .collect::<Result<_, _>>()?;
 
-        let elem_parents: BTreeMap<u32, BTreeSet<Option<u32>>> = pathed_elems
+        let mut elem_parents: BTreeMap<u32, BTreeSet<Option<u32>>> = pathed_elems
             .iter()
             .map(|(path_atoms, elem)| {
                 //let expt_first_atom = &[&(Default::default(), "".to_string())];
@@ -168,7 +788,8 @@ impl Builder {
                 let ((global_span, name), parent_path_atoms) = path_atoms
                     .split_last()
                     .ok_or_else(|| BuilderGenerateError::EmptyPath(elem.name.clone()))?;
-                if name != &elem.name {
+                // a leading '+' marks the element as recursive
+                if name.strip_prefix('+').unwrap_or(name) != elem.name {
                     return Err(BuilderGenerateError::MismatchedPathName(
                         elem.name.clone(),
                         name.to_string(),
@@ -216,12 +837,95 @@ impl Builder {
                 .or_insert_with(BTreeSet::new);
         }
 
+        // Apply the allow/block-lists
+        let options = &self.options; // edition 2018 closures capture all of `self`
+        let mut kept: BTreeSet<u32> = elems
+            .values()
+            .filter(|elem| options.keeps(&elem.path) && options.keeps_extensions(elem))
+            .map(|elem| elem.id)
+            .collect();
+        loop {
+            // global elements may lose all of their parents -> drop them too
+            let orphans: Vec<u32> = kept
+                .iter()
+                .copied()
+                .filter(|id| {
+                    elem_parents[id]
+                        .iter()
+                        .all(|pid| pid.is_some_and(|pid| !kept.contains(&pid)))
+                })
+                .collect();
+            if orphans.is_empty() {
+                break;
+            }
+            for id in orphans {
+                kept.remove(&id);
+            }
+        }
+
+        let is_kept = |id: &Option<u32>| id.map_or(true, |id| kept.contains(&id));
+        let mut skipped: BTreeMap<Option<u32>, BTreeSet<u32>> = elem_children
+            .iter()
+            .filter(|(pid, _cids)| is_kept(pid))
+            .map(|(pid, cids)| (*pid, cids.difference(&kept).copied().collect()))
+            .collect();
+        let rejected = if options.reject_excluded {
+            let rejected = skipped.clone();
+            skipped.values_mut().for_each(BTreeSet::clear);
+            rejected
+        } else {
+            skipped.keys().map(|pid| (*pid, BTreeSet::new())).collect()
+        };
+        elems.retain(|id, _elem| kept.contains(id));
+        elem_parents.retain(|id, _pids| kept.contains(id));
+        for pids in elem_parents.values_mut() {
+            pids.retain(is_kept);
+        }
+        elem_children.retain(|pid, _cids| is_kept(pid));
+        for cids in elem_children.values_mut() {
+            cids.retain(|cid| kept.contains(cid));
+        }
+        // recursive elements nest within themselves (& only then, so that they're still dropped
+        // along with their other parents)
+        for elem in elems.values().filter(|elem| is_recursive(elem)) {
+            elem_parents
+                .entry(elem.id)
+                .or_default()
+                .insert(Some(elem.id));
+            elem_children
+                .entry(Some(elem.id))
+                .or_default()
+                .insert(elem.id);
+        }
+
+        // Element names must map to distinct Rust type names
+        let mut names_by_type_name: BTreeMap<String, &str> = BTreeMap::new();
+        for elem in elems.values() {
+            let type_name = rust_type_name(&options.type_prefix, &elem.name);
+            if let Some(other_name) = names_by_type_name.insert(type_name.clone(), &elem.name) {
+                return Err(BuilderGenerateError::TypeNameCollision(
+                    other_name.to_string(),
+                    elem.name.clone(),
+                    type_name,
+                ));
+            }
+        }
+
         Ok(Parsers {
             elements: elems,
             parents: elem_parents,
             children: elem_children,
+            skipped,
+            rejected,
+            options: self.options,
         })
     }
+
+    pub fn build_dynamic(self) -> Result<DynParser, BuilderGenerateError> {
+        let parsers = self.generate()?;
+
+        Ok(DynParser::new(parsers.elements, parsers.children))
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -236,6 +940,172 @@ pub enum BuilderGenerateError {
     NoDirectParent(String),
     #[error("expected a null prefix in path {0}")]
     NonNullPathPrefix(String),
+    #[error("invalid default value for date element {0}: {1}")]
+    InvalidDateDefault(String, DateError),
+    #[error("invalid default value for element {0}: {1:?}")]
+    InvalidDefault(String, String),
+    #[error("invalid range for date element {0}: {1}")]
+    InvalidDateRange(String, DateRangeParserError),
+    #[error("invalid value for builder option {0}: {1:?}")]
+    InvalidOption(&'static str, String),
+    #[error("elements {0} and {1} both map to the Rust type name {2}")]
+    TypeNameCollision(String, String, String),
+    #[error("invalid schema: {}", list_violations(.0))]
+    InvalidSchema(Vec<SchemaViolation>),
+}
+
+fn list_violations(violations: &[SchemaViolation]) -> String {
+    itertools::intersperse(violations.iter().map(ToString::to_string), "; ".to_string()).collect()
+}
+
+/// A breach of the EBML schema rules in RFC 8794, as found by `Builder::generate`.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum SchemaViolation {
+    #[error("invalid element name {0:?}")]
+    InvalidName(String),
+    #[error("invalid ID {1:#X} for element {0}")]
+    InvalidId(String, u32),
+    #[error("ID {1:#X} for element {0} has VINT_DATA of all 0's")]
+    ZeroDataId(String, u32),
+    #[error("duplicate ID {0:#X} for elements {1} and {2}")]
+    DuplicateId(u32, String, String),
+    #[error("duplicate path {0}")]
+    DuplicatePath(String),
+    #[error("attribute {1} not allowed for {2} element {0}")]
+    AttributeNotAllowed(String, &'static str, &'static str),
+    #[error("minOccurs {1} exceeds maxOccurs {2} for element {0}")]
+    MinOccursExceedsMaxOccurs(String, usize, usize),
+    #[error("minOccurs {1} exceeds 1 for element {0} with a default value")]
+    DefaultWithMinOccurs(String, usize),
+}
+
+impl SchemaViolation {
+    // Whether code can still be generated by ignoring the violation (see `SchemaStrictness`)
+    fn is_ignorable(&self) -> bool {
+        matches!(
+            self,
+            Self::AttributeNotAllowed(..) | Self::DefaultWithMinOccurs(..) | Self::ZeroDataId(..)
+        )
+    }
+}
+
+// The attributes of an element, & whether its type disallows them
+fn disallowed_attributes(elem: &Element) -> [(&'static str, bool); 4] {
+    let is_master = elem.r#type == ElementType::Master;
+    let is_numeric = matches!(
+        elem.r#type,
+        ElementType::SignedInteger
+            | ElementType::UnsignedInteger
+            | ElementType::Float
+            | ElementType::Date
+    );
+    [
+        ("range", elem.range.is_some() && !is_numeric),
+        ("default", elem.default.is_some() && is_master),
+        (
+            "unknownsizeallowed",
+            elem.unknownsizeallowed == Some(true) && !is_master,
+        ),
+        ("recursive", elem.recursive == Some(true) && !is_master),
+    ]
+}
+
+// Drops the attributes that the element's type disallows, as if they weren't in the schema
+fn remove_disallowed_attributes(elem: &mut Element) {
+    for (attribute, _) in disallowed_attributes(elem).iter().filter(|(_, bad)| *bad) {
+        match *attribute {
+            "range" => elem.range = None,
+            "default" => elem.default = None,
+            "unknownsizeallowed" => elem.unknownsizeallowed = None,
+            _ => elem.recursive = None,
+        }
+    }
+}
+
+// The elements of `ebml_header.xml` (see `Builder::ebml_header`)
+fn ebml_header_elements() -> Vec<Element> {
+    let schema: EbmlSchema = from_reader(include_str!("ebml_header.xml").as_bytes())
+        .expect("the RFC 8794 elements are a valid schema");
+    schema.elements.unwrap_or_default()
+}
+
+// An element name must be `[A-Za-z0-9][A-Za-z0-9.-]*`
+fn is_valid_element_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+}
+
+// An element ID must be a VINT of the shortest possible width, whose data isn't all 0's or 1's
+fn is_valid_element_id(id: u32) -> bool {
+    if id == 0 {
+        return false;
+    }
+    let width = 4 - id.leading_zeros() / 8;
+    let first_byte = id >> (8 * (width - 1));
+    if first_byte.leading_zeros() != 32 - 8 + width - 1 {
+        return false;
+    }
+    let data = id ^ (1 << (7 * width));
+    let data_max = (1 << (7 * width)) - 1;
+    data != 0 && data != data_max && (width == 1 || data >= (1 << (7 * (width - 1))) - 1)
+}
+
+fn validate_elements(elements: &[Element]) -> Vec<SchemaViolation> {
+    let mut violations = Vec::new();
+    let mut names_by_id: BTreeMap<u32, &str> = BTreeMap::new();
+    let mut paths = BTreeSet::new();
+
+    for elem in elements {
+        if !is_valid_element_name(&elem.name) {
+            violations.push(SchemaViolation::InvalidName(elem.name.clone()));
+        }
+        // the only such ID of the shortest width, still used by Matroska's ChapterDisplay
+        if elem.id == 0x80 {
+            violations.push(SchemaViolation::ZeroDataId(elem.name.clone(), elem.id));
+        } else if !is_valid_element_id(elem.id) {
+            violations.push(SchemaViolation::InvalidId(elem.name.clone(), elem.id));
+        }
+        if let Some(other_name) = names_by_id.insert(elem.id, &elem.name) {
+            violations.push(SchemaViolation::DuplicateId(
+                elem.id,
+                other_name.to_string(),
+                elem.name.clone(),
+            ));
+        }
+        if !paths.insert(&elem.path) {
+            violations.push(SchemaViolation::DuplicatePath(elem.path.clone()));
+        }
+
+        for (attribute, _) in disallowed_attributes(elem).iter().filter(|(_, bad)| *bad) {
+            violations.push(SchemaViolation::AttributeNotAllowed(
+                elem.name.clone(),
+                attribute,
+                elem.r#type.as_str(),
+            ));
+        }
+
+        if let (Some(min), Some(max)) = (elem.min_occurs, elem.max_occurs) {
+            if min > max {
+                violations.push(SchemaViolation::MinOccursExceedsMaxOccurs(
+                    elem.name.clone(),
+                    min,
+                    max,
+                ));
+            }
+        }
+        if let (Some(_), Some(min)) = (&elem.default, elem.min_occurs) {
+            if min > 1 {
+                violations.push(SchemaViolation::DefaultWithMinOccurs(
+                    elem.name.clone(),
+                    min,
+                ));
+            }
+        }
+    }
+
+    violations
 }
 
 /**
@@ -244,50 +1114,212 @@ Everything else (reading the schema, validating the element definitions & hierar
 should be done elsewhere.
 
 **/
-
 pub struct Parsers {
     // u32's are the element ID's
     // ID = `None` -> root document
     elements: BTreeMap<u32, Element>, // the root doesn't have a schema config
     parents: BTreeMap<u32, BTreeSet<Option<u32>>>, // the root can BE a parent, but will not HAVE a parent
     children: BTreeMap<Option<u32>, BTreeSet<u32>>, // the root can HAVE children, but will not BE a child
+    skipped: BTreeMap<Option<u32>, BTreeSet<u32>>,  // children excluded by the allow/block-lists
+    rejected: BTreeMap<Option<u32>, BTreeSet<u32>>, // excluded children, with `reject_excluded`
+    options: Options,
 }
 
-impl Parsers {
-    pub fn write_element_defs<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
-        writer.write_all(
-            r#"
-#[allow(unused_imports)]
-use crate::base::element_defs::{
-    BinaryElementDef, DateElementDef, ElementDef, FloatElementDef, IntElementDef, MasterElementDef,
-    Range, StringElementDef, UIntElementDef, Utf8ElementDef,
+// The template's parser macros are exported at the crate root of a generated package
+const MACRO_IMPORTS: &str = r#"#[allow(unused_imports)]
+use crate::{
+    impl_bytes_left, impl_from_readers_for_states, impl_from_subreaders_for_readers,
+    impl_from_substates_for_states, impl_into_reader, impl_next_state_navigation,
+    impl_skip_state_navigation,
 };
+"#;
 
-use core::ops::Bound;
-            "#
-            .as_bytes(),
-        )?;
+// Doc comments for the implementation notes on the given schema attribute of an element
+fn implementation_notes(element: &Element, attribute: &str) -> String {
+    element
+        .metadata
+        .iter()
+        .flatten()
+        .filter_map(|value| match value {
+            ElementValue::ImplementationNote(note) if note.note_attribute == attribute => {
+                Some(note.value.as_str())
+            }
+            _ => None,
+        })
+        .flat_map(str::lines)
+        .map(|line| format!("/// {}\n    ", line.trim()))
+        .collect()
+}
 
-        for element in self.elements.values() {
-            write!(
-                writer,
-                r#"
-#[derive(Debug, Clone, PartialEq)]
-pub struct {name}Def;
+// The `ElementDef::EXTENSIONS` override for an element, if it has any extensions
+fn extensions_const(element: &Element) -> String {
+    let extensions = element
+        .metadata
+        .iter()
+        .flatten()
+        .filter_map(|value| match value {
+            ElementValue::Extension(extension) => Some(format!(
+                "({:?}, &[{}])",
+                extension.r#type,
+                extension
+                    .attributes
+                    .iter()
+                    .map(|(name, value)| format!("({:?}, {:?})", name, value))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if extensions.is_empty() {
+        return String::new();
+    }
+    format!(
+        "\n\n    const EXTENSIONS: &'static [(&'static str, &'static [(&'static str, &'static str)])] = &[{}];",
+        extensions.join(", ")
+    )
+}
+
+// Parses a float in a schema `default` attribute: either a decimal, or a C99 hexadecimal
+// floating-point literal (e.g., "0x1.f4p+12"), as used throughout the Matroska schema
+fn parse_schema_float(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let (sign, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let hex = match unsigned
+        .strip_prefix("0x")
+        .or_else(|| unsigned.strip_prefix("0X"))
+    {
+        Some(hex) => hex,
+        None => return s.parse().ok().filter(|x: &f64| x.is_finite()),
+    };
+
+    let (mantissa, exponent) = hex.split_once(['p', 'P']).unwrap_or((hex, "0"));
+    let (int_digits, frac_digits) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if int_digits.is_empty() && frac_digits.is_empty() {
+        return None;
+    }
+    let mut value = 0.0_f64;
+    for digit in int_digits.chars().chain(frac_digits.chars()) {
+        value = value * 16.0 + f64::from(digit.to_digit(16)?);
+    }
+    let exponent = exponent.parse::<i32>().ok()? - 4 * frac_digits.len() as i32;
+
+    Some(sign * value * 2.0_f64.powi(exponent)).filter(|x| x.is_finite())
+}
+
+// Renders the `default` attribute of a non-date element as a Rust expression
+fn default_expr(elem: &Element) -> Result<Option<String>, BuilderGenerateError> {
+    let default = match elem.default.as_deref() {
+        Some(default) => default,
+        None => return Ok(None),
+    };
+    let invalid = || BuilderGenerateError::InvalidDefault(elem.name.clone(), default.to_string());
+
+    let expr = match elem.r#type {
+        ElementType::UnsignedInteger => default
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| invalid())?
+            .to_string(),
+        ElementType::SignedInteger => default
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| invalid())?
+            .to_string(),
+        ElementType::Float => format!("{:?}", parse_schema_float(default).ok_or_else(invalid)?),
+        ElementType::String if !default.is_ascii() => return Err(invalid()),
+        ElementType::String | ElementType::Utf8 => format!("{:?}", default),
+        // master elements can't have defaults, and binary defaults have no defined encoding
+        _ => return Ok(None),
+    };
+    Ok(Some(expr))
+}
+
+fn date_expr(date: Date) -> String {
+    format!("Date::from_nanos({})", date.nanos())
+}
+
+fn date_range_expr(range: DateRange) -> String {
+    let bound_expr = |bound: Bound<Date>| match bound {
+        Bound::Included(date) => format!("Bound::Included({})", date_expr(date)),
+        Bound::Excluded(date) => format!("Bound::Excluded({})", date_expr(date)),
+        Bound::Unbounded => "Bound::Unbounded".to_string(),
+    };
+
+    match range {
+        DateRange::IsExactly(date) => format!("Range::IsExactly({})", date_expr(date)),
+        DateRange::Excludes(date) => format!("Range::Excludes({})", date_expr(date)),
+        DateRange::IsWithin(lower, upper) => {
+            format!(
+                "Range::IsWithin({}, {})",
+                bound_expr(lower),
+                bound_expr(upper)
+            )
+        }
+    }
+}
+
+impl Parsers {
+    fn type_name(&self, elem_name: &str) -> String {
+        rust_type_name(&self.options.type_prefix, elem_name)
+    }
+
+    fn derives(&self) -> String {
+        let mut derives = vec!["Debug", "Clone", "PartialEq"];
+        for derive in self.options.derives.iter() {
+            if !derives.contains(&derive.as_str()) {
+                derives.push(derive);
+            }
+        }
+        derives.join(", ")
+    }
+
+    pub fn write_element_defs<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(
+            r#"
+#[allow(unused_imports)]
+use crate::base::element_defs::{
+    BinaryElementDef, Date, DateElementDef, ElementDef, FloatElementDef, IntElementDef,
+    MasterElementDef, Range, StringElementDef, UIntElementDef, Utf8ElementDef,
+};
+
+use core::ops::Bound;
+            "#
+            .as_bytes(),
+        )?;
+
+        for element in self.elements.values() {
+            let name = self.type_name(&element.name);
+            let notes = |attribute: &str| implementation_notes(element, attribute);
+            write!(
+                writer,
+                r#"
+#[derive({derives})]
+pub struct {name}Def;
 
 impl ElementDef for {name}Def {{
     const ID: u32 = {id};
     const PATH: &'static str = r"{path}";
 
-    const MIN_OCCURS: usize = {min_occurs};
-    const MAX_OCCURS: Option<usize> = {max_occurs};
-    const LENGTH: Range<usize> = Range::IsWithin(Bound::Unbounded, Bound::Unbounded);
+    {min_occurs_notes}const MIN_OCCURS: usize = {min_occurs};
+    {max_occurs_notes}const MAX_OCCURS: Option<usize> = {max_occurs};
+    {length_notes}const LENGTH: Range<usize> = Range::IsWithin(Bound::Unbounded, Bound::Unbounded);
     const RECURRING: bool = {recurring};
-    const MIN_VERSION: u64 = {minver};
-    const MAX_VERSION: Option<u64> = {maxver};
+    {minver_notes}const MIN_VERSION: u64 = {minver};
+    {maxver_notes}const MAX_VERSION: Option<u64> = {maxver};{extensions}
 }}
                 "#,
-                name = element.name,
+                name = name,
+                min_occurs_notes = notes("minOccurs"),
+                max_occurs_notes = notes("maxOccurs"),
+                length_notes = notes("length"),
+                minver_notes = notes("minver"),
+                maxver_notes = notes("maxver"),
+                extensions = extensions_const(element),
+                derives = self.derives(),
                 id = element.id,
                 path = element.path,
                 min_occurs = element.min_occurs.unwrap_or(0),
@@ -312,7 +1344,7 @@ impl MasterElementDef for {name}Def {{
     const RECURSIVE: bool = {recursive};
 }}
                     "#,
-                    name = element.name,
+                    name = name,
                     unknown_size_allowed = element.unknownsizeallowed.unwrap_or(false),
                     recursive = element.recursive.unwrap_or(false),
                 ),
@@ -320,75 +1352,112 @@ impl MasterElementDef for {name}Def {{
                     writer,
                     r#"
 impl IntElementDef for {name}Def {{
-    const RANGE: Range<i64> = Range::IsWithin(Bound::Unbounded, Bound::Unbounded);
-    const DEFAULT: Option<i64> = {default};
+    {range_notes}const RANGE: Range<i64> = Range::IsWithin(Bound::Unbounded, Bound::Unbounded);
+    {default_notes}const DEFAULT: Option<i64> = {default};
 }}
                     "#,
-                    name = element.name,
-                    default = "None"
+                    name = name,
+                    range_notes = notes("range"),
+                    default_notes = notes("default"),
+                    default = default_expr(element)
+                        .expect("validated in `Builder::generate`")
+                        .map_or_else(|| "None".to_string(), |expr| format!("Some({expr})")),
                 ),
                 ElementType::UnsignedInteger => write!(
                     writer,
                     r#"
 impl UIntElementDef for {name}Def {{
-    const RANGE: Range<u64> = Range::IsWithin(Bound::Unbounded, Bound::Unbounded);
-    const DEFAULT: Option<u64> = {default};
+    {range_notes}const RANGE: Range<u64> = Range::IsWithin(Bound::Unbounded, Bound::Unbounded);
+    {default_notes}const DEFAULT: Option<u64> = {default};
 }}
                     "#,
-                    name = element.name,
-                    default = "None"
+                    name = name,
+                    range_notes = notes("range"),
+                    default_notes = notes("default"),
+                    default = default_expr(element)
+                        .expect("validated in `Builder::generate`")
+                        .map_or_else(|| "None".to_string(), |expr| format!("Some({expr})")),
                 ),
                 ElementType::Float => write!(
                     writer,
                     r#"
 impl FloatElementDef for {name}Def {{
-    const RANGE: Range<f64> = Range::IsWithin(Bound::Unbounded, Bound::Unbounded);
-    const DEFAULT: Option<f64> = {default};
+    {range_notes}const RANGE: Range<f64> = Range::IsWithin(Bound::Unbounded, Bound::Unbounded);
+    {default_notes}const DEFAULT: Option<f64> = {default};
 }}
                     "#,
-                    name = element.name,
-                    default = "None"
+                    name = name,
+                    range_notes = notes("range"),
+                    default_notes = notes("default"),
+                    default = default_expr(element)
+                        .expect("validated in `Builder::generate`")
+                        .map_or_else(|| "None".to_string(), |expr| format!("Some({expr})")),
                 ),
                 ElementType::Date => write!(
                     writer,
                     r#"
 impl DateElementDef for {name}Def {{
-    const RANGE: Range<i64> = Range::IsWithin(Bound::Unbounded, Bound::Unbounded);
-    const DEFAULT: Option<i64> = {default};
+    {range_notes}const RANGE: Range<Date> = {range};
+    {default_notes}const DEFAULT: Option<Date> = {default};
 }}
                     "#,
-                    name = element.name,
-                    default = "None"
+                    name = name,
+                    range_notes = notes("range"),
+                    default_notes = notes("default"),
+                    range = element
+                        .range
+                        .as_deref()
+                        .map(|range| range.parse().expect("validated in `Builder::generate`"))
+                        .map_or_else(
+                            || "Range::IsWithin(Bound::Unbounded, Bound::Unbounded)".to_string(),
+                            date_range_expr
+                        ),
+                    default = element
+                        .default
+                        .as_deref()
+                        .map(|default| {
+                            parse_schema_date(default).expect("validated in `Builder::generate`")
+                        })
+                        .map_or_else(
+                            || "None".to_string(),
+                            |date| format!("Some({})", date_expr(date))
+                        ),
                 ),
                 ElementType::String => write!(
                     writer,
                     r#"
 impl StringElementDef for {name}Def {{
-    const DEFAULT: Option<&'static str> = {default};
+    {default_notes}const DEFAULT: Option<&'static str> = {default};
 }}
                     "#,
-                    name = element.name,
-                    default = "None"
+                    name = name,
+                    default_notes = notes("default"),
+                    default = default_expr(element)
+                        .expect("validated in `Builder::generate`")
+                        .map_or_else(|| "None".to_string(), |expr| format!("Some({expr})")),
                 ),
                 ElementType::Utf8 => write!(
                     writer,
                     r#"
 impl Utf8ElementDef for {name}Def {{
-    const DEFAULT: Option<&'static str> = {default};
+    {default_notes}const DEFAULT: Option<&'static str> = {default};
 }}
                     "#,
-                    name = element.name,
-                    default = "None"
+                    name = name,
+                    default_notes = notes("default"),
+                    default = default_expr(element)
+                        .expect("validated in `Builder::generate`")
+                        .map_or_else(|| "None".to_string(), |expr| format!("Some({expr})")),
                 ),
                 ElementType::Binary => write!(
                     writer,
                     r#"
 impl BinaryElementDef for {name}Def {{
-    const DEFAULT: Option<&'static [u8]> = {default};
+    {default_notes}const DEFAULT: Option<&'static [u8]> = None;
 }}
                     "#,
-                    name = element.name,
-                    default = "None"
+                    name = name,
+                    default_notes = notes("default"),
                 ),
             }?;
         }
@@ -397,10 +1466,15 @@ impl BinaryElementDef for {name}Def {{
     }
 
     pub fn write_parsers<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let readers = self.options.readers;
+        let elem_type_name = |id: &u32| self.type_name(&self.elements.get(id).unwrap().name);
+        let parent_type_name =
+            |id: &Option<u32>| id.as_ref().map_or("_Document".to_string(), elem_type_name);
+
         let element_names = self
             .elements
-            .values()
-            .map(|elem| elem.name.clone())
+            .keys()
+            .map(elem_type_name)
             .chain(core::iter::once("_Document".to_string()))
             .collect::<Vec<_>>();
         let parent_names = self
@@ -408,14 +1482,10 @@ impl BinaryElementDef for {name}Def {{
             .iter()
             .map(|(id, parent_ids)| {
                 (
-                    self.elements.get(id).unwrap().name.clone(),
+                    elem_type_name(id),
                     parent_ids
                         .iter()
-                        .map(|parent_id| {
-                            parent_id.map_or("_Document".to_string(), |pid| {
-                                self.elements.get(&pid).unwrap().name.clone()
-                            })
-                        })
+                        .map(parent_type_name)
                         .collect::<BTreeSet<_>>(),
                 )
             })
@@ -426,84 +1496,123 @@ impl BinaryElementDef for {name}Def {{
             .iter()
             .map(|(id, child_ids)| {
                 (
-                    id.map_or("_Document".to_string(), |pid| {
-                        self.elements.get(&pid).unwrap().name.clone()
-                    }),
+                    parent_type_name(id),
                     child_ids
                         .iter()
-                        .map(|child_id| self.elements.get(child_id).unwrap().name.clone())
+                        .map(elem_type_name)
                         .collect::<BTreeSet<_>>(),
                 )
             })
             .collect::<BTreeMap<_, _>>();
+        let id_lists = |ids_by_parent: &BTreeMap<Option<u32>, BTreeSet<u32>>| {
+            ids_by_parent
+                .iter()
+                .map(|(id, ids)| {
+                    (
+                        parent_type_name(id),
+                        itertools::intersperse(
+                            ids.iter().map(|id| format!("{:#X}", id)),
+                            ", ".to_string(),
+                        )
+                        .collect::<String>(),
+                    )
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        let skipped_ids = id_lists(&self.skipped);
+        let rejected_ids = id_lists(&self.rejected);
+        // only pass the rejected IDs when there are any, to keep the common case terse
+        let excluded_ids = |name: &str| match rejected_ids.get(name).unwrap().as_str() {
+            "" => format!("[{}]", skipped_ids.get(name).unwrap()),
+            rejected => format!("[{}], [{}]", skipped_ids.get(name).unwrap(), rejected),
+        };
 
-        writer.write_all(
+        write!(
+            writer,
             r#"
 use crate::base::element_defs::ElementDef;
 #[allow(unused_imports)]
-use crate::base::parser::{
-    BoundTo, ElementReader, ElementState, IntoReader, NextStateNavigation, ReaderError,
-    SkipStateNavigation, StateDataParser, StateError,
-};
-#[allow(unused_imports)]
-use crate::base::stream::{parse, serialize, stream_diff};
-use crate::core::element_defs;
+use crate::base::parser::{{
+    BoundTo, BytesLeft, ElementReader, ElementState, IntoReader, NextStateNavigation, ReaderError,
+    SkipStateNavigation, StateDataParser, StateError, UnknownSizeSkip, Unread,
+}};
 #[allow(unused_imports)]
-use crate::{
-    impl_from_readers_for_states, impl_from_subreaders_for_readers, impl_from_substates_for_states,
-    impl_into_reader, impl_next_state_navigation, impl_skip_state_navigation,
-};
-
+use crate::base::stream::{{parse, serialize, stream_diff}};
+use crate::{module}::element_defs;
+{macro_imports}
 use enum_dispatch::enum_dispatch;
 
-use core::convert::{From, TryInto};
+use core::convert::{{From, TryInto}};
 use core::marker::PhantomData;
-use std::io::BufRead;
-
+{bufread}
 // Top-Level Reader/State Enums #########################################################################
-            "#.as_bytes()
+            "#,
+            module = self.options.module_name,
+            macro_imports = MACRO_IMPORTS,
+            bufread = if readers {
+                "use std::io::BufRead;\n"
+            } else {
+                ""
+            },
         )?;
 
         for element_name in child_names
             .iter()
-            .filter_map(|(name, c_names)| (!c_names.is_empty()).then(|| name))
+            .filter(|(_name, c_names)| !c_names.is_empty())
+            .map(|(name, _c_names)| name)
         {
             write!(
                 writer,
                 r#"
 #[enum_dispatch({name}NextStates)]
-#[enum_dispatch({name}NextReaders<R>)]
-                "#,
+{readers}                "#,
                 name = element_name,
+                readers = if readers {
+                    format!("#[enum_dispatch({}NextReaders<R>)]\n", element_name)
+                } else {
+                    String::new()
+                },
             )?;
         }
         for element_name in parent_names
             .iter()
-            .filter_map(|(name, p_names)| (p_names.len() > 1).then(|| name))
+            .filter(|(_name, p_names)| p_names.len() > 1)
+            .map(|(name, _p_names)| name)
         {
             write!(
                 writer,
                 r#"
 #[enum_dispatch({name}PrevStates)]
-#[enum_dispatch({name}PrevReaders<R>)]
-                "#,
+{readers}                "#,
                 name = element_name,
+                readers = if readers {
+                    format!("#[enum_dispatch({}PrevReaders<R>)]\n", element_name)
+                } else {
+                    String::new()
+                },
             )?;
         }
 
-        writer.write_all(
+        write!(
+            writer,
             r#"
 #[enum_dispatch(States)]
-#[enum_dispatch(Readers<R>)]
-trait BlankTrait {}
-            "#
-            .as_bytes(),
+{readers}trait _BlankTrait {{}}
+            "#,
+            readers = if readers {
+                "#[enum_dispatch(Readers<R>)]\n"
+            } else {
+                ""
+            },
         )?;
 
         write!(
             writer,
             r#"
+// the variants are named after the schema's elements (e.g., `StatesElement`)
+#[allow(clippy::enum_variant_names)]
 #[enum_dispatch]
+#[derive(Debug, Clone, PartialEq)]
 pub enum States {{
     {elements}
 }}
@@ -513,38 +1622,37 @@ pub enum States {{
                 .map(|elem_name| format!("{0}({0}State), ", elem_name))
                 .collect::<String>()
         )?;
-        write!(
-            writer,
-            r#"
+        if readers {
+            write!(
+                writer,
+                r#"
+#[allow(clippy::enum_variant_names)]
 #[enum_dispatch]
 pub enum Readers<R> {{
     {elements}
 }}
-            "#,
-            elements = element_names
-                .iter()
-                .map(|elem_name| format!("{0}({0}Reader<R>),", elem_name))
-                .collect::<String>()
-        )?;
 
-        write!(
-            writer,
-            r#"
 impl_into_reader!(
     States,
     Readers,
-    [{elements}]
+    [{element_list}]
 );
 
 impl_from_readers_for_states!(
     Readers,
     States,
-    [{elements}]
+    [{element_list}]
 );
-            "#,
-            elements = itertools::intersperse(element_names.iter().map(String::as_str), ", ")
-                .collect::<String>()
-        )?;
+                "#,
+                elements = element_names
+                    .iter()
+                    .map(|elem_name| format!("{0}({0}Reader<R>),", elem_name))
+                    .collect::<String>(),
+                element_list =
+                    itertools::intersperse(element_names.iter().map(String::as_str), ", ")
+                        .collect::<String>(),
+            )?;
+        }
 
         write!(
             writer,
@@ -553,91 +1661,118 @@ impl_from_readers_for_states!(
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct _DocumentState;
+
+// the document isn't bounded by any size
+impl BytesLeft for _DocumentState {{
+    fn set_bytes_left(&mut self, _bytes_left: usize) {{}}
+}}
+
+// The IDs of the children of an unknown-sized element, which ends at the first element that isn't
+// one of them
+#[allow(dead_code)]
+fn unknown_size_children(id: u32) -> Result<&'static [u32], StateError> {{
+    match id {{{unknown_size_arms}
+        id => Err(StateError::UnknownSizeSkipped(id)),
+    }}
+}}
+            "#,
+            unknown_size_arms = self.unknown_size_arms(),
+        )?;
+        if readers {
+            writer.write_all(
+                r#"
 pub type _DocumentReader<R> = ElementReader<R, _DocumentState>;
 
-impl<R: BufRead> _DocumentReader<R> {{
-    pub fn new(reader: R) -> Self {{
-        Self {{
+impl<R: BufRead> _DocumentReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
             reader,
             state: _DocumentState,
-        }}
-    }}
-}}
+            unread: None,
+        }
+    }
+}
 
-impl<R: BufRead> IntoReader<R> for _DocumentState {{
+impl<R: BufRead> IntoReader<R> for _DocumentState {
     type Reader = _DocumentReader<R>;
-    fn into_reader(self, reader: R) -> _DocumentReader<R> {{
+    fn into_reader(self, reader: R) -> _DocumentReader<R> {
         _DocumentReader::new(reader)
-    }}
-}}
+    }
+}
+
+impl<R> From<_DocumentReader<R>> for _DocumentState {
+    fn from(reader: _DocumentReader<R>) -> Self {
+        reader.state
+    }
+}
+                "#
+                .as_bytes(),
+            )?;
+        }
 
+        let doc_child_names = child_names.get("_Document").unwrap();
+        write!(
+            writer,
+            r#"
 impl_next_state_navigation!(
     _DocumentState,
     _DocumentNextStates,
-    [{child_pairs}]
+    [{child_pairs}],
+    {excluded_ids}
 );
+
+#[derive(Debug, Clone, PartialEq)]
+#[enum_dispatch]
+pub enum _DocumentNextStates {{
+    {child_states}
+}}
+
+impl_from_substates_for_states!(_DocumentNextStates, States, [{children}]);
             "#,
             child_pairs = itertools::intersperse(
-                child_names
-                    .get("_Document")
-                    .unwrap()
+                doc_child_names
                     .iter()
                     .map(|cname| format!("({cname}, {cname}State)")),
                 ", ".to_string()
             )
             .collect::<String>(),
+            excluded_ids = excluded_ids("_Document"),
+            child_states = doc_child_names
+                .iter()
+                .map(|name| format!("{name}({name}State), "))
+                .collect::<String>(),
+            children = itertools::intersperse(doc_child_names.iter().map(String::as_str), ", ")
+                .collect::<String>(),
         )?;
-
-        write!(
-            writer,
-            r#"
-#[derive(Debug, Clone, PartialEq)]
-#[enum_dispatch]
-pub enum _DocumentNextStates {{
-    {child_states}
-}}
-
+        if readers {
+            write!(
+                writer,
+                r#"
 #[derive(Debug, PartialEq)]
 #[enum_dispatch]
 pub enum _DocumentNextReaders<R> {{
     {child_readers}
 }}
 
-impl_from_substates_for_states!(_DocumentNextStates, States, [{children}]);
 impl_from_subreaders_for_readers!(_DocumentNextReaders, Readers, [{children}]);
 
 impl_into_reader!(_DocumentNextStates, _DocumentNextReaders, [{children}]);
 impl_from_readers_for_states!(_DocumentNextReaders, _DocumentNextStates, [{children}]);
-            "#,
-            child_states = child_names
-                .get("_Document")
-                .unwrap()
-                .iter()
-                .map(|name| format!("{name}({name}State), "))
-                .collect::<String>(),
-            child_readers = child_names
-                .get("_Document")
-                .unwrap()
-                .iter()
-                .map(|name| format!("{name}({name}Reader<R>), "))
-                .collect::<String>(),
-            children = itertools::intersperse(
-                child_names
-                    .get("_Document")
-                    .unwrap()
+                "#,
+                child_readers = doc_child_names
                     .iter()
-                    .map(String::as_str),
-                ", "
-            )
-            .collect::<String>(),
-        )?;
+                    .map(|name| format!("{name}({name}Reader<R>), "))
+                    .collect::<String>(),
+                children = itertools::intersperse(doc_child_names.iter().map(String::as_str), ", ")
+                    .collect::<String>(),
+            )?;
+        }
 
         let make_state = |name: &str| format!("{}State", name);
         let make_reader = |name: &str| format!("{}Reader", name);
         let make_prev_states = |name: &str| format!("{}PrevStates", name);
         let make_prev_readers = |name: &str| format!("{}PrevReaders", name);
         let make_next_states = |name: &str| format!("{}NextStates", name);
-        let make_next_readers = |name: &str| format!("{}NextReaders", name);
 
         for element_name in element_names {
             if element_name == "_Document" {
@@ -674,26 +1809,20 @@ impl_from_readers_for_states!(_DocumentNextReaders, _DocumentNextStates, [{child
 // {name} Objects #########################################################################
 
 pub type {name}State = ElementState<element_defs::{name}Def, {parent_state}>;
-pub type {name}Reader<R> = ElementReader<R, {name}State>;
 
 impl {name}State {{
     pub fn new(bytes_left: usize, parent_state: {parent_state}) -> Self {{
         Self {{
             bytes_left,
+            unknown_size: false,
             parent_state,
             _phantom: PhantomData::<_>,
         }}
     }}
 }}
 
-impl<R: BufRead> {name}Reader<R> {{
-    pub fn new(reader: R, state: {name}State) -> Self {{
-        Self {{ reader, state }}
-    }}
-}}
-
 impl_skip_state_navigation!({name}State, {parent_state});
-impl_next_state_navigation!({name}State, {child_state}, [{child_pairs}]);
+impl_next_state_navigation!({name}State, {child_state}, [{child_pairs}], {excluded_ids});
                 "#,
                 name = element_name,
                 parent_state = parent_state_name.as_str(),
@@ -704,10 +1833,39 @@ impl_next_state_navigation!({name}State, {child_state}, [{child_pairs}]);
                         .map(|cname| format!("({cname}, {cname}State)")),
                     ", ".to_string()
                 )
-                .collect::<String>()
+                .collect::<String>(),
+                excluded_ids = excluded_ids(&element_name),
             )?;
+            if readers {
+                write!(
+                    writer,
+                    r#"
+pub type {name}Reader<R> = ElementReader<R, {name}State>;
+
+impl<R: BufRead> {name}Reader<R> {{
+    pub fn new(reader: R, state: {name}State) -> Self {{
+        Self {{
+            reader,
+            state,
+            unread: None,
+        }}
+    }}
+}}
+                    "#,
+                    name = element_name,
+                )?;
+            }
 
             if !elem_child_names.is_empty() {
+                let children = itertools::intersperse(
+                    elem_child_names
+                        .iter()
+                        .map(String::as_str)
+                        .chain(core::iter::once("Parent")),
+                    ", ",
+                )
+                .collect::<String>();
+
                 write!(
                     writer,
                     r#"
@@ -718,6 +1876,20 @@ pub enum {name}NextStates {{
     Parent({parent_state}),
 }}
 
+impl_from_substates_for_states!({name}NextStates, States, [{children}]);
+                    "#,
+                    name = element_name,
+                    parent_state = parent_state_name,
+                    child_states = elem_child_names
+                        .iter()
+                        .map(|name| format!("{name}({name}State), "))
+                        .collect::<String>(),
+                    children = children,
+                )?;
+                if readers {
+                    write!(
+                        writer,
+                        r#"
 #[derive(Debug, PartialEq)]
 #[enum_dispatch]
 pub enum {name}NextReaders<R> {{
@@ -725,35 +1897,27 @@ pub enum {name}NextReaders<R> {{
     Parent({parent_reader}<R>),
 }}
 
-impl_from_substates_for_states!({name}NextStates, States, [{children}]);
 impl_from_subreaders_for_readers!({name}NextReaders, Readers, [{children}]);
 
 impl_into_reader!({name}NextStates, {name}NextReaders, [{children}]);
 impl_from_readers_for_states!({name}NextReaders, {name}NextStates, [{children}]);
-                    "#,
-                    name = element_name,
-                    parent_state = parent_state_name,
-                    parent_reader = parent_reader_name,
-                    child_states = elem_child_names
-                        .iter()
-                        .map(|name| format!("{name}({name}State), "))
-                        .collect::<String>(),
-                    child_readers = elem_child_names
-                        .iter()
-                        .map(|name| format!("{name}({name}Reader<R>), "))
-                        .collect::<String>(),
-                    children = itertools::intersperse(
-                        elem_child_names
+                        "#,
+                        name = element_name,
+                        parent_reader = parent_reader_name,
+                        child_readers = elem_child_names
                             .iter()
-                            .map(String::as_str)
-                            .chain(core::iter::once("Parent")),
-                        ", "
-                    )
-                    .collect::<String>(),
-                )?;
+                            .map(|name| format!("{name}({name}Reader<R>), "))
+                            .collect::<String>(),
+                        children = children,
+                    )?;
+                }
             }
 
             if elem_parent_names.len() > 1 {
+                let parents =
+                    itertools::intersperse(elem_parent_names.iter().map(String::as_str), ", ")
+                        .collect::<String>();
+
                 write!(
                     writer,
                     r#"
@@ -762,89 +1926,1447 @@ impl_from_readers_for_states!({name}NextReaders, {name}NextStates, [{children}])
 pub enum {name}PrevStates {{
     {parent_states}
 }}
-#[derive(Debug, PartialEq)]
-#[enum_dispatch]
-pub enum {name}PrevReaders<R> {{
-    {parent_readers}
-}}
 
-impl_from_substates_for_states!({name}PrevStates, States, [_Document, Files, File]);
-impl_from_subreaders_for_readers!({name}PrevReaders, Readers, [_Document, Files, File]);
-
-impl_into_reader!({name}PrevStates, {name}PrevReaders, [_Document, Files, File]);
-impl_from_readers_for_states!({name}PrevReaders, {name}PrevStates, [_Document, Files, File]);
+impl_from_substates_for_states!({name}PrevStates, States, [{parents}]);
+impl_bytes_left!({name}PrevStates, [{parents}]);
                     "#,
                     name = element_name,
                     parent_states = elem_parent_names
                         .iter()
-                        .map(|name| format!("{name}({name}State),"))
-                        .collect::<String>(),
-                    parent_readers = elem_parent_names
-                        .iter()
-                        .map(|name| format!("{name}({name}Reader<R>),"))
+                        .map(|name| match name == &element_name {
+                            // a recursive element's state holds its parent's -> box the state
+                            true => format!("{name}(Box<{name}State>),"),
+                            false => format!("{name}({name}State),"),
+                        })
                         .collect::<String>(),
+                    parents = parents,
                 )?;
+                if elem_parent_names.contains(&element_name) {
+                    write!(
+                        writer,
+                        r#"
+impl From<{name}State> for {name}PrevStates {{
+    fn from(state: {name}State) -> Self {{
+        Self::{name}(Box::new(state))
+    }}
+}}
+
+impl From<Box<{name}State>> for States {{
+    fn from(state: Box<{name}State>) -> Self {{
+        (*state).into()
+    }}
+}}
+                        "#,
+                        name = element_name,
+                    )?;
+                }
+                if readers {
+                    write!(
+                        writer,
+                        r#"
+#[derive(Debug, PartialEq)]
+#[enum_dispatch]
+pub enum {name}PrevReaders<R> {{
+    {parent_readers}
+}}
+
+impl_from_subreaders_for_readers!({name}PrevReaders, Readers, [{parents}]);
+
+impl_into_reader!({name}PrevStates, {name}PrevReaders, [{parents}]);
+impl_from_readers_for_states!({name}PrevReaders, {name}PrevStates, [{parents}]);
+                        "#,
+                        name = element_name,
+                        parent_readers = elem_parent_names
+                            .iter()
+                            .map(|name| format!("{name}({name}Reader<R>),"))
+                            .collect::<String>(),
+                        parents = parents,
+                    )?;
+                }
             }
         }
 
         Ok(())
     }
 
-    pub fn write_package<P: AsRef<Path>>(&self, path: P) -> Result<(), WriteParserPackageError> {
-        let template_dir_path = {
-            let mut cwd = std::env::var("CARGO_MANIFEST_DIR")
-                .map(std::path::PathBuf::from)
-                .map_err(WriteParserPackageError::NoManifestPath)?;
-            cwd.pop();
-            cwd.push("base_template");
-            cwd
-        };
+    /// Writes a `Visitor` trait with a callback per element, & a `walk` function that drives a
+    /// visitor through a whole document with the generated readers. Without readers (see
+    /// `Builder::readers`), the module is left empty.
+    pub fn write_visitor<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if !self.options.readers {
+            return writeln!(writer, "// no visitor without the generated readers");
+        }
 
+        let mut elements = self
+            .elements
+            .values()
+            .map(|elem| (self.type_name(&elem.name), elem))
+            .collect::<Vec<_>>();
+        elements.sort_by(|(name1, _), (name2, _)| name1.cmp(name2));
+
+        let mut callbacks = String::new();
+        let mut arms = String::new();
+        for (name, elem) in elements.iter() {
+            let method = rust_method_name(&elem.name);
+            let value_type = match elem.r#type {
+                ElementType::Master => None,
+                ElementType::UnsignedInteger => Some("u64"),
+                ElementType::SignedInteger => Some("i64"),
+                ElementType::Float => Some("f64"),
+                ElementType::Date => Some("Date"),
+                ElementType::String | ElementType::Utf8 => Some("&str"),
+                ElementType::Binary => Some("&[u8]"),
+            };
+
+            match value_type {
+                Some(value_type) => {
+                    callbacks.push_str(&format!(
+                        r#"
+    fn visit_{method}(&mut self, _value: {value_type}) {{}}"#
+                    ));
+                    arms.push_str(&format!(
+                        r#"
+            parser::Readers::{name}(mut r) => {{
+                visitor.visit_{method}(r.read()?);
+                (r.next()?.into(), false)
+            }}"#
+                    ));
+                }
+                None => {
+                    callbacks.push_str(&format!(
+                        r#"
+    fn enter_{method}(&mut self) -> Visit {{
+        Visit::Continue
+    }}
+    fn exit_{method}(&mut self) {{}}"#
+                    ));
+                    // without any (generated) children, `next` skips straight to the parent
+                    let next = if self.children[&Some(elem.id)].is_empty() {
+                        format!(
+                            r#"
+                    visitor.exit_{method}();
+                    (r.next()?.into(), false)"#
+                        )
+                    } else {
+                        format!(
+                            r#"
+                    match r.next()? {{
+                        parser::{name}NextReaders::Parent(r_parent) => {{
+                            visitor.exit_{method}();
+                            (r_parent.into(), false)
+                        }}
+                        r_next => (r_next.into(), true),
+                    }}"#
+                        )
+                    };
+                    arms.push_str(&format!(
+                        r#"
+            parser::Readers::{name}(r) => {{
+                if entering && visitor.enter_{method}() == Visit::Skip {{
+                    (r.skip()?.into(), false)
+                }} else {{{next}
+                }}
+            }}"#
+                    ));
+                }
+            }
+        }
+
+        write!(
+            writer,
+            r#"
+#[allow(unused_imports)]
+use crate::base::date::Date;
+#[allow(unused_imports)]
+use crate::base::parser::{{
+    NextReaderNavigation, ReaderDataParser, ReaderError, SkipReaderNavigation, Visit,
+}};
+use crate::{module}::parser;
+
+use std::io::BufRead;
+
+/**
+Callbacks for the elements of a document, as called by `walk` in document order. Leaf elements
+are passed their decoded values; master elements are entered before, & exited after, their
+children. Returning `Visit::Skip` on entering a master element moves on past it, without calling
+its children's callbacks or its exit callback. The skipped data is still read from the reader (&
+discarded), just not decoded.
+
+All callbacks default to doing nothing.
+
+**/
+pub trait Visitor {{{callbacks}
+}}
+
+/// Reads a whole document, calling the visitor's callbacks for each element.
+pub fn walk<R: BufRead, V: Visitor + ?Sized>(
+    reader: parser::_DocumentReader<R>,
+    visitor: &mut V,
+) -> Result<(), ReaderError> {{
+    let mut reader: parser::Readers<R> = reader.into();
+    // whether the reader just descended into its element, or returned to it from a child
+    let mut entering = true;
+
+    loop {{
+        let (next_reader, next_entering) = match reader {{
+            parser::Readers::_Document(mut r) => {{
+                if r.reader.fill_buf()?.is_empty() {{
+                    return Ok(());
+                }}
+                (r.next()?.into(), true)
+            }}{arms}
+        }};
+        reader = next_reader;
+        entering = next_entering;
+    }}
+}}
+"#,
+            module = self.options.module_name,
+            callbacks = callbacks,
+            arms = arms,
+        )
+    }
+
+    pub fn write_events<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if !self.options.readers {
+            return writeln!(writer, "// no event iterator without the generated readers");
+        }
+
+        let mut elements = self
+            .elements
+            .values()
+            .map(|elem| (self.type_name(&elem.name), elem))
+            .collect::<Vec<_>>();
+        elements.sort_by(|(name1, _), (name2, _)| name1.cmp(name2));
+
+        let mut headers = String::new();
+        let mut arms = String::new();
+        for (name, elem) in elements.iter() {
+            headers.push_str(&format!(
+                r#"
+            parser::Readers::{name}(r) => (
+                element_defs::{name}Def::ID,
+                {elem_name:?},
+                r.state.size(),
+                r.reader.last_offset(),
+                r.reader.offset(),
+            ),"#,
+                elem_name = elem.name,
+            ));
+
+            match owned_value(&elem.r#type) {
+                Some((value_type, variant, owned)) => {
+                    arms.push_str(&format!(
+                        r#"
+            parser::Readers::{name}(mut r) => {{
+                let value: {value_type} = r.read()?;
+                self.value(Value::{variant}({owned}));
+                self.end();
+                r.next()?.into()
+            }}"#
+                    ));
+                }
+                // without any (generated) children, `next` skips straight to the parent
+                None if self.children[&Some(elem.id)].is_empty() => {
+                    arms.push_str(&format!(
+                        r#"
+            parser::Readers::{name}(r) => {{
+                self.end();
+                r.next()?.into()
+            }}"#
+                    ));
+                }
+                None => {
+                    arms.push_str(&format!(
+                        r#"
+            parser::Readers::{name}(r) => {{
+                match r.next()? {{
+                    parser::{name}NextReaders::Parent(r_parent) => {{
+                        self.end();
+                        r_parent.into()
+                    }}
+                    r_next => {{
+                        let next = r_next.into();
+                        self.start(&next)?;
+                        next
+                    }}
+                }}
+            }}"#
+                    ));
+                }
+            }
+        }
+
+        write!(
+            writer,
+            r#"
+#[allow(unused_imports)]
+use crate::base::date::Date;
+use crate::base::element_defs::ElementDef;
+use crate::base::events::{{Event, OffsetReader, Value}};
+#[allow(unused_imports)]
+use crate::base::parser::{{NextReaderNavigation, ReaderDataParser, ReaderError}};
+use crate::{module}::{{element_defs, parser}};
+
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+/**
+An iterator over the elements of a document as `Start`, `Value` & `End` events, in document order.
+
+Every element is started & ended; leaf elements yield their decoded value in between. Iteration
+ends at the end of the document, or after the first error.
+
+**/
+pub struct Events<R> {{
+    reader: Option<parser::Readers<OffsetReader<R>>>,
+    open: Vec<(u32, usize)>, // the IDs of the started elements, & the path lengths before them
+    path: String,
+    pending: VecDeque<Event>,
+}}
+
+impl<R: BufRead> Events<R> {{
+    pub fn new(reader: parser::_DocumentReader<R>) -> Self {{
+        Self {{
+            reader: Some(parser::_DocumentReader::new(OffsetReader::new(reader.reader)).into()),
+            open: Vec::new(),
+            path: String::new(),
+            pending: VecDeque::new(),
+        }}
+    }}
+
+    // Moves the reader by a single step, queueing the resulting events
+    fn step(
+        &mut self,
+        reader: parser::Readers<OffsetReader<R>>,
+    ) -> Result<Option<parser::Readers<OffsetReader<R>>>, ReaderError> {{
+        let next_reader = match reader {{
+            parser::Readers::_Document(mut r) => {{
+                if r.reader.fill_buf()?.is_empty() {{
+                    return Ok(None);
+                }}
+                let next = r.next()?.into();
+                self.start(&next)?;
+                next
+            }}{arms}
+        }};
+        Ok(Some(next_reader))
+    }}
+
+    // Starts the element that the reader just descended into: having discarded any skipped elements
+    // before it, the reader consumed the element's header last
+    fn start(&mut self, reader: &parser::Readers<OffsetReader<R>>) -> Result<(), ReaderError> {{
+        let (id, name, size, offset, data_offset) = match reader {{
+            parser::Readers::_Document(_) => unreachable!("the document isn't a child element"),{headers}
+        }};
+        if offset >= data_offset {{
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "the element header wasn't consumed in a single step",
+            )
+            .into());
+        }}
+
+        self.open.push((id, self.path.len()));
+        self.path.push('\\');
+        self.path.push_str(name);
+        self.pending.push_back(Event::Start {{
+            id,
+            path: self.path.clone(),
+            offset,
+            size: size.map(|size| size as u64),
+        }});
+        Ok(())
+    }}
+
+    fn value(&mut self, value: Value) {{
+        let (id, _) = *self
+            .open
+            .last()
+            .expect("values are read from started elements");
+        self.pending.push_back(Event::Value {{ id, value }});
+    }}
+
+    fn end(&mut self) {{
+        let (id, path_len) = self.open.pop().expect("only started elements are ended");
+        self.path.truncate(path_len);
+        self.pending.push_back(Event::End {{ id }});
+    }}
+}}
+
+impl<R: BufRead> Iterator for Events<R> {{
+    type Item = Result<Event, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {{
+        while self.pending.is_empty() {{
+            let reader = self.reader.take()?;
+            match self.step(reader) {{
+                Ok(next_reader) => self.reader = next_reader,
+                Err(err) => return Some(Err(err)),
+            }}
+        }}
+        self.pending.pop_front().map(Ok)
+    }}
+}}
+"#,
+            module = self.options.module_name,
+            arms = arms,
+            headers = headers,
+        )
+    }
+
+    pub fn write_select<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if !self.options.readers {
+            return writeln!(writer, "// no selection without the generated readers");
+        }
+
+        let mut elements = self
+            .elements
+            .values()
+            .map(|elem| (self.type_name(&elem.name), elem))
+            .collect::<Vec<_>>();
+        elements.sort_by(|(name1, _), (name2, _)| name1.cmp(name2));
+
+        let mut arms = String::new();
+        for (name, elem) in elements.iter() {
+            match owned_value(&elem.r#type) {
+                Some((value_type, variant, owned)) => {
+                    arms.push_str(&format!(
+                        r#"
+            parser::Readers::{name}(mut r) => {{
+                let value = if element_defs::{name}Def::PATH == self.path {{
+                    let value: {value_type} = r.read()?;
+                    Some(Value::{variant}({owned}))
+                }} else {{
+                    None
+                }};
+                (r.next()?.into(), false, value)
+            }}"#
+                    ));
+                }
+                // without any (generated) children, `next` skips straight to the parent
+                None if self.children[&Some(elem.id)].is_empty() => {
+                    arms.push_str(&format!(
+                        r#"
+            parser::Readers::{name}(r) => (r.next()?.into(), false, None),"#
+                    ));
+                }
+                None => {
+                    arms.push_str(&format!(
+                        r#"
+            parser::Readers::{name}(r) => {{
+                if self.entering && !path_contains(element_defs::{name}Def::PATH, self.path) {{
+                    (r.skip()?.into(), false, None)
+                }} else {{
+                    match r.next()? {{
+                        parser::{name}NextReaders::Parent(r_parent) => {{
+                            (r_parent.into(), false, None)
+                        }}
+                        r_next => (r_next.into(), true, None),
+                    }}
+                }}
+            }}"#
+                    ));
+                }
+            }
+        }
+
+        write!(
+            writer,
+            r#"
+#[allow(unused_imports)]
+use crate::base::date::Date;
+use crate::base::element_defs::{{path_contains, ElementDef}};
+use crate::base::events::Value;
+#[allow(unused_imports)]
+use crate::base::parser::{{
+    NextReaderNavigation, ReaderDataParser, ReaderError, SkipReaderNavigation,
+}};
+use crate::{module}::{{element_defs, parser}};
+
+use std::io::BufRead;
+
+/**
+An iterator over the decoded values of the leaf elements with a given schema path, in document
+order.
+
+Paths use the syntax of `ElementDef::PATH` (e.g., `\Files\File\FileName`); master elements whose
+path can't contain the selected elements are skipped over without reading their children. Paths
+of master elements, or paths not in the schema, select nothing. Iteration ends at the end of the
+document, or after the first error.
+
+**/
+pub struct Select<'a, R> {{
+    reader: Option<parser::Readers<R>>,
+    entering: bool, // whether the reader just descended into its element
+    path: &'a str,
+}}
+
+/// Selects the values of the given element, e.g. `select::<element_defs::FileNameDef, _>(reader)`.
+pub fn select<E: ElementDef, R: BufRead>(reader: parser::_DocumentReader<R>) -> Select<'static, R> {{
+    select_path(reader, E::PATH)
+}}
+
+/// Selects the values of the elements with the given schema path.
+pub fn select_path<R: BufRead>(reader: parser::_DocumentReader<R>, path: &str) -> Select<'_, R> {{
+    Select {{
+        reader: Some(reader.into()),
+        entering: true,
+        path,
+    }}
+}}
+
+impl<'a, R: BufRead> Select<'a, R> {{
+    // Moves the reader by a single step, reading the value of a selected element
+    #[allow(clippy::type_complexity)]
+    fn step(
+        &mut self,
+        reader: parser::Readers<R>,
+    ) -> Result<(Option<parser::Readers<R>>, Option<Value>), ReaderError> {{
+        let (next_reader, next_entering, value) = match reader {{
+            parser::Readers::_Document(mut r) => {{
+                if r.reader.fill_buf()?.is_empty() {{
+                    return Ok((None, None));
+                }}
+                (r.next()?.into(), true, None)
+            }}{arms}
+        }};
+        self.entering = next_entering;
+        Ok((Some(next_reader), value))
+    }}
+}}
+
+impl<'a, R: BufRead> Iterator for Select<'a, R> {{
+    type Item = Result<Value, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {{
+        loop {{
+            let reader = self.reader.take()?;
+            match self.step(reader) {{
+                Ok((next_reader, value)) => {{
+                    self.reader = next_reader;
+                    if value.is_some() {{
+                        return value.map(Ok);
+                    }}
+                }}
+                Err(err) => return Some(Err(err)),
+            }}
+        }}
+    }}
+}}
+"#,
+            module = self.options.module_name,
+            arms = arms,
+        )
+    }
+
+    pub fn write_decoder<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let mut elements = self
+            .elements
+            .values()
+            .map(|elem| (self.type_name(&elem.name), elem))
+            .collect::<Vec<_>>();
+        elements.sort_by(|(name1, _), (name2, _)| name1.cmp(name2));
+
+        let skipped_ids = self
+            .skipped
+            .values()
+            .flatten()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|id| format!("{:#X}", id))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut headers = String::new();
+        let mut ends = String::new();
+        let mut arms = String::new();
+        for (name, elem) in elements.iter() {
+            headers.push_str(&format!(
+                r#"
+            parser::States::{name}(s) => (element_defs::{name}Def::ID, {elem_name:?}, s.size()),"#,
+                elem_name = elem.name,
+            ));
+            ends.push_str(&format!(
+                r#"
+            parser::States::{name}(s) => s.end_of_stream().ok().map(Into::into),"#
+            ));
+
+            match owned_value(&elem.r#type) {
+                Some((value_type, variant, owned)) => {
+                    arms.push_str(&format!(
+                        r#"
+            parser::States::{name}(s) => {{
+                let (next_stream, (parent, value)): (_, (_, {value_type})) = s.read(stream)?;
+                self.value(Value::{variant}({owned}));
+                self.end();
+                (next_stream, parent.into())
+            }}"#
+                    ));
+                }
+                // without any (generated) children, `next` skips straight to the parent
+                None if self.children[&Some(elem.id)].is_empty() => {
+                    arms.push_str(&format!(
+                        r#"
+            parser::States::{name}(s) => {{
+                let (next_stream, parent) = s.next(stream)?;
+                self.end();
+                (next_stream, parent.into())
+            }}"#
+                    ));
+                }
+                None => {
+                    arms.push_str(&format!(
+                        r#"
+            parser::States::{name}(s) => {{
+                let headers = scan_headers(stream, self.offset, SKIPPED_IDS);
+                match s.next(stream)? {{
+                    (next_stream, parser::{name}NextStates::Parent(parent)) => {{
+                        self.end();
+                        (next_stream, parent.into())
+                    }}
+                    (next_stream, next) => {{
+                        let next = next.into();
+                        let data_offset = self.offset + stream_diff(stream, next_stream) as u64;
+                        self.start(&next, &headers, data_offset);
+                        (next_stream, next)
+                    }}
+                }}
+            }}"#
+                    ));
+                }
+            }
+        }
+
+        write!(
+            writer,
+            r#"
+#[allow(unused_imports)]
+use crate::base::date::Date;
+use crate::base::element_defs::ElementDef;
+use crate::base::events::{{scan_headers, Event, Value}};
+#[allow(unused_imports)]
+use crate::base::parser::{{NextStateNavigation, StateDataParser, StateError}};
+use crate::base::stream::stream_diff;
+use crate::{module}::{{element_defs, parser}};
+
+use std::collections::vec_deque::{{Drain, VecDeque}};
+
+// the IDs of the elements skipped over by the states, under any parent
+const SKIPPED_IDS: &[u32] = &[{skipped_ids}];
+
+/**
+A push-based decoder of a document that arrives in chunks of any size, e.g. from network packets.
+
+Pushed bytes are buffered until they complete the next element header (or leaf element), at which
+point its `Start`, `Value` & `End` events are queued, in document order. An incomplete header or
+leaf element never loses the decoder's state; decoding resumes once the next chunk is pushed.
+
+Leaf elements are decoded as a whole, so their data is buffered in full. An unknown-sized element
+ends at the first element that isn't its child, or at the end of the stream, as marked by `finish`.
+
+**/
+#[derive(Debug)]
+pub struct Decoder {{
+    state: parser::States,
+    buffer: Vec<u8>,         // the pushed bytes that aren't decoded yet
+    offset: u64,             // the stream position of the first buffered byte
+    open: Vec<(u32, usize)>, // the IDs of the started elements, & the path lengths before them
+    path: String,
+    pending: VecDeque<Event>,
+}}
+
+impl Default for Decoder {{
+    fn default() -> Self {{
+        Self::new()
+    }}
+}}
+
+impl Decoder {{
+    pub fn new() -> Self {{
+        Self {{
+            state: parser::_DocumentState.into(),
+            buffer: Vec::new(),
+            offset: 0,
+            open: Vec::new(),
+            path: String::new(),
+            pending: VecDeque::new(),
+        }}
+    }}
+
+    /// Pushes the next chunk of the stream, decoding as many elements as it completes. On an
+    /// error, the decoder stays at the last element that was decoded.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), StateError> {{
+        self.buffer.extend_from_slice(chunk);
+        let buffer = std::mem::take(&mut self.buffer);
+
+        let mut stream = &buffer[..];
+        let result = loop {{
+            // steps from a copy of the state, so that it's kept if the step is incomplete
+            match self.step(self.state.clone(), stream) {{
+                Ok((next_stream, next_state)) => {{
+                    self.offset += stream_diff(stream, next_stream) as u64;
+                    self.state = next_state;
+                    stream = next_stream;
+                }}
+                Err(nom::Err::Incomplete(_)) => break Ok(()),
+                Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => break Err(err),
+            }}
+        }};
+        let consumed = buffer.len() - stream.len();
+
+        self.buffer = buffer;
+        self.buffer.drain(..consumed);
+        result
+    }}
+
+    /// Removes & returns the queued events, in document order.
+    pub fn events(&mut self) -> Drain<'_, Event> {{
+        self.pending.drain(..)
+    }}
+
+    /// Whether the pushed bytes end between top-level elements, i.e. form a complete document.
+    pub fn is_complete(&self) -> bool {{
+        matches!(self.state, parser::States::_Document(_)) && self.buffer.is_empty()
+    }}
+
+    /// Ends the stream, & with it any open unknown-sized elements (queueing their `End` events).
+    /// Returns whether the pushed bytes then form a complete document.
+    pub fn finish(&mut self) -> bool {{
+        while self.buffer.is_empty() {{
+            match Self::end_of_stream(self.state.clone()) {{
+                Some(next_state) => {{
+                    self.end();
+                    self.state = next_state;
+                }}
+                None => break,
+            }}
+        }}
+        self.is_complete()
+    }}
+
+    // Ends the unknown-sized element of the state at the end of the stream, moving on to its parent
+    fn end_of_stream(state: parser::States) -> Option<parser::States> {{
+        match state {{
+            parser::States::_Document(_) => None,{ends}
+        }}
+    }}
+
+    // Moves the state by a single step over the buffered stream, queueing the resulting events
+    fn step<'a>(
+        &mut self,
+        state: parser::States,
+        stream: &'a [u8],
+    ) -> nom::IResult<&'a [u8], parser::States, StateError> {{
+        Ok(match state {{
+            parser::States::_Document(s) => {{
+                let headers = scan_headers(stream, self.offset, SKIPPED_IDS);
+                let (next_stream, next) = s.next(stream)?;
+                let next = next.into();
+                let data_offset = self.offset + stream_diff(stream, next_stream) as u64;
+                self.start(&next, &headers, data_offset);
+                (next_stream, next)
+            }}{arms}
+        }})
+    }}
+
+    // Starts the element that the state just descended into, given the headers scanned beforehand
+    fn start(&mut self, state: &parser::States, headers: &[(u64, u64)], data_offset: u64) {{
+        let (id, name, size) = match state {{
+            parser::States::_Document(_) => unreachable!("the document isn't a child element"),{headers}
+        }};
+        let offset = headers
+            .iter()
+            .find(|(_, header_end)| *header_end == data_offset)
+            .map(|(header_start, _)| *header_start)
+            .expect("the states only descend into scanned headers");
+
+        self.open.push((id, self.path.len()));
+        self.path.push('\\');
+        self.path.push_str(name);
+        self.pending.push_back(Event::Start {{
+            id,
+            path: self.path.clone(),
+            offset,
+            size: size.map(|size| size as u64),
+        }});
+    }}
+
+    fn value(&mut self, value: Value) {{
+        let (id, _) = *self
+            .open
+            .last()
+            .expect("values are read from started elements");
+        self.pending.push_back(Event::Value {{ id, value }});
+    }}
+
+    fn end(&mut self) {{
+        let (id, path_len) = self.open.pop().expect("only started elements are ended");
+        self.path.truncate(path_len);
+        self.pending.push_back(Event::End {{ id }});
+    }}
+}}
+"#,
+            module = self.options.module_name,
+            skipped_ids = skipped_ids,
+            arms = arms,
+            headers = headers,
+            ends = ends,
+        )
+    }
+
+    pub fn write_codec<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        write!(
+            writer,
+            r#"
+use crate::base::codec::{{CodecError, EventEncoder}};
+use crate::base::events::Event;
+use crate::base::parser::StateError;
+use crate::{module}::decoder::Decoder;
+
+use bytes::BytesMut;
+
+use std::collections::VecDeque;
+
+/**
+A `tokio_util` codec between EBML streams & their `Start`, `Value` & `End` events, e.g. to read &
+write documents over framed sockets.
+
+Decoding is incremental, as with `Decoder`; any events decoded before an error are yielded first.
+Encoding buffers each top-level element until it ends, as with `EventEncoder`.
+
+**/
+#[derive(Debug, Default)]
+pub struct EventCodec {{
+    decoder: Decoder,
+    pending: VecDeque<Event>,
+    error: Option<StateError>,
+    encoder: EventEncoder,
+}}
+
+impl EventCodec {{
+    pub fn new() -> Self {{
+        Self::default()
+    }}
+
+    /// Encodes with the given encoder, e.g. one with set `SizeWidth`s.
+    pub fn with_encoder(encoder: EventEncoder) -> Self {{
+        Self {{
+            encoder,
+            ..Self::default()
+        }}
+    }}
+}}
+
+impl tokio_util::codec::Decoder for EventCodec {{
+    type Item = Event;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Event>, CodecError> {{
+        // the decoder buffers incomplete elements itself
+        if !src.is_empty() && self.error.is_none() {{
+            let result = self.decoder.push(&src.split());
+            self.pending.extend(self.decoder.events());
+            self.error = result.err();
+        }}
+
+        match self.pending.pop_front() {{
+            Some(event) => Ok(Some(event)),
+            None => self.error.take().map_or(Ok(None), |err| Err(err.into())),
+        }}
+    }}
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Event>, CodecError> {{
+        if let Some(event) = self.decode(src)? {{
+            return Ok(Some(event));
+        }}
+
+        // the stream may end within unknown-sized elements
+        let complete = self.decoder.finish();
+        self.pending.extend(self.decoder.events());
+        match self.pending.pop_front() {{
+            Some(event) => Ok(Some(event)),
+            None if complete => Ok(None),
+            None => Err(CodecError::Incomplete),
+        }}
+    }}
+}}
+
+impl tokio_util::codec::Encoder<Event> for EventCodec {{
+    type Error = CodecError;
+
+    fn encode(&mut self, event: Event, dst: &mut BytesMut) -> Result<(), CodecError> {{
+        self.encoder.encode(event, dst)
+    }}
+}}
+"#,
+            module = self.options.module_name,
+        )
+    }
+
+    // The match arms of `unknown_size_children`: the children of master elements that allow an
+    // unknown size (whether read, skipped or rejected), & an error for any other element
+    fn unknown_size_arms(&self) -> String {
+        let mut arms = String::new();
+        let mut sized_ids = Vec::new();
+        for elem in self.elements.values() {
+            if elem.r#type != ElementType::Master || elem.unknownsizeallowed != Some(true) {
+                sized_ids.push(format!("{:#X}", elem.id));
+                continue;
+            }
+
+            let id = Some(elem.id);
+            let child_ids = [&self.children, &self.skipped, &self.rejected]
+                .iter()
+                .filter_map(|ids_by_parent| ids_by_parent.get(&id))
+                .flatten()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(|id| format!("{:#X}", id))
+                .collect::<Vec<_>>()
+                .join(", ");
+            arms.push_str(&format!(
+                "\n        {:#X} => Ok(&[{}]),",
+                elem.id, child_ids
+            ));
+        }
+        if !sized_ids.is_empty() {
+            arms.push_str(&format!(
+                "\n        {} => Err(StateError::UnknownSizeNotAllowed(id)),",
+                sized_ids.join(" | ")
+            ));
+        }
+        arms
+    }
+
+    // Whether the master element `from` can contain the element `to`, at any depth
+    fn contains_recursively(&self, from: u32, to: u32) -> bool {
+        let mut seen = BTreeSet::new();
+        let mut stack = vec![from];
+        while let Some(id) = stack.pop() {
+            for &child_id in self.children.get(&Some(id)).into_iter().flatten() {
+                if child_id == to {
+                    return true;
+                }
+                if seen.insert(child_id) {
+                    stack.push(child_id);
+                }
+            }
+        }
+        false
+    }
+
+    pub fn write_owned<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if !self.options.owned {
+            return writeln!(writer, "// no owned types without `Builder::owned`");
+        }
+
+        let mut derives = self.derives();
+        if !self
+            .options
+            .derives
+            .iter()
+            .any(|derive| derive == "Default")
+        {
+            derives.push_str(", Default");
+        }
+
+        writeln!(
+            writer,
+            r#"
+#[allow(unused_imports)]
+use crate::base::element_defs::{{
+    DateElementDef as _, ElementDef as _, FloatElementDef as _, IntElementDef as _,
+    UIntElementDef as _,
+}};
+use crate::base::{{events, owned as base}};
+use crate::{module}::element_defs;"#,
+            module = self.options.module_name,
+        )?;
+
+        let masters = std::iter::once((None, "_Document".to_string())).chain(
+            self.elements
+                .values()
+                .filter(|elem| elem.r#type == ElementType::Master)
+                .map(|elem| (Some(elem.id), self.type_name(&elem.name))),
+        );
+        for (id, name) in masters {
+            let mut fields = String::new();
+            let mut read_arms = String::new();
+            let mut write_stmts = String::new();
+            let mut validate_stmts = String::new();
+            for child in self.children[&id].iter().map(|cid| &self.elements[cid]) {
+                let child_name = self.type_name(&child.name);
+                let def = format!("element_defs::{}Def", child_name);
+                let field = rust_field_name(&child.name);
+                let is_single = child.max_occurs == Some(1);
+                // a struct can't hold itself, even indirectly, without an indirection
+                let is_boxed = is_single
+                    && child.r#type == ElementType::Master
+                    && id.is_some_and(|id| self.contains_recursively(child.id, id));
+
+                let value_type = match child.r#type {
+                    ElementType::Master => child_name.as_str(),
+                    ElementType::UnsignedInteger => "u64",
+                    ElementType::SignedInteger => "i64",
+                    ElementType::Float => "f64",
+                    ElementType::Date => "crate::base::date::Date",
+                    ElementType::String | ElementType::Utf8 => "String",
+                    ElementType::Binary => "Vec<u8>",
+                };
+                let field_type = match (is_single, is_boxed) {
+                    (true, true) => format!("Option<Box<{}>>", value_type),
+                    (true, false) => format!("Option<{}>", value_type),
+                    (false, _) => format!("Vec<{}>", value_type),
+                };
+                fields.push_str(&format!(
+                    "\n    pub {field}: {field_type}, // {name}",
+                    name = child.name,
+                ));
+
+                let read = match (&child.r#type, is_boxed) {
+                    (ElementType::Master, true) => {
+                        format!("Box::new({}::from_events(iter)?)", child_name)
+                    }
+                    (ElementType::Master, false) => format!("{}::from_events(iter)?", child_name),
+                    _ => format!("base::read_value(iter, {}::ID)?", def),
+                };
+                let read = match is_single {
+                    true => format!("base::set_once(&mut owned.{field}, {read}, {def}::ID)?"),
+                    false => format!("owned.{field}.push({read})"),
+                };
+                read_arms.push_str(&format!(
+                    "\n                events::Event::Start {{ id: {def}::ID, .. }} => {read},"
+                ));
+
+                let data = match child.r#type {
+                    ElementType::Master => None,
+                    ElementType::UnsignedInteger => Some("base::uint_data(*value).as_deref()"),
+                    ElementType::SignedInteger => Some("base::int_data(*value).as_deref()"),
+                    ElementType::Float => Some("base::float_data(*value).as_deref()"),
+                    ElementType::Date => Some("base::date_data(*value).as_deref()"),
+                    ElementType::String => Some("base::ascii_data(value)"),
+                    ElementType::Utf8 => Some("Some(value.as_bytes())"),
+                    ElementType::Binary => Some("Some(value.as_slice())"),
+                };
+                write_stmts.push_str(&match data {
+                    Some(data) => format!(
+                        r#"
+        for value in self.{field}.iter() {{
+            base::write_element(buffer, {def}::ID, {data})?;
+        }}"#
+                    ),
+                    None => format!(
+                        r#"
+        for value in self.{field}.iter() {{
+            let mut data = Vec::new();
+            value.write_data(&mut data)?;
+            base::write_element(buffer, {def}::ID, Some(&data))?;
+        }}"#
+                    ),
+                });
+
+                validate_stmts.push_str(&format!(
+                    "\n        base::check_occurrences::<{def}>({count}, {has_default})?;",
+                    count = match is_single {
+                        true => format!("usize::from(self.{field}.is_some())"),
+                        false => format!("self.{field}.len()"),
+                    },
+                    has_default = child.default.is_some(),
+                ));
+                match child.r#type {
+                    ElementType::Master => validate_stmts.push_str(&format!(
+                        r#"
+        for value in self.{field}.iter() {{
+            value.validate()?;
+        }}"#
+                    )),
+                    ElementType::UnsignedInteger
+                    | ElementType::SignedInteger
+                    | ElementType::Float
+                    | ElementType::Date => validate_stmts.push_str(&format!(
+                        r#"
+        for value in self.{field}.iter() {{
+            base::check_range(&{def}::RANGE, value, {def}::ID)?;
+        }}"#
+                    )),
+                    _ => {}
+                }
+            }
+
+            let owned = match read_arms.is_empty() {
+                true => "owned",
+                false => "mut owned",
+            };
+            match id {
+                None => write!(
+                    writer,
+                    r#"
+/**
+A document's top-level elements. Reads all of the `Start`, `Value` & `End` events of a document,
+e.g. as yielded by the `Events` iterator or `Decoder`.
+
+**/
+#[derive({derives})]
+pub struct {name} {{{fields}
+}}
+
+impl {name} {{
+    pub fn from_events<I: IntoIterator<Item = events::Event>>(
+        events: I,
+    ) -> Result<Self, base::OwnedError> {{
+        let iter = &mut events.into_iter();
+        let {owned} = Self::default();
+        while let Some(event) = iter.next() {{
+            match event {{{read_arms}
+                event => return Err(base::OwnedError::Unexpected(event)),
+            }}
+        }}
+        Ok(owned)
+    }}
+}}
+"#
+                ),
+                Some(_) => write!(
+                    writer,
+                    r#"
+#[derive({derives})]
+pub struct {name} {{{fields}
+}}
+
+impl {name} {{
+    /// Reads the element from the events following its `Start` event, up to & including its
+    /// `End` event.
+    pub fn from_events<I: Iterator<Item = events::Event>>(
+        iter: &mut I,
+    ) -> Result<Self, base::OwnedError> {{
+        let {owned} = Self::default();
+        loop {{
+            match iter.next().ok_or(base::OwnedError::Incomplete)? {{
+                events::Event::End {{ id: element_defs::{name}Def::ID }} => return Ok(owned),{read_arms}
+                event => return Err(base::OwnedError::Unexpected(event)),
+            }}
+        }}
+    }}
+}}
+"#
+                ),
+            }?;
+
+            if self.options.writers {
+                write!(
+                    writer,
+                    r#"
+impl {name} {{
+    /// Appends the element's children to `buffer`.
+    pub fn write_data(&self, buffer: &mut Vec<u8>) -> Result<(), base::WriteError> {{{write_stmts}
+        Ok(())
+    }}
+}}
+"#
+                )?;
+                if id.is_none() {
+                    write!(
+                        writer,
+                        r#"
+impl {name} {{
+    pub fn write<W: std::io::Write>(&self, writer: &mut W) -> Result<(), base::WriteError> {{
+        let mut buffer = Vec::new();
+        self.write_data(&mut buffer)?;
+        writer.write_all(&buffer)?;
+        Ok(())
+    }}
+}}
+"#
+                    )?;
+                }
+            }
+
+            if self.options.validation {
+                write!(
+                    writer,
+                    r#"
+impl {name} {{
+    /// Checks the occurrences & ranges of the element's children against the schema,
+    /// recursively.
+    pub fn validate(&self) -> Result<(), base::ValidationError> {{{validate_stmts}
+        Ok(())
+    }}
+}}
+"#
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn write_package<P: AsRef<Path>>(&self, path: P) -> Result<(), WriteParserPackageError> {
+        let module_dir = format!("src/{}/", self.options.module_name);
         std::fs::create_dir_all((path.as_ref()).join("src/base/"))
             .map_err(WriteParserPackageError::IOError)?;
-        std::fs::create_dir_all((path.as_ref()).join("src/core/"))
+        std::fs::create_dir_all((path.as_ref()).join(&module_dir))
             .map_err(WriteParserPackageError::IOError)?;
 
-        for filename in &[
-            "Cargo.toml",
-            "src/lib.rs",
-            "src/base/element_defs.rs",
-            "src/base/mod.rs",
-            "src/base/parser.rs",
-            "src/base/stream.rs",
-            "src/core/mod.rs",
-        ] {
-            std::fs::copy(
-                template_dir_path.join(filename),
-                path.as_ref().join(filename),
-            )
+        for (filename, contents) in crate::base::TEMPLATE_BASE_FILES {
+            std::fs::write(path.as_ref().join("src/base/").join(filename), contents)
+                .map_err(WriteParserPackageError::IOError)?;
+        }
+        std::fs::write(
+            path.as_ref().join(&module_dir).join("mod.rs"),
+            crate::base::TEMPLATE_CORE_MOD,
+        )
+        .map_err(WriteParserPackageError::IOError)?;
+
+        let manifest = crate::base::TEMPLATE_MANIFEST.replace(
+            "edition = \"2018\"",
+            &format!("edition = \"{}\"", self.options.edition.as_str()),
+        );
+        std::fs::write(path.as_ref().join("Cargo.toml"), manifest)
             .map_err(WriteParserPackageError::IOError)?;
+        std::fs::write(
+            path.as_ref().join("src/lib.rs"),
+            format!("pub mod base;\npub mod {};\n", self.options.module_name),
+        )
+        .map_err(WriteParserPackageError::IOError)?;
+
+        {
+            let mut writer =
+                std::fs::File::create(path.as_ref().join(&module_dir).join("codec.rs"))
+                    .map(std::io::BufWriter::new)
+                    .map_err(WriteParserPackageError::IOError)?;
+            self.write_codec(&mut writer)
+                .map_err(WriteParserPackageError::IOError)?;
+        }
+
+        {
+            let mut writer =
+                std::fs::File::create(path.as_ref().join(&module_dir).join("decoder.rs"))
+                    .map(std::io::BufWriter::new)
+                    .map_err(WriteParserPackageError::IOError)?;
+            self.write_decoder(&mut writer)
+                .map_err(WriteParserPackageError::IOError)?;
+        }
+
+        {
+            let mut writer =
+                std::fs::File::create(path.as_ref().join(&module_dir).join("element_defs.rs"))
+                    .map(std::io::BufWriter::new)
+                    .map_err(WriteParserPackageError::IOError)?;
+            self.write_element_defs(&mut writer)
+                .map_err(WriteParserPackageError::IOError)?;
+        }
+
+        {
+            let mut writer =
+                std::fs::File::create(path.as_ref().join(&module_dir).join("owned.rs"))
+                    .map(std::io::BufWriter::new)
+                    .map_err(WriteParserPackageError::IOError)?;
+            self.write_owned(&mut writer)
+                .map_err(WriteParserPackageError::IOError)?;
+        }
+
+        {
+            let mut writer =
+                std::fs::File::create(path.as_ref().join(&module_dir).join("parser.rs"))
+                    .map(std::io::BufWriter::new)
+                    .map_err(WriteParserPackageError::IOError)?;
+            self.write_parsers(&mut writer)
+                .map_err(WriteParserPackageError::IOError)?;
         }
 
         {
-            let mut writer = std::fs::File::create(path.as_ref().join("src/core/element_defs.rs"))
-                .map(std::io::BufWriter::new)
-                .map_err(WriteParserPackageError::IOError)?;
-            self.write_element_defs(&mut writer)
+            let mut writer =
+                std::fs::File::create(path.as_ref().join(&module_dir).join("visitor.rs"))
+                    .map(std::io::BufWriter::new)
+                    .map_err(WriteParserPackageError::IOError)?;
+            self.write_visitor(&mut writer)
                 .map_err(WriteParserPackageError::IOError)?;
         }
 
         {
-            let mut writer = std::fs::File::create(path.as_ref().join("src/core/parser.rs"))
-                .map(std::io::BufWriter::new)
+            let mut writer =
+                std::fs::File::create(path.as_ref().join(&module_dir).join("events.rs"))
+                    .map(std::io::BufWriter::new)
+                    .map_err(WriteParserPackageError::IOError)?;
+            self.write_events(&mut writer)
                 .map_err(WriteParserPackageError::IOError)?;
-            self.write_parsers(&mut writer)
+        }
+
+        {
+            let mut writer =
+                std::fs::File::create(path.as_ref().join(&module_dir).join("select.rs"))
+                    .map(std::io::BufWriter::new)
+                    .map_err(WriteParserPackageError::IOError)?;
+            self.write_select(&mut writer)
                 .map_err(WriteParserPackageError::IOError)?;
         }
 
         Ok(())
     }
+
+    /// Writes the base & schema-specific modules into a single self-contained file `ebml.rs` in
+    /// the given directory, meant to be used from a build script:
+    ///
+    /// ```ignore
+    /// mod ebml {
+    ///     include!(concat!(env!("OUT_DIR"), "/ebml.rs"));
+    /// }
+    /// ```
+    ///
+    /// The including crate needs the same dependencies as a generated package (`nom`,
+    /// `enum_dispatch`, `thiserror`). If the schema was read with `Builder::from_path`, this also
+    /// prints `cargo:rerun-if-changed` for the schema file.
+    pub fn write_module<P: AsRef<Path>>(&self, out_dir: P) -> Result<(), WriteParserPackageError> {
+        use std::io::Write;
+
+        let mut writer = std::fs::File::create(out_dir.as_ref().join("ebml.rs"))
+            .map(std::io::BufWriter::new)
+            .map_err(WriteParserPackageError::IOError)?;
+        self.write_module_source(&mut writer)
+            .and_then(|_| writer.flush())
+            .map_err(WriteParserPackageError::IOError)?;
+
+        if let Some(schema_path) = &self.options.schema_path {
+            println!("cargo:rerun-if-changed={}", schema_path.display());
+        }
+
+        Ok(())
+    }
+
+    /// Writes the source of the self-contained module written by `write_module`.
+    pub fn write_module_source<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let module_name = &self.options.module_name;
+        let mut codec = Vec::new();
+        self.write_codec(&mut codec)?;
+        let mut decoder = Vec::new();
+        self.write_decoder(&mut decoder)?;
+        let mut element_defs = Vec::new();
+        self.write_element_defs(&mut element_defs)?;
+        let mut owned = Vec::new();
+        self.write_owned(&mut owned)?;
+        let mut parsers = Vec::new();
+        self.write_parsers(&mut parsers)?;
+        let mut visitor = Vec::new();
+        self.write_visitor(&mut visitor)?;
+        let mut events = Vec::new();
+        self.write_events(&mut events)?;
+        let mut select = Vec::new();
+        self.write_select(&mut select)?;
+
+        // The template & generated code refer to its modules by absolute paths, which only hold
+        // if the file is included at the crate root; make them relative. Macros from an `include!`
+        // can't be exported & imported by path, so they're left in textual scope instead (see
+        // `write_inline_modules`). Tests are compiled out, since the including crate doesn't have
+        // their dev-dependencies.
+        let base_files = crate::base::TEMPLATE_BASE_FILES
+            .iter()
+            .map(|(filename, contents)| {
+                let name = filename.trim_end_matches(".rs");
+                let contents = inline_source(
+                    name,
+                    contents,
+                    &[
+                        ("crate::base::", "super::"),
+                        ("#[macro_export]\n", ""),
+                        ("#[cfg(test)]", "#[cfg(any())]"),
+                    ],
+                )?;
+                Ok((name, contents))
+            })
+            .collect::<std::io::Result<BTreeMap<_, _>>>()?;
+        let module_prefix = format!("crate::{}::", module_name);
+        let relative = |name, contents: Vec<u8>| {
+            let contents = inline_source(
+                name,
+                &String::from_utf8_lossy(&contents),
+                &[
+                    ("crate::base::", "super::super::base::"),
+                    (MACRO_IMPORTS, ""),
+                    (&module_prefix, "super::"),
+                ],
+            )?;
+            Ok((name, contents))
+        };
+        let module_files = vec![
+            relative("codec", codec),
+            relative("decoder", decoder),
+            relative("element_defs", element_defs),
+            relative("events", events),
+            relative("owned", owned),
+            relative("parser", parsers),
+            relative("select", select),
+            relative("visitor", visitor),
+        ]
+        .into_iter()
+        .collect::<std::io::Result<BTreeMap<_, _>>>()?;
+
+        writeln!(writer, "// Generated by iron-ebmlem; do not edit.")?;
+        // the base template's `chrono`, `time` & `codec` features aren't the including crate's
+        writeln!(writer, "#[allow(unknown_lints, unexpected_cfgs)]")?;
+        writeln!(writer, "#[macro_use]\npub mod base {{")?;
+        write_inline_modules(writer, base_files["mod"].as_str(), &base_files)?;
+        writeln!(writer, "}}")?;
+        writeln!(writer, "#[allow(unknown_lints, unexpected_cfgs)]")?;
+        writeln!(writer, "pub mod {} {{", module_name)?;
+        write_inline_modules(writer, crate::base::TEMPLATE_CORE_MOD, &module_files)?;
+        writeln!(writer, "}}")
+    }
+}
+
+// Applies the rewrites of `Parsers::write_module_source` to a module's source. Fails if any
+// absolute path, exported macro or test module is left over, i.e. if the rewrites missed part of
+// the template (e.g., after it was reformatted), rather than writing a module that won't compile.
+fn inline_source(name: &str, contents: &str, rewrites: &[(&str, &str)]) -> std::io::Result<String> {
+    let contents = rewrites
+        .iter()
+        .fold(contents.to_string(), |contents, (from, to)| {
+            contents.replace(from, to)
+        });
+    match ["crate::", "#[macro_export]", "#[cfg(test)]"]
+        .iter()
+        .find(|pattern| contents.contains(*pattern))
+    {
+        Some(pattern) => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{} is left in the inlined module {}", pattern, name),
+        )),
+        None => Ok(contents),
+    }
+}
+
+// Expands each `[pub] mod name;` declaration in a `mod.rs` file into an inline module. Each is
+// marked `#[macro_use]`, so that macros stay in scope for the modules written after them. Attribute
+// lines (e.g., `#[cfg(feature = "codec")]`) are kept, applying to the module declared after them.
+fn write_inline_modules<W: std::io::Write, S: AsRef<str>>(
+    writer: &mut W,
+    mod_rs: &str,
+    files: &BTreeMap<&str, S>,
+) -> std::io::Result<()> {
+    for decl in mod_rs.lines().map(|line| line.trim().trim_end_matches(';')) {
+        if decl.starts_with("#[") {
+            writeln!(writer, "{}", decl)?;
+            continue;
+        }
+        let name = match decl.rsplit(' ').next() {
+            Some(name) if !name.is_empty() => name,
+            _ => continue,
+        };
+        let contents = files.get(name).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no template file for module {}", name),
+            )
+        })?;
+        writeln!(writer, "#[macro_use]\n{} {{", decl)?;
+        writer.write_all(contents.as_ref().as_bytes())?;
+        writeln!(writer, "}}")?;
+    }
+    Ok(())
 }
 
 #[derive(thiserror::Error, Debug)]
 pub enum WriteParserPackageError {
-    #[error("no path to cargo manifest: {0}")]
-    NoManifestPath(std::env::VarError),
     #[error("IO error: {0}")]
     IOError(std::io::Error),
 }
@@ -889,6 +3411,32 @@ mod tests {
         assert_eq!(s.parse(), expt_result);
     }
 
+    #[rstest]
+    #[case("2001-01-01", Ok(DateRange::IsExactly(Date::EPOCH)))]
+    #[case("not 1", Ok(DateRange::Excludes(Date::from_nanos(1))))]
+    #[case(
+        ">=2001-01-01T00:00:00Z",
+        Ok(DateRange::IsWithin(Bound::Included(Date::EPOCH), Bound::Unbounded))
+    )]
+    #[case(
+        ">0,<2001-01-01T00:00:01Z",
+        Ok(DateRange::IsWithin(
+            Bound::Excluded(Date::EPOCH),
+            Bound::Excluded(Date::from_nanos(1_000_000_000)),
+        ))
+    )]
+    #[case(">0,>1", Err(DateRangeParserError::DuplicateBound(">1".to_string())))]
+    #[case(">0,1", Err(DateRangeParserError::InvalidBound("1".to_string())))]
+    #[case("2001-13-01", Err(DateRangeParserError::InvalidDate(
+        DateError::InvalidFormat("2001-13-01".to_string()),
+    )))]
+    fn date_range_parse(
+        #[case] s: &'static str,
+        #[case] expt_result: Result<DateRange, DateRangeParserError>,
+    ) {
+        assert_eq!(s.parse(), expt_result);
+    }
+
     #[fixture]
     fn schema() -> EbmlSchema {
         EbmlSchema {
@@ -970,7 +3518,11 @@ mod tests {
 
     #[rstest]
     fn builder_generate(schema: EbmlSchema) {
-        let result = Builder { schema }.generate();
+        let result = Builder {
+            schema,
+            options: Options::default(),
+        }
+        .generate();
         let result = result.unwrap();
 
         assert_eq!(
@@ -1016,4 +3568,1027 @@ mod tests {
             ]
         );
     }
+
+    #[rstest]
+    #[case(&[r"\EBML\EBMLVersion"], &[], &[0x4286, 0x1A45DFA3], vec![(None, vec![0xEC]), (Some(0x1A45DFA3), vec![0x4282, 0xEC])])]
+    #[case(&[], &[r"\EBML"], &[0xEC], vec![(None, vec![0x1A45DFA3])])]
+    #[case(&[r"\EBML"], &[r"\EBML\DocType"], &[0x4286, 0x1A45DFA3], vec![(None, vec![0xEC]), (Some(0x1A45DFA3), vec![0x4282, 0xEC])])]
+    fn builder_generate_filtered(
+        schema: EbmlSchema,
+        #[case] allowlist: &[&str],
+        #[case] blocklist: &[&str],
+        #[case] expt_ids: &[u32],
+        #[case] expt_skipped: Vec<(Option<u32>, Vec<u32>)>,
+    ) {
+        let mut builder = Builder {
+            schema,
+            options: Options::default(),
+        };
+        for path in allowlist {
+            builder = builder.allowlist_path(*path);
+        }
+        for path in blocklist {
+            builder = builder.blocklist_path(*path);
+        }
+        let result = builder.generate().unwrap();
+
+        assert_eq!(
+            result.elements.keys().copied().collect::<Vec<_>>(),
+            expt_ids
+        );
+        assert_eq!(
+            result
+                .skipped
+                .into_iter()
+                .filter(|(_id, skipped_ids)| !skipped_ids.is_empty())
+                .collect::<Vec<_>>(),
+            expt_skipped
+                .into_iter()
+                .map(|(id, skipped_ids)| (id, skipped_ids.into_iter().collect()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    fn builder_generate_recursive() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="matroska" version="4">
+              <element name="Tags" path="\Tags" id="0x1254C367" type="master"/>
+              <element name="SimpleTag" path="\Tags\+SimpleTag" id="0x67C8" type="master"/>
+              <element name="TagName" path="\Tags\+SimpleTag\TagName" id="0x45A3" type="utf-8"/>
+            </EBMLSchema>
+        "#;
+        let builder = || Builder::new(schema.as_bytes()).unwrap();
+
+        let result = builder().generate().unwrap();
+        assert_eq!(
+            result.parents,
+            vec![
+                (0x45A3, vec![Some(0x67C8)].into_iter().collect()),
+                (
+                    0x67C8,
+                    vec![Some(0x67C8), Some(0x1254C367)].into_iter().collect()
+                ),
+                (0x1254C367, vec![None].into_iter().collect()),
+            ]
+            .into_iter()
+            .collect()
+        );
+        assert!(result.skipped.values().all(BTreeSet::is_empty));
+
+        // nested occurrences box the state of their parent
+        let mut output = Vec::new();
+        result.write_parsers(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("SimpleTag(Box<SimpleTagState>),Tags(TagsState),"));
+        assert!(output.contains("impl From<SimpleTagState> for SimpleTagPrevStates {"));
+        assert!(output.contains("impl From<Box<SimpleTagState>> for States {"));
+
+        // dropping the parent drops the recursive element too
+        let result = builder().blocklist_path(r"\Tags").generate().unwrap();
+        assert!(result.elements.is_empty());
+
+        let parser = builder().build_dynamic().unwrap();
+        let stream = [
+            0x12, 0x54, 0xC3, 0x67, 0x8A, // Tags element, length = 10
+            0x67, 0xC8, 0x87, // SimpleTag element, length = 7
+            0x67, 0xC8, 0x84, // nested SimpleTag element, length = 4
+            0x45, 0xA3, 0x81, 0x61, // TagName element = "a"
+        ];
+        let paths = parser
+            .reader(&stream[..])
+            .map(|entry| entry.unwrap().path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                r"\Tags",
+                r"\Tags\SimpleTag",
+                r"\Tags\SimpleTag\SimpleTag",
+                r"\Tags\SimpleTag\SimpleTag\TagName",
+            ]
+        );
+    }
+
+    #[rstest]
+    fn builder_generate_extension_filter() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="matroska" version="4">
+              <element name="Void" path="\(-\)Void" id="0xEC" type="binary">
+                <extension type="webmproject.org" webm="1"/>
+              </element>
+              <element name="Segment" path="\Segment" id="0x18538067" type="master">
+                <extension type="webmproject.org" webm="1"/>
+              </element>
+              <element name="Cluster" path="\Segment\Cluster" id="0x1F43B675" type="master">
+                <extension type="libmatroska" cppname="Cluster"/>
+                <extension type="webmproject.org" webm="1"/>
+              </element>
+              <element name="SilentTracks" path="\Segment\Cluster\SilentTracks" id="0x5854" type="master">
+                <extension type="webmproject.org" webm="0"/>
+              </element>
+              <element name="SilentTrackNumber" path="\Segment\Cluster\SilentTracks\SilentTrackNumber" id="0x58D7" type="uinteger">
+                <extension type="webmproject.org" webm="1"/>
+              </element>
+              <element name="Timestamp" path="\Segment\Cluster\Timestamp" id="0xE7" type="uinteger">
+                <extension type="webmproject.org" webm="1"/>
+              </element>
+              <element name="Chapters" path="\Segment\Chapters" id="0x1043A770" type="master"/>
+            </EBMLSchema>
+        "#;
+        let is_webm = |extensions: &[Extension]| {
+            extensions.iter().any(|extension| {
+                extension.r#type == "webmproject.org"
+                    && extension.attributes.get("webm").map(String::as_str) == Some("1")
+            })
+        };
+        let result = Builder::new(schema.as_bytes())
+            .unwrap()
+            .extension_filter(is_webm)
+            .generate()
+            .unwrap();
+
+        assert_eq!(
+            result.elements.keys().copied().collect::<Vec<_>>(),
+            vec![0xE7, 0xEC, 0x18538067, 0x1F43B675]
+        );
+        // `SilentTrackNumber` is WebM, but unreachable without its `SilentTracks` parent
+        assert_eq!(
+            result
+                .skipped
+                .into_iter()
+                .filter(|(_id, skipped_ids)| !skipped_ids.is_empty())
+                .map(|(id, skipped_ids)| (id, skipped_ids.into_iter().collect()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(0x18538067), vec![0x1043A770]),
+                (Some(0x1F43B675), vec![0x5854]),
+            ]
+        );
+
+        // ...or rejected, with `reject_excluded`
+        let result = Builder::new(schema.as_bytes())
+            .unwrap()
+            .extension_filter(is_webm)
+            .reject_excluded(true)
+            .generate()
+            .unwrap();
+        assert!(result.skipped.values().all(BTreeSet::is_empty));
+        assert_eq!(
+            result.rejected[&Some(0x1F43B675)],
+            vec![0x5854].into_iter().collect()
+        );
+        let mut output = Vec::new();
+        result.write_parsers(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            "impl_next_state_navigation!(ClusterState, ClusterNextStates, [(Timestamp, TimestampState), (Void, VoidState)], [], [0x5854]);"
+        ));
+    }
+
+    #[rstest]
+    #[case(Options { module_name: "base".to_string(), ..Options::default() }, "invalid value for builder option module_name: \"base\"")]
+    #[case(Options { module_name: "my-module".to_string(), ..Options::default() }, "invalid value for builder option module_name: \"my-module\"")]
+    #[case(Options { type_prefix: "1".to_string(), ..Options::default() }, "invalid value for builder option type_prefix: \"1\"")]
+    #[case(Options { writers: true, ..Options::default() }, "invalid value for builder option writers: \"requires the owned types\"")]
+    #[case(Options { validation: true, ..Options::default() }, "invalid value for builder option validation: \"requires the owned types\"")]
+    fn builder_generate_err_options(
+        schema: EbmlSchema,
+        #[case] options: Options,
+        #[case] expt_error: &str,
+    ) {
+        let error = Builder { schema, options }.generate().err().unwrap();
+
+        assert_eq!(format!("{}", error), expt_error);
+    }
+
+    #[rstest]
+    fn write_parsers_options(schema: EbmlSchema) {
+        let builder = Builder {
+            schema,
+            options: Options::default(),
+        };
+        let parsers = builder
+            .blocklist_path(r"\EBML\DocType")
+            .type_prefix("Mkv")
+            .module_name("matroska")
+            .derive("Hash")
+            .readers(false)
+            .generate()
+            .unwrap();
+        let mut output = Vec::new();
+        parsers.write_parsers(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("use crate::matroska::element_defs;"));
+        assert!(output.contains(
+            "pub type MkvEBMLState = ElementState<element_defs::MkvEBMLDef, _DocumentState>;"
+        ));
+        assert!(output.contains(
+            "impl_next_state_navigation!(MkvEBMLState, MkvEBMLNextStates, [(MkvEBMLVersion, MkvEBMLVersionState), (MkvVoid, MkvVoidState)], [0x4282]);"
+        ));
+        assert!(!output.contains("Readers"));
+
+        let mut output = Vec::new();
+        parsers.write_element_defs(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("#[derive(Debug, Clone, PartialEq, Hash)]\npub struct MkvEBMLDef;"));
+    }
+
+    #[rstest]
+    fn write_parsers_prev_states() {
+        // `Void` is a child of every master element -> its `PrevStates`/`PrevReaders` list them all
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="parents" version="1">
+              <element name="Segment" path="\Segment" id="0x18538067" type="master"/>
+              <element name="Info" path="\Segment\Info" id="0x1549A966" type="master"/>
+              <element name="Tracks" path="\Segment\Tracks" id="0x1654AE6B" type="master"/>
+              <element name="Void" path="\(-\)Void" id="0xEC" type="binary"/>
+            </EBMLSchema>
+        "#;
+        let mut output = Vec::new();
+        Builder::new(schema.as_bytes())
+            .unwrap()
+            .generate()
+            .unwrap()
+            .write_parsers(&mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains(
+            "impl_from_substates_for_states!(VoidPrevStates, States, [Info, Segment, Tracks, _Document]);"
+        ));
+        assert!(output.contains(
+            "impl_into_reader!(VoidPrevStates, VoidPrevReaders, [Info, Segment, Tracks, _Document]);"
+        ));
+        assert!(!output.contains("Files"));
+    }
+
+    #[rstest]
+    fn write_parsers_unknown_size() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="live" version="1">
+              <element name="Segment" path="\Segment" id="0x18538067" type="master" unknownsizeallowed="1"/>
+              <element name="Info" path="\Segment\Info" id="0x1549A966" type="master"/>
+              <element name="Tags" path="\Segment\Tags" id="0x1254C367" type="master"/>
+              <element name="Cluster" path="\Segment\Cluster" id="0x1F43B675" type="master" unknownsizeallowed="1"/>
+              <element name="Timestamp" path="\Segment\Cluster\Timestamp" id="0xE7" type="uinteger"/>
+            </EBMLSchema>
+        "#;
+        let mut output = Vec::new();
+        Builder::new(schema.as_bytes())
+            .unwrap()
+            .blocklist_path(r"\Segment\Tags")
+            .generate()
+            .unwrap()
+            .write_parsers(&mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        // skipped children still belong to an unknown-sized parent
+        assert!(output.contains("0x18538067 => Ok(&[0x1254C367, 0x1549A966, 0x1F43B675]),"));
+        assert!(output.contains("0x1F43B675 => Ok(&[0xE7]),"));
+        assert!(output.contains("0xE7 | 0x1549A966 => Err(StateError::UnknownSizeNotAllowed(id)),"));
+        assert!(output.contains("id => Err(StateError::UnknownSizeSkipped(id)),"));
+        assert!(output.contains("impl BytesLeft for _DocumentState {"));
+    }
+
+    #[rstest]
+    fn write_package(schema: EbmlSchema) {
+        let path =
+            std::env::temp_dir().join(format!("iron-ebmlem-write-package-{}", std::process::id()));
+        let parsers = Builder {
+            schema,
+            options: Options::default(),
+        }
+        .module_name("mkv")
+        .rust_edition(RustEdition::Edition2021)
+        .generate()
+        .unwrap();
+        parsers.write_package(&path).unwrap();
+
+        for (filename, contents) in crate::base::TEMPLATE_BASE_FILES {
+            assert_eq!(
+                std::fs::read_to_string(path.join("src/base/").join(filename)).unwrap(),
+                *contents
+            );
+        }
+        assert_eq!(
+            std::fs::read_to_string(path.join("src/lib.rs")).unwrap(),
+            "pub mod base;\npub mod mkv;\n"
+        );
+        assert!(std::fs::read_to_string(path.join("Cargo.toml"))
+            .unwrap()
+            .contains("edition = \"2021\""));
+        for filename in &[
+            "mod.rs",
+            "codec.rs",
+            "decoder.rs",
+            "element_defs.rs",
+            "events.rs",
+            "owned.rs",
+            "parser.rs",
+            "select.rs",
+            "visitor.rs",
+        ] {
+            assert!(path.join("src/mkv/").join(filename).is_file());
+        }
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[rstest]
+    #[case("use crate::base::date::Date;\n", Some("use super::date::Date;\n"))]
+    #[case("#[macro_export]\nmacro_rules! m {}\n", Some("macro_rules! m {}\n"))]
+    // rewrites that miss part of the source
+    #[case("use crate::{base::date::Date};\n", None)]
+    #[case("#[macro_export]\r\nmacro_rules! m {}\n", None)]
+    #[case("#[cfg(test)] mod tests {}\n", None)]
+    fn inline_source(#[case] contents: &str, #[case] expt_result: Option<&str>) {
+        let result = super::inline_source(
+            "date",
+            contents,
+            &[("crate::base::", "super::"), ("#[macro_export]\n", "")],
+        );
+        assert_eq!(result.ok().as_deref(), expt_result);
+    }
+
+    #[rstest]
+    fn write_module(schema: EbmlSchema) {
+        let path =
+            std::env::temp_dir().join(format!("iron-ebmlem-write-module-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        Builder {
+            schema,
+            options: Options::default(),
+        }
+        .module_name("mkv")
+        .generate()
+        .unwrap()
+        .write_module(&path)
+        .unwrap();
+        let output = std::fs::read_to_string(path.join("ebml.rs")).unwrap();
+
+        assert!(output.contains("#[macro_use]\npub mod base {"));
+        assert!(output.contains("\npub mod mkv {"));
+        assert!(output.contains("#[macro_use]\npub mod element_defs {"));
+        assert!(output.contains("#[macro_use]\npub mod visitor {"));
+        assert!(output.contains("#[macro_use]\npub mod events {"));
+        assert!(output.contains("#[macro_use]\npub mod select {"));
+        assert!(output.contains("#[macro_use]\npub mod decoder {"));
+        assert!(output.contains("#[cfg(feature = \"codec\")]\n#[macro_use]\npub mod codec {"));
+        assert!(output.contains("use super::decoder::Decoder;"));
+        assert!(output.contains("use super::super::base::parser::{"));
+        assert!(!output.contains("crate::"));
+        assert!(!output.contains("#[macro_export]"));
+        assert!(!output.contains("#[cfg(test)]"));
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[rstest]
+    fn write_element_defs_metadata() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="matroska" version="4">
+              <element name="FlagInterlaced" path="\FlagInterlaced" id="0x9A" type="uinteger"
+                minOccurs="1" default="0">
+                <implementation_note note_attribute="default">Absent means
+                  undetermined.</implementation_note>
+                <implementation_note note_attribute="minOccurs">Always written.</implementation_note>
+                <extension type="libmatroska" cppname="VideoFlagInterlaced"/>
+                <extension type="webmproject.org" webm="1"/>
+              </element>
+            </EBMLSchema>
+        "#;
+        let mut output = Vec::new();
+        Builder::new(schema.as_bytes())
+            .unwrap()
+            .generate()
+            .unwrap()
+            .write_element_defs(&mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("    /// Always written.\n    const MIN_OCCURS: usize = 1;"));
+        assert!(output.contains(
+            "    /// Absent means\n    /// undetermined.\n    const DEFAULT: Option<u64> = "
+        ));
+        assert!(output.contains(
+            r#"const EXTENSIONS: &'static [(&'static str, &'static [(&'static str, &'static str)])] = &[("libmatroska", &[("cppname", "VideoFlagInterlaced")]), ("webmproject.org", &[("webm", "1")])];"#
+        ));
+    }
+
+    #[rstest]
+    fn write_date_element_defs() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="dates" version="1">
+              <element name="Created" path="\Created" id="0x4461" type="date"
+                default="2001-01-01T00:00:01Z" range="&gt;=0"/>
+            </EBMLSchema>
+        "#;
+        let mut output = Vec::new();
+        Builder::new(schema.as_bytes())
+            .unwrap()
+            .generate()
+            .unwrap()
+            .write_element_defs(&mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains(
+            "const RANGE: Range<Date> = Range::IsWithin(Bound::Included(Date::from_nanos(0)), Bound::Unbounded);"
+        ));
+        assert!(
+            output.contains("const DEFAULT: Option<Date> = Some(Date::from_nanos(1000000000));")
+        );
+    }
+
+    #[rstest]
+    #[case("8000.0", Some(8000.0))]
+    #[case("-1.5", Some(-1.5))]
+    #[case("0x1.f4p+12", Some(8000.0))]
+    #[case("0x0p+0", Some(0.0))]
+    #[case("-0x1p-1", Some(-0.5))]
+    #[case("0x10", Some(16.0))]
+    #[case("0xp+1", None)]
+    #[case("0x1.gp+0", None)]
+    #[case("inf", None)]
+    fn schema_float_parse(#[case] s: &str, #[case] expt_result: Option<f64>) {
+        assert_eq!(parse_schema_float(s), expt_result);
+    }
+
+    #[rstest]
+    fn write_element_defs_defaults() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="matroska" version="4">
+              <element name="TimestampScale" path="\TimestampScale" id="0x2AD7B1" type="uinteger" default="1000000"/>
+              <element name="CodecDelay" path="\CodecDelay" id="0x56AA" type="integer" default="-2"/>
+              <element name="SamplingFrequency" path="\SamplingFrequency" id="0xB5" type="float" default="0x1.f4p+12"/>
+              <element name="Language" path="\Language" id="0x22B59C" type="string" default="eng"/>
+              <element name="TagName" path="\TagName" id="0x45A3" type="utf-8" default="T&#xe4;g"/>
+            </EBMLSchema>
+        "#;
+        let mut output = Vec::new();
+        Builder::new(schema.as_bytes())
+            .unwrap()
+            .generate()
+            .unwrap()
+            .write_element_defs(&mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("const DEFAULT: Option<u64> = Some(1000000);"));
+        assert!(output.contains("const DEFAULT: Option<i64> = Some(-2);"));
+        assert!(output.contains("const DEFAULT: Option<f64> = Some(8000.0);"));
+        assert!(output.contains(r#"const DEFAULT: Option<&'static str> = Some("eng");"#));
+        assert!(output.contains(r#"const DEFAULT: Option<&'static str> = Some("Täg");"#));
+    }
+
+    #[rstest]
+    #[case(r#"type="uinteger" default="-1""#, "\"-1\"")]
+    #[case(r#"type="float" default="fast""#, "\"fast\"")]
+    #[case(r#"type="string" default="é""#, "\"é\"")]
+    fn builder_generate_default_err(#[case] attrs: &str, #[case] expt_error: &str) {
+        let schema = format!(
+            r#"<EBMLSchema xmlns="urn:ietf:rfc:8794" docType="defaults" version="1">
+              <element name="Created" path="\Created" id="0x4461" {attrs}/>
+            </EBMLSchema>"#
+        );
+        let error = Builder::new(schema.as_bytes())
+            .unwrap()
+            .generate()
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            format!("invalid default value for element Created: {expt_error}")
+        );
+    }
+
+    #[rstest]
+    #[case(
+        r#"default="yesterday""#,
+        "invalid default value for date element Created: invalid ISO 8601 timestamp: \"yesterday\""
+    )]
+    #[case(
+        r#"range="&lt;0,&lt;1""#,
+        "invalid range for date element Created: duplicate bound <1"
+    )]
+    fn builder_generate_date_err(#[case] attrs: &str, #[case] expt_error: &str) {
+        let schema = format!(
+            r#"<EBMLSchema xmlns="urn:ietf:rfc:8794" docType="dates" version="1">
+              <element name="Created" path="\Created" id="0x4461" type="date" {}/>
+            </EBMLSchema>"#,
+            attrs
+        );
+        let error = Builder::new(schema.as_bytes())
+            .unwrap()
+            .generate()
+            .err()
+            .unwrap();
+
+        assert_eq!(format!("{}", error), expt_error);
+    }
+
+    #[rstest]
+    #[case(0xEC, true)]
+    #[case(0x4282, true)]
+    #[case(0x1A45DFA3, true)]
+    #[case(0x407F, true)] // 0x7F would be all 1's, so it needs 2 bytes
+    #[case(0, false)]
+    #[case(0x80, false)] // all 0's
+    #[case(0xFF, false)] // all 1's
+    #[case(0x4000, false)]
+    #[case(0x407E, false)] // could be 0xFE
+    #[case(0x2282, false)] // marker doesn't match width
+    #[case(0x0A45DFA3, false)]
+    fn valid_element_id(#[case] id: u32, #[case] expt_result: bool) {
+        assert_eq!(is_valid_element_id(id), expt_result);
+    }
+
+    #[rstest]
+    #[case("EBML", true)]
+    #[case("CRC-32", true)]
+    #[case("3D.Stuff", true)]
+    #[case("", false)]
+    #[case("-Name", false)]
+    #[case("Doc_Type", false)]
+    #[case("Name With Spaces", false)]
+    fn valid_element_name(#[case] name: &str, #[case] expt_result: bool) {
+        assert_eq!(is_valid_element_name(name), expt_result);
+    }
+
+    #[rstest]
+    fn builder_generate_err_schema() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="bad" version="1">
+              <element name="Top" path="\Top" id="0x1A45DFA3" type="master" default="0"/>
+              <element name="Doc_Type" path="\Top\Doc_Type" id="0x4000" type="string" range="1"/>
+              <element name="Count" path="\Top\Count" id="0x4282" type="uinteger"
+                minOccurs="2" maxOccurs="1" recursive="1"/>
+              <element name="Other" path="\Top\Count" id="0x4282" type="binary"/>
+              <element name="Size" path="\Top\Size" id="0x4283" type="uinteger"
+                minOccurs="2" default="1"/>
+            </EBMLSchema>
+        "#;
+        let error = Builder::new(schema.as_bytes())
+            .unwrap()
+            .generate()
+            .err()
+            .unwrap();
+
+        match &error {
+            BuilderGenerateError::InvalidSchema(violations) => assert_eq!(
+                violations,
+                &vec![
+                    SchemaViolation::AttributeNotAllowed("Top".to_string(), "default", "master"),
+                    SchemaViolation::InvalidName("Doc_Type".to_string()),
+                    SchemaViolation::InvalidId("Doc_Type".to_string(), 0x4000),
+                    SchemaViolation::AttributeNotAllowed("Doc_Type".to_string(), "range", "string"),
+                    SchemaViolation::AttributeNotAllowed(
+                        "Count".to_string(),
+                        "recursive",
+                        "uinteger"
+                    ),
+                    SchemaViolation::MinOccursExceedsMaxOccurs("Count".to_string(), 2, 1),
+                    SchemaViolation::DuplicateId(0x4282, "Count".to_string(), "Other".to_string()),
+                    SchemaViolation::DuplicatePath("\\Top\\Count".to_string()),
+                    SchemaViolation::DefaultWithMinOccurs("Size".to_string(), 2),
+                ]
+            ),
+            _ => panic!("unexpected error: {}", error),
+        }
+        assert!(format!("{}", error).starts_with(
+            "invalid schema: attribute default not allowed for master element Top; invalid element name \"Doc_Type\"; "
+        ));
+    }
+
+    #[rstest]
+    #[case(r#"unknownsizeallowed="1""#, Some("unknownsizeallowed"))]
+    #[case(r#"recursive="1""#, Some("recursive"))]
+    // the attributes' defaults are allowed, whatever the element type
+    #[case(r#"unknownsizeallowed="0""#, None)]
+    #[case(r#"recursive="0""#, None)]
+    fn builder_generate_master_attributes(
+        #[case] attribute: &str,
+        #[case] expt_violation: Option<&'static str>,
+    ) {
+        let schema = format!(
+            r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="attributes" version="1">
+              <element name="Top" path="\Top" id="0x1A45DFA3" type="master"/>
+              <element name="Title" path="\Top\Title" id="0x4281" type="utf-8" {attribute}/>
+            </EBMLSchema>
+        "#
+        );
+        let result = Builder::new(schema.as_bytes()).unwrap().generate();
+
+        match expt_violation {
+            None => assert!(result.is_ok()),
+            Some(expt_attribute) => assert!(matches!(
+                result,
+                Err(BuilderGenerateError::InvalidSchema(violations))
+                    if violations == vec![SchemaViolation::AttributeNotAllowed(
+                        "Title".to_string(),
+                        expt_attribute,
+                        "utf-8",
+                    )]
+            )),
+        }
+    }
+
+    #[rstest]
+    #[case(SchemaStrictness::Warn)]
+    #[case(SchemaStrictness::Lenient)]
+    fn builder_generate_ignored_violations(#[case] strictness: SchemaStrictness) {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="lenient" version="1">
+              <element name="Top" path="\Top" id="0x1A45DFA3" type="master"/>
+              <element name="Hash" path="\Top\Hash" id="0x4281" type="binary" range="1-2"/>
+              <element name="Count" path="\Top\Count" id="0x4282" type="uinteger"
+                minOccurs="2" default="1"/>
+              <element name="Display" path="\Top\Display" id="0x80" type="master"/>
+            </EBMLSchema>
+        "#;
+        assert!(Builder::new(schema.as_bytes()).unwrap().generate().is_err());
+
+        let parsers = Builder::new(schema.as_bytes())
+            .unwrap()
+            .schema_strictness(strictness)
+            .generate()
+            .unwrap();
+        // the disallowed attribute is dropped, the default & the all-0's ID kept
+        assert_eq!(parsers.elements[&0x4281].range, None);
+        assert_eq!(parsers.elements[&0x4282].default.as_deref(), Some("1"));
+        assert_eq!(parsers.elements[&0x80].name, "Display");
+
+        // violations that can't be worked around still fail
+        let schema = schema.replace("0x4282", "0x4281");
+        let error = Builder::new(schema.as_bytes())
+            .unwrap()
+            .schema_strictness(strictness)
+            .generate()
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error,
+            BuilderGenerateError::InvalidSchema(violations)
+                if violations == vec![SchemaViolation::DuplicateId(
+                    0x4281,
+                    "Hash".to_string(),
+                    "Count".to_string(),
+                )]
+        ));
+    }
+
+    #[test]
+    fn builder_generate_ebml_header() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="header" version="1">
+              <element name="EBMLMaxSizeLength" path="\EBML\EBMLMaxSizeLength" id="0x42F3"
+                type="uinteger" range="1-8" default="8" minOccurs="1" maxOccurs="1"/>
+              <element name="Segment" path="\Segment" id="0x18538067" type="master"/>
+              <element name="Title" path="\Segment\Title" id="0x7BA9" type="utf-8"/>
+            </EBMLSchema>
+        "#;
+        // the schema only restricts a header element, without defining its parent
+        assert!(matches!(
+            Builder::new(schema.as_bytes()).unwrap().generate(),
+            Err(BuilderGenerateError::NoDirectParent(name)) if name == "EBMLMaxSizeLength"
+        ));
+
+        let parsers = Builder::new(schema.as_bytes())
+            .unwrap()
+            .ebml_header(true)
+            .generate()
+            .unwrap();
+        // the schema's own definitions take precedence over RFC 8794's
+        assert_eq!(parsers.elements[&0x42F3].range.as_deref(), Some("1-8"));
+        assert_eq!(parsers.parents[&0x1A45DFA3], BTreeSet::from([None]));
+        assert_eq!(parsers.parents[&0x4282], BTreeSet::from([Some(0x1A45DFA3)]));
+        assert_eq!(
+            parsers.parents[&0xBF],
+            BTreeSet::from([Some(0x1A45DFA3), Some(0x4281), Some(0x18538067)])
+        );
+        assert_eq!(
+            parsers.parents[&0xEC],
+            BTreeSet::from([None, Some(0x1A45DFA3), Some(0x4281), Some(0x18538067)])
+        );
+    }
+
+    #[rstest]
+    #[case("", "EBMLVersion", "EBMLVersion")]
+    #[case("", "CRC-32", "Crc32")]
+    #[case("", "Stereo-3D", "Stereo3D")]
+    #[case("", "Doc.type-ext", "DocTypeExt")]
+    #[case("", "3D", "Element3D")]
+    #[case("", "Self", "SelfElement")]
+    #[case("", "States", "StatesElement")]
+    #[case("", "Element", "ElementElement")]
+    #[case("", "Parent", "ParentElement")]
+    #[case("", "FloatElement", "FloatElementElement")]
+    #[case("", "Iterator", "IteratorElement")]
+    #[case("", "R", "RElement")]
+    #[case("", "Date", "DateElementElement")]
+    #[case("Mkv", "CRC-32", "MkvCrc32")]
+    #[case("Mkv", "3D", "Mkv3D")]
+    #[case("Mkv", "Element", "MkvElement")]
+    #[case("_", "Document", "_DocumentElement")]
+    fn rust_type_name(#[case] prefix: &str, #[case] elem_name: &str, #[case] expt_result: &str) {
+        assert_eq!(super::rust_type_name(prefix, elem_name), expt_result);
+    }
+
+    #[rstest]
+    #[case("EBMLVersion", "ebml_version")]
+    #[case("EBMLMaxIDLength", "ebml_max_id_length")]
+    #[case("CRC-32", "crc32")]
+    #[case("SimpleTag", "simple_tag")]
+    #[case("3D", "element3_d")]
+    #[case("Self", "self_element")]
+    #[case("File_Name", "file_name")]
+    fn rust_method_name(#[case] elem_name: &str, #[case] expt_result: &str) {
+        assert_eq!(super::rust_method_name(elem_name), expt_result);
+    }
+
+    #[rstest]
+    fn write_visitor() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="visits" version="1">
+              <element name="Files" path="\Files" id="0x1946696C" type="master"/>
+              <element name="File" path="\Files\File" id="0x6146" type="master"/>
+              <element name="FileName" path="\Files\File\FileName" id="0x614E" type="utf-8"/>
+            </EBMLSchema>
+        "#;
+        let write_visitor = |builder: Builder| {
+            let mut output = Vec::new();
+            builder
+                .type_prefix("Demo")
+                .generate()
+                .unwrap()
+                .write_visitor(&mut output)
+                .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        let output = write_visitor(Builder::new(schema.as_bytes()).unwrap());
+        assert!(output.contains("    fn visit_file_name(&mut self, _value: &str) {}"));
+        assert!(output.contains("    fn enter_files(&mut self) -> Visit {"));
+        assert!(output.contains("    fn exit_files(&mut self) {}"));
+        assert!(output.contains("parser::DemoFilesNextReaders::Parent(r_parent) => {"));
+        assert!(output.contains("            parser::Readers::DemoFileName(mut r) => {"));
+
+        // `File` loses all of its children -> it has no `NextReaders` to match on
+        let output = write_visitor(
+            Builder::new(schema.as_bytes())
+                .unwrap()
+                .blocklist_path(r"\Files\File\FileName"),
+        );
+        assert!(!output.contains("visit_file_name"));
+        assert!(!output.contains("DemoFileNextReaders"));
+        assert!(output.contains("    fn exit_file(&mut self) {}"));
+
+        let output = write_visitor(Builder::new(schema.as_bytes()).unwrap().readers(false));
+        assert!(!output.contains("Visitor"));
+    }
+
+    #[rstest]
+    fn write_events() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="events" version="1">
+              <element name="Tags" path="\Tags" id="0x1254C367" type="master"/>
+              <element name="SimpleTag" path="\Tags\+SimpleTag" id="0x67C8" type="master"/>
+              <element name="TagName" path="\Tags\+SimpleTag\TagName" id="0x45A3" type="utf-8"/>
+              <element name="TagBinary" path="\Tags\+SimpleTag\TagBinary" id="0x4485" type="binary"/>
+            </EBMLSchema>
+        "#;
+        let write_events = |builder: Builder| {
+            let mut output = Vec::new();
+            builder
+                .type_prefix("Demo")
+                .generate()
+                .unwrap()
+                .write_events(&mut output)
+                .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        let output = write_events(Builder::new(schema.as_bytes()).unwrap());
+        // nested SimpleTags are read like any other child
+        assert!(output.contains("parser::DemoTagsNextReaders::Parent(r_parent) => {"));
+        assert!(output.contains("parser::DemoSimpleTagNextReaders::Parent(r_parent) => {"));
+        assert!(
+            output.contains("element_defs::DemoSimpleTagDef::ID,\n                \"SimpleTag\",")
+        );
+        assert!(output.contains("self.value(Value::Utf8(value.to_owned()));"));
+        assert!(output.contains("self.value(Value::Binary(value.to_vec()));"));
+
+        let output = write_events(
+            Builder::new(schema.as_bytes())
+                .unwrap()
+                .blocklist_path(r"\Tags\+SimpleTag"),
+        );
+        // the header offsets come from the reader, whatever it skipped over before them
+        assert!(output.contains("r.reader.last_offset(),"));
+        assert!(!output.contains("SKIPPED_IDS"));
+        assert!(!output.contains("DemoTagsNextReaders"));
+
+        let output = write_events(Builder::new(schema.as_bytes()).unwrap().readers(false));
+        assert!(!output.contains("Events"));
+    }
+
+    #[rstest]
+    fn write_decoder() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="decoders" version="1">
+              <element name="Tags" path="\Tags" id="0x1254C367" type="master"/>
+              <element name="SimpleTag" path="\Tags\+SimpleTag" id="0x67C8" type="master"/>
+              <element name="TagName" path="\Tags\+SimpleTag\TagName" id="0x45A3" type="utf-8"/>
+              <element name="TagBinary" path="\Tags\+SimpleTag\TagBinary" id="0x4485" type="binary"/>
+            </EBMLSchema>
+        "#;
+        let write_decoder = |builder: Builder| {
+            let mut output = Vec::new();
+            builder
+                .type_prefix("Demo")
+                .generate()
+                .unwrap()
+                .write_decoder(&mut output)
+                .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        let output = write_decoder(Builder::new(schema.as_bytes()).unwrap());
+        // nested SimpleTags are decoded like any other child
+        assert!(output.contains("const SKIPPED_IDS: &[u32] = &[];"));
+        assert!(output.contains("(next_stream, parser::DemoTagsNextStates::Parent(parent)) => {"));
+        assert!(output.contains(
+            "parser::States::DemoTagName(s) => (element_defs::DemoTagNameDef::ID, \"TagName\","
+        ));
+        assert!(output.contains("self.value(Value::Utf8(value.to_owned()));"));
+        assert!(output.contains("self.value(Value::Binary(value.to_vec()));"));
+        assert!(output.contains(
+            "parser::States::DemoSimpleTag(s) => s.end_of_stream().ok().map(Into::into),"
+        ));
+
+        // the decoder only needs the states, not the readers
+        let output = write_decoder(
+            Builder::new(schema.as_bytes())
+                .unwrap()
+                .blocklist_path(r"\Tags\+SimpleTag")
+                .readers(false),
+        );
+        assert!(output.contains("pub struct Decoder {"));
+        assert!(output.contains("const SKIPPED_IDS: &[u32] = &[0x67C8];"));
+        assert!(!output.contains("DemoTagsNextStates"));
+    }
+
+    #[rstest]
+    fn write_select() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="selects" version="1">
+              <element name="Files" path="\Files" id="0x1946696C" type="master"/>
+              <element name="File" path="\Files\File" id="0x6146" type="master"/>
+              <element name="FileName" path="\Files\File\FileName" id="0x614E" type="utf-8"/>
+              <element name="Void" path="\(-\)Void" id="0xEC" type="binary"/>
+            </EBMLSchema>
+        "#;
+        let write_select = |builder: Builder| {
+            let mut output = Vec::new();
+            builder
+                .type_prefix("Demo")
+                .generate()
+                .unwrap()
+                .write_select(&mut output)
+                .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        let output = write_select(Builder::new(schema.as_bytes()).unwrap());
+        assert!(output.contains("!path_contains(element_defs::DemoFilesDef::PATH, self.path)"));
+        assert!(output.contains("parser::DemoFileNextReaders::Parent(r_parent) => {"));
+        assert!(output.contains("let value = if element_defs::DemoVoidDef::PATH == self.path {"));
+        assert!(output.contains("Some(Value::Utf8(value.to_owned()))"));
+
+        // `File` loses all of its children -> it has no `NextReaders` to match on
+        let output = write_select(
+            Builder::new(schema.as_bytes())
+                .unwrap()
+                .blocklist_path(r"\Files\File\FileName")
+                .blocklist_path(r"\(-\)Void"),
+        );
+        assert!(output.contains("parser::Readers::DemoFile(r) => (r.next()?.into(), false, None),"));
+        assert!(!output.contains("DemoFileNextReaders"));
+
+        let output = write_select(Builder::new(schema.as_bytes()).unwrap().readers(false));
+        assert!(!output.contains("Select"));
+    }
+
+    #[rstest]
+    fn write_owned() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="owned" version="1">
+              <element name="Tags" path="\Tags" id="0x1254C367" type="master" maxOccurs="1"/>
+              <element name="SimpleTag" path="\Tags\+SimpleTag" id="0x67C8" type="master" recursive="1"/>
+              <element name="Target" path="\Tags\Target" id="0x63C0" type="master" maxOccurs="1"/>
+              <element name="Type" path="\Tags\Target\Type" id="0x63CA" type="string" maxOccurs="1"/>
+              <element name="Level" path="\Tags\Target\Level" id="0x68CA" type="uinteger" maxOccurs="1" default="50"/>
+              <element name="Lang" path="\Tags\+SimpleTag\Lang" id="0x447A" type="utf-8"/>
+            </EBMLSchema>
+        "#;
+        let write_owned = |builder: Builder| {
+            let mut output = Vec::new();
+            builder
+                .type_prefix("Demo")
+                .derive("Hash")
+                .generate()
+                .unwrap()
+                .write_owned(&mut output)
+                .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        let output = write_owned(Builder::new(schema.as_bytes()).unwrap().owned(true));
+        assert!(output
+            .contains("#[derive(Debug, Clone, PartialEq, Hash, Default)]\npub struct DemoTags {"));
+        assert!(output.contains("pub target: Option<DemoTarget>, // Target"));
+        assert!(output.contains("pub simple_tag: Vec<DemoSimpleTag>, // SimpleTag"));
+        assert!(output.contains("pub type_: Option<String>, // Type"));
+        assert!(output.contains(
+            "events::Event::Start { id: element_defs::DemoLevelDef::ID, .. } => base::set_once(&mut owned.level, base::read_value(iter, element_defs::DemoLevelDef::ID)?, element_defs::DemoLevelDef::ID)?,"
+        ));
+        assert!(output.contains("owned.simple_tag.push(DemoSimpleTag::from_events(iter)?)"));
+        // nested SimpleTags are owned by their parent
+        assert!(output.contains(
+            "pub struct DemoSimpleTag {\n    pub lang: Vec<String>, // Lang\n    pub simple_tag: Vec<DemoSimpleTag>, // SimpleTag\n}"
+        ));
+        assert!(!output.contains("fn write_data"));
+        assert!(!output.contains("fn validate"));
+
+        let output = write_owned(
+            Builder::new(schema.as_bytes())
+                .unwrap()
+                .owned(true)
+                .writers(true)
+                .validation(true),
+        );
+        assert!(output.contains(
+            "base::write_element(buffer, element_defs::DemoTypeDef::ID, base::ascii_data(value))?;"
+        ));
+        assert!(output.contains(
+            "base::check_occurrences::<element_defs::DemoLevelDef>(usize::from(self.level.is_some()), true)?;"
+        ));
+        assert!(output.contains(
+            "base::check_range(&element_defs::DemoLevelDef::RANGE, value, element_defs::DemoLevelDef::ID)?;"
+        ));
+
+        let output = write_owned(Builder::new(schema.as_bytes()).unwrap());
+        assert!(!output.contains("struct"));
+    }
+
+    #[rstest]
+    fn write_element_defs_sanitized_names() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="names" version="1">
+              <element name="Top" path="\Top" id="0x1A45DFA3" type="master"/>
+              <element name="CRC-32" path="\(1-\)CRC-32" id="0xBF" type="binary"/>
+              <element name="Self" path="\Top\Self" id="0x4282" type="uinteger"/>
+            </EBMLSchema>
+        "#;
+        let parsers = Builder::new(schema.as_bytes()).unwrap().generate().unwrap();
+        let mut output = Vec::new();
+        parsers.write_element_defs(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("pub struct Crc32Def;"));
+        assert!(output.contains("pub struct SelfElementDef;"));
+
+        let mut output = Vec::new();
+        parsers.write_parsers(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("pub type Crc32State = "));
+        assert!(output.contains("SelfElement(SelfElementState),"));
+    }
+
+    #[rstest]
+    fn builder_generate_err_type_name_collision() {
+        let schema = r#"
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="names" version="1">
+              <element name="CRC-32" path="\CRC-32" id="0xBF" type="binary"/>
+              <element name="Crc32" path="\Crc32" id="0xC0" type="binary"/>
+            </EBMLSchema>
+        "#;
+        let error = Builder::new(schema.as_bytes())
+            .unwrap()
+            .generate()
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            format!("{}", error),
+            "elements CRC-32 and Crc32 both map to the Rust type name Crc32"
+        );
+    }
 }
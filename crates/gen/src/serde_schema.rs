@@ -1,5 +1,11 @@
 use serde_derive::{Deserialize, Serialize};
-pub(crate) use serde_xml_rs::{from_reader, from_str, to_string};
+pub(crate) use serde_xml_rs::from_reader;
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+/// The XML namespace of EBML schemas.
+pub const NAMESPACE: &str = "urn:ietf:rfc:8794";
 
 // documentation, element, enum, extension, implementation_note, restriction, EBMLSchema
 
@@ -34,40 +40,40 @@ pub(crate) mod custom_serde {
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename = "EBMLSchema")]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct EbmlSchema {
-    pub(crate) doc_type: String,
-    pub(crate) version: u32,
-    pub(crate) ebml: Option<u32>,
+pub struct EbmlSchema {
+    pub doc_type: String,
+    pub version: u32,
+    pub ebml: Option<u32>,
     #[serde(rename = "$value")]
-    pub(crate) elements: Option<Vec<Element>>,
+    pub elements: Option<Vec<Element>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct Element {
-    pub(crate) name: String,
-    pub(crate) path: String,
+pub struct Element {
+    pub name: String,
+    pub path: String,
     #[serde(with = "custom_serde::hexadecimal")]
-    pub(crate) id: u32,
-    pub(crate) min_occurs: Option<usize>,
-    pub(crate) max_occurs: Option<usize>,
-    pub(crate) range: Option<String>, // numeric elements only
-    pub(crate) length: Option<String>,
-    pub(crate) default: Option<String>, // non-master elements only
-    pub(crate) r#type: ElementType,
-    pub(crate) unknownsizeallowed: Option<bool>, // master elements only
-    pub(crate) recursive: Option<bool>,          // master elements only
-    pub(crate) recurring: Option<bool>,
-    pub(crate) minver: Option<u32>,
-    pub(crate) maxver: Option<u32>,
+    pub id: u32,
+    pub min_occurs: Option<usize>,
+    pub max_occurs: Option<usize>,
+    pub range: Option<String>, // numeric elements only
+    pub length: Option<String>,
+    pub default: Option<String>, // non-master elements only
+    pub r#type: ElementType,
+    pub unknownsizeallowed: Option<bool>, // master elements only
+    pub recursive: Option<bool>,          // master elements only
+    pub recurring: Option<bool>,
+    pub minver: Option<u32>,
+    pub maxver: Option<u32>,
 
     #[serde(rename = "$value")]
-    pub(crate) metadata: Option<Vec<ElementValue>>,
+    pub metadata: Option<Vec<ElementValue>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename = "type")]
-pub(crate) enum ElementType {
+pub enum ElementType {
     #[serde(rename = "integer")]
     SignedInteger,
     #[serde(rename = "uinteger")]
@@ -86,9 +92,25 @@ pub(crate) enum ElementType {
     Binary,
 }
 
+impl ElementType {
+    /// The type's name in a schema.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SignedInteger => "integer",
+            Self::UnsignedInteger => "uinteger",
+            Self::Float => "float",
+            Self::String => "string",
+            Self::Date => "date",
+            Self::Utf8 => "utf-8",
+            Self::Master => "master",
+            Self::Binary => "binary",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "snake_case")]
-pub(crate) enum ElementValue {
+pub enum ElementValue {
     Documentation(Documentation),
     Extension(Extension),
     ImplementationNote(ImplementationNote),
@@ -96,16 +118,16 @@ pub(crate) enum ElementValue {
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub(crate) struct Documentation {
-    pub(crate) lang: Option<String>,
-    pub(crate) purpose: DocumentationPurpose,
-    #[serde(rename = "$value")]
-    pub(crate) value: String,
+pub struct Documentation {
+    pub lang: Option<String>,
+    pub purpose: DocumentationPurpose,
+    #[serde(rename = "$value", default)]
+    pub value: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename = "purpose")]
-pub(crate) enum DocumentationPurpose {
+pub enum DocumentationPurpose {
     #[serde(rename = "definition")]
     Definition,
     #[serde(rename = "rationale")]
@@ -116,37 +138,224 @@ pub(crate) enum DocumentationPurpose {
     References,
 }
 
+impl DocumentationPurpose {
+    /// The purpose's name in a schema.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Definition => "definition",
+            Self::Rationale => "rationale",
+            Self::UsageNotes => "usage notes",
+            Self::References => "references",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub(crate) struct ImplementationNote {
-    pub(crate) note_attribute: String,
+pub struct ImplementationNote {
+    pub note_attribute: String,
+    #[serde(rename = "$value", default)]
+    pub value: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub(crate) struct Restriction {
+pub struct Restriction {
     #[serde(rename = "$value")]
-    pub(crate) enums: Vec<Enum>,
+    pub enums: Vec<Enum>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub(crate) struct Enum {
-    pub(crate) label: String,
-    pub(crate) value: u32,
+pub struct Enum {
+    pub label: Option<String>,
+    pub value: String, // formatted as for the element's `default`
     #[serde(rename = "$value")]
-    pub(crate) docs: Option<Vec<Documentation>>,
+    pub docs: Option<Vec<Documentation>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub(crate) struct Extension {
-    pub(crate) r#type: String,
-    pub(crate) webm: Option<bool>,
-    pub(crate) keep: Option<bool>,
-    pub(crate) cppname: Option<String>,
+pub struct Extension {
+    pub r#type: String,
+    // extensions may define any other attributes, e.g. `webm="1"` or `cppname="..."`
+    #[serde(flatten)]
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl EbmlSchema {
+    /// Reads a schema from its XML form.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, serde_xml_rs::Error> {
+        from_reader(reader)
+    }
+
+    /**
+    Writes the schema as an XML document in the `urn:ietf:rfc:8794` namespace.
+
+    The output is normalized: attributes are written in a fixed order, booleans as `0`/`1`, IDs in
+    uppercase hexadecimal, and nested tags are indented by two spaces. Reading the output back
+    yields an equal schema.
+    **/
+    pub fn write_xml<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        let attributes = [
+            ("xmlns", Some(NAMESPACE.to_string())),
+            ("docType", Some(self.doc_type.clone())),
+            ("version", Some(self.version.to_string())),
+            ("ebml", self.ebml.map(|ebml| ebml.to_string())),
+        ];
+        let elements = self.elements.as_deref().unwrap_or_default();
+        write_start_tag(
+            &mut writer,
+            0,
+            "EBMLSchema",
+            &attributes,
+            elements.is_empty(),
+        )?;
+        if !elements.is_empty() {
+            for element in elements {
+                element.write_xml(&mut writer)?;
+            }
+            writeln!(writer, "</EBMLSchema>")?;
+        }
+        Ok(())
+    }
+}
+
+impl Element {
+    fn write_xml<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let flag = |b: Option<bool>| b.map(|b| if b { "1" } else { "0" }.to_string());
+        let attributes = [
+            ("name", Some(self.name.clone())),
+            ("path", Some(self.path.clone())),
+            ("id", Some(format!("{:#X}", self.id))),
+            ("type", Some(self.r#type.as_str().to_string())),
+            ("minOccurs", self.min_occurs.map(|x| x.to_string())),
+            ("maxOccurs", self.max_occurs.map(|x| x.to_string())),
+            ("range", self.range.clone()),
+            ("length", self.length.clone()),
+            ("default", self.default.clone()),
+            ("unknownsizeallowed", flag(self.unknownsizeallowed)),
+            ("recursive", flag(self.recursive)),
+            ("recurring", flag(self.recurring)),
+            ("minver", self.minver.map(|x| x.to_string())),
+            ("maxver", self.maxver.map(|x| x.to_string())),
+        ];
+        let metadata = self.metadata.as_deref().unwrap_or_default();
+        write_start_tag(writer, 1, "element", &attributes, metadata.is_empty())?;
+        if metadata.is_empty() {
+            return Ok(());
+        }
+
+        for value in metadata {
+            match value {
+                ElementValue::Documentation(docs) => docs.write_xml(writer, 2)?,
+                ElementValue::ImplementationNote(note) => write_text_tag(
+                    writer,
+                    2,
+                    "implementation_note",
+                    &[("note_attribute", Some(note.note_attribute.clone()))],
+                    &note.value,
+                )?,
+                ElementValue::Restriction(restriction) => {
+                    write_start_tag(writer, 2, "restriction", &[], restriction.enums.is_empty())?;
+                    if restriction.enums.is_empty() {
+                        continue;
+                    }
+                    for enum_ in restriction.enums.iter() {
+                        let attributes = [
+                            ("label", enum_.label.clone()),
+                            ("value", Some(enum_.value.clone())),
+                        ];
+                        let docs = enum_.docs.as_deref().unwrap_or_default();
+                        write_start_tag(writer, 3, "enum", &attributes, docs.is_empty())?;
+                        if !docs.is_empty() {
+                            for doc in docs {
+                                doc.write_xml(writer, 4)?;
+                            }
+                            writeln!(writer, "      </enum>")?;
+                        }
+                    }
+                    writeln!(writer, "    </restriction>")?;
+                }
+                ElementValue::Extension(extension) => {
+                    let attributes = std::iter::once(("type", Some(extension.r#type.clone())))
+                        .chain(
+                            extension
+                                .attributes
+                                .iter()
+                                .map(|(name, value)| (name.as_str(), Some(value.clone()))),
+                        )
+                        .collect::<Vec<_>>();
+                    write_start_tag(writer, 2, "extension", &attributes, true)?;
+                }
+            }
+        }
+        writeln!(writer, "  </element>")
+    }
+}
+
+impl Documentation {
+    fn write_xml<W: Write>(&self, writer: &mut W, depth: usize) -> std::io::Result<()> {
+        let attributes = [
+            ("lang", self.lang.clone()),
+            ("purpose", Some(self.purpose.as_str().to_string())),
+        ];
+        write_text_tag(writer, depth, "documentation", &attributes, &self.value)
+    }
+}
+
+// Writes an opening tag with the given (present) attributes, or a self-closing tag if `is_empty`
+fn write_start_tag<W: Write>(
+    writer: &mut W,
+    depth: usize,
+    name: &str,
+    attributes: &[(&str, Option<String>)],
+    is_empty: bool,
+) -> std::io::Result<()> {
+    write_tag_head(writer, depth, name, attributes)?;
+    writeln!(writer, "{}", if is_empty { "/>" } else { ">" })
+}
+
+// Writes a tag containing only text, on a single line
+fn write_text_tag<W: Write>(
+    writer: &mut W,
+    depth: usize,
+    name: &str,
+    attributes: &[(&str, Option<String>)],
+    text: &str,
+) -> std::io::Result<()> {
+    write_tag_head(writer, depth, name, attributes)?;
+    writeln!(writer, ">{}</{}>", xml_escape(text), name)
+}
+
+fn write_tag_head<W: Write>(
+    writer: &mut W,
+    depth: usize,
+    name: &str,
+    attributes: &[(&str, Option<String>)],
+) -> std::io::Result<()> {
+    write!(writer, "{}<{}", "  ".repeat(depth), name)?;
+    for (attr_name, value) in attributes {
+        if let Some(value) = value {
+            write!(
+                writer,
+                r#" {}="{}""#,
+                attr_name,
+                xml_escape(value).replace('"', "&quot;")
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use rstest::*;
+    use serde_xml_rs::from_str;
 
     #[rstest]
     #[case(
@@ -204,4 +413,68 @@ mod tests {
         let result: ElementType = from_str(source).unwrap();
         assert_eq!(result, expt_result);
     }
+
+    const SCHEMA_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<EBMLSchema xmlns="urn:ietf:rfc:8794" docType="matroska" version="4" ebml="1">
+  <element name="Void" path="\(-\)Void" id="0xEC" type="binary"/>
+  <element name="Segment" path="\Segment" id="0x18538067" type="master" minOccurs="1" maxOccurs="1" unknownsizeallowed="1">
+    <documentation lang="en" purpose="definition">The Root Element &amp; its "children".</documentation>
+    <extension type="libmatroska" cppname="Segment" webm="1"/>
+  </element>
+  <element name="FlagInterlaced" path="\Segment\FlagInterlaced" id="0x9A" type="uinteger" minOccurs="1" maxOccurs="1" range="0-2" default="0" minver="2">
+    <documentation purpose="usage notes">Whether the video is interlaced.</documentation>
+    <implementation_note note_attribute="default">Absent means undetermined.</implementation_note>
+    <restriction>
+      <enum label="undetermined" value="0"/>
+      <enum label="interlaced" value="1">
+        <documentation lang="en" purpose="definition">Interlaced frames.</documentation>
+      </enum>
+      <enum value="2"/>
+    </restriction>
+    <extension type="webmproject.org" webm="1"/>
+  </element>
+</EBMLSchema>
+"#;
+
+    #[rstest]
+    fn write_xml() {
+        let schema = EbmlSchema::from_reader(SCHEMA_XML.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        schema.write_xml(&mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), SCHEMA_XML);
+    }
+
+    #[rstest]
+    fn write_xml_roundtrip() {
+        let source = include_str!("../../example_generated/eg_schema.xml");
+        let schema = EbmlSchema::from_reader(source.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        schema.write_xml(&mut output).unwrap();
+
+        assert_eq!(EbmlSchema::from_reader(output.as_slice()).unwrap(), schema);
+    }
+
+    #[rstest]
+    fn read_metadata() {
+        let schema = EbmlSchema::from_reader(SCHEMA_XML.as_bytes()).unwrap();
+        let metadata = schema.elements.unwrap()[2].metadata.clone().unwrap();
+
+        assert_eq!(
+            metadata[1],
+            ElementValue::ImplementationNote(ImplementationNote {
+                note_attribute: "default".to_string(),
+                value: "Absent means undetermined.".to_string(),
+            })
+        );
+        assert_eq!(
+            metadata[3],
+            ElementValue::Extension(Extension {
+                r#type: "webmproject.org".to_string(),
+                attributes: vec![("webm".to_string(), "1".to_string())]
+                    .into_iter()
+                    .collect(),
+            })
+        );
+    }
 }
@@ -0,0 +1,416 @@
+use crate::serde_schema::{from_reader, EbmlSchema, NAMESPACE};
+
+use std::io::Read;
+
+use xml::common::Position;
+use xml::reader::{EventReader, XmlEvent};
+
+const XHTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+
+#[derive(thiserror::Error, Debug)]
+pub enum StrictSchemaError {
+    #[error("IOError: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("XMLError: {0}")]
+    Xml(#[from] xml::reader::Error),
+    #[error("schema doesn't conform to RFC 8794: {}", list_deviations(.0))]
+    Nonconforming(Vec<XmlDeviation>),
+    #[error("invalid schema: {0}")]
+    Deserialize(serde_xml_rs::Error),
+}
+
+fn list_deviations(deviations: &[XmlDeviation]) -> String {
+    itertools::intersperse(deviations.iter().map(ToString::to_string), "; ".to_string()).collect()
+}
+
+/// A deviation of a schema file from the RFC 8794 "XML Schema for EBML Schema", located by its
+/// (1-based) line & column.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[error("{line}:{column}: {kind}")]
+pub struct XmlDeviation {
+    pub line: u64,
+    pub column: u64,
+    pub kind: XmlDeviationKind,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum XmlDeviationKind {
+    #[error("tag <{0}> not in the urn:ietf:rfc:8794 namespace (found {1:?})")]
+    WrongNamespace(String, Option<String>),
+    #[error("unexpected tag <{1}> in <{0}>")]
+    UnexpectedTag(String, String),
+    #[error("tag <{1}> out of order or repeated in <{0}>")]
+    MisplacedTag(String, String),
+    #[error("unknown attribute {1} in <{0}>")]
+    UnknownAttribute(String, String),
+    #[error("missing attribute {1} in <{0}>")]
+    MissingAttribute(String, &'static str),
+    #[error("invalid value {2:?} for attribute {1} in <{0}>")]
+    InvalidAttribute(String, String, String),
+    #[error("unexpected text in <{0}>")]
+    UnexpectedText(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tag {
+    Root, // i.e., the document itself
+    Schema,
+    // the index of the last child tag in the element's sequence:
+    // documentation* -> implementation_note* -> restriction? -> extension*
+    Element(usize),
+    Documentation,
+    ImplementationNote,
+    Restriction,
+    Enum,
+    Extension,
+    Ignored, // XHTML within documentation, contents of extensions, or unexpected tags
+}
+
+const ELEMENT_SEQUENCE: [&str; 4] = [
+    "documentation",
+    "implementation_note",
+    "restriction",
+    "extension",
+];
+
+enum AttributeType {
+    Text,
+    PositiveInt,
+    NonNegativeInt,
+    Boolean,
+    HexId,
+    OneOf(&'static [&'static str]),
+}
+
+impl AttributeType {
+    fn accepts(&self, value: &str) -> bool {
+        match self {
+            Self::Text => true,
+            Self::PositiveInt => value.parse::<u64>().is_ok_and(|x| x > 0),
+            Self::NonNegativeInt => value.parse::<u64>().is_ok(),
+            Self::Boolean => ["true", "false", "1", "0"].contains(&value),
+            Self::HexId => value
+                .strip_prefix("0x")
+                .is_some_and(|hex| !hex.is_empty() && u32::from_str_radix(hex, 16).is_ok()),
+            Self::OneOf(values) => values.contains(&value),
+        }
+    }
+}
+
+// (name, is required, type)
+type AttributeSpec = (&'static str, bool, AttributeType);
+
+impl Tag {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Root => "(document)",
+            Self::Schema => "EBMLSchema",
+            Self::Element(_) => "element",
+            Self::Documentation => "documentation",
+            Self::ImplementationNote => "implementation_note",
+            Self::Restriction => "restriction",
+            Self::Enum => "enum",
+            Self::Extension => "extension",
+            Self::Ignored => "",
+        }
+    }
+
+    fn attributes(&self) -> &'static [AttributeSpec] {
+        use AttributeType::*;
+
+        match self {
+            Self::Schema => &[
+                ("docType", true, Text),
+                ("version", true, PositiveInt),
+                ("ebml", false, PositiveInt),
+            ],
+            Self::Element(_) => &[
+                ("name", true, Text),
+                ("path", true, Text),
+                ("id", true, HexId),
+                (
+                    "type",
+                    true,
+                    OneOf(&[
+                        "integer", "uinteger", "float", "string", "date", "utf-8", "master",
+                        "binary",
+                    ]),
+                ),
+                ("minOccurs", false, NonNegativeInt),
+                ("maxOccurs", false, NonNegativeInt),
+                ("range", false, Text),
+                ("length", false, Text),
+                ("default", false, Text),
+                ("unknownsizeallowed", false, Boolean),
+                ("recursive", false, Boolean),
+                ("recurring", false, Boolean),
+                ("minver", false, NonNegativeInt),
+                ("maxver", false, NonNegativeInt),
+            ],
+            Self::Documentation => &[
+                ("lang", false, Text),
+                (
+                    "purpose",
+                    true,
+                    OneOf(&["definition", "rationale", "usage notes", "references"]),
+                ),
+            ],
+            Self::ImplementationNote => &[(
+                "note_attribute",
+                true,
+                OneOf(&[
+                    "minOccurs",
+                    "maxOccurs",
+                    "range",
+                    "length",
+                    "default",
+                    "minver",
+                    "maxver",
+                ]),
+            )],
+            Self::Enum => &[("label", false, Text), ("value", true, Text)],
+            Self::Extension => &[("type", true, Text)],
+            Self::Root | Self::Restriction | Self::Ignored => &[],
+        }
+    }
+
+    fn allows_text(&self) -> bool {
+        matches!(
+            self,
+            Self::Documentation | Self::ImplementationNote | Self::Extension | Self::Ignored
+        )
+    }
+
+    // The tag for a child named `name`, reporting any deviation it constitutes
+    fn child(&mut self, name: &str, report: &mut impl FnMut(XmlDeviationKind)) -> Tag {
+        let parent_name = self.name().to_string();
+        let child = match (*self, name) {
+            (Self::Root, "EBMLSchema") => Self::Schema,
+            (Self::Schema, "element") => Self::Element(0),
+            (Self::Element(last_index), _) => {
+                match ELEMENT_SEQUENCE.iter().position(|&c| c == name) {
+                    Some(index) => {
+                        // the restriction tag (only) can't repeat
+                        if index < last_index || (index == last_index && name == "restriction") {
+                            report(XmlDeviationKind::MisplacedTag(
+                                parent_name.clone(),
+                                name.to_string(),
+                            ));
+                        }
+                        *self = Self::Element(index.max(last_index));
+                        match name {
+                            "documentation" => Self::Documentation,
+                            "implementation_note" => Self::ImplementationNote,
+                            "restriction" => Self::Restriction,
+                            _ => Self::Extension,
+                        }
+                    }
+                    None => Self::Ignored,
+                }
+            }
+            (Self::Restriction, "enum") => Self::Enum,
+            (Self::Enum, "documentation") => Self::Documentation,
+            _ => Self::Ignored,
+        };
+        if child == Self::Ignored {
+            report(XmlDeviationKind::UnexpectedTag(
+                parent_name,
+                name.to_string(),
+            ));
+        }
+        child
+    }
+}
+
+/**
+Checks a schema file against the structure defined by the RFC 8794 "XML Schema for EBML Schema",
+returning every deviation found (e.g., a missing or wrong namespace, unknown or misspelled
+attributes, misplaced tags, or stray text). Errors are returned only for malformed XML.
+
+**/
+pub fn check_schema_xml<R: Read>(reader: R) -> Result<Vec<XmlDeviation>, xml::reader::Error> {
+    let mut parser = EventReader::new(reader);
+    let mut deviations = Vec::new();
+    let mut stack = vec![Tag::Root];
+
+    loop {
+        let event = parser.next()?;
+        let position = parser.position();
+        let mut report = |kind| {
+            deviations.push(XmlDeviation {
+                line: position.row + 1,
+                column: position.column + 1,
+                kind,
+            })
+        };
+
+        match event {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                let parent = stack.last_mut().expect("the root is never popped");
+                if *parent == Tag::Ignored || *parent == Tag::Extension {
+                    stack.push(Tag::Ignored);
+                    continue;
+                }
+                if *parent == Tag::Documentation
+                    && name.namespace.as_deref() == Some(XHTML_NAMESPACE)
+                {
+                    stack.push(Tag::Ignored);
+                    continue;
+                }
+                if name.namespace.as_deref() != Some(NAMESPACE) {
+                    report(XmlDeviationKind::WrongNamespace(
+                        name.local_name.clone(),
+                        name.namespace.clone(),
+                    ));
+                }
+
+                let tag = parent.child(&name.local_name, &mut report);
+                if tag == Tag::Ignored {
+                    stack.push(tag);
+                    continue;
+                }
+                let specs = tag.attributes();
+                for attribute in attributes.iter() {
+                    let spec = specs
+                        .iter()
+                        .find(|(spec_name, _, _)| *spec_name == attribute.name.local_name)
+                        .filter(|_| attribute.name.namespace.is_none());
+                    match spec {
+                        Some((_, _, attr_type)) if !attr_type.accepts(&attribute.value) => {
+                            report(XmlDeviationKind::InvalidAttribute(
+                                tag.name().to_string(),
+                                attribute.name.local_name.clone(),
+                                attribute.value.clone(),
+                            ))
+                        }
+                        Some(_) => {}
+                        // extensions may have arbitrary attributes
+                        None if tag == Tag::Extension => {}
+                        None => report(XmlDeviationKind::UnknownAttribute(
+                            tag.name().to_string(),
+                            attribute.name.to_string(),
+                        )),
+                    }
+                }
+                for (spec_name, _, _) in specs.iter().filter(|(_, is_required, _)| *is_required) {
+                    if !attributes
+                        .iter()
+                        .any(|attribute| attribute.name.local_name == *spec_name)
+                    {
+                        report(XmlDeviationKind::MissingAttribute(
+                            tag.name().to_string(),
+                            spec_name,
+                        ));
+                    }
+                }
+
+                stack.push(tag);
+            }
+            XmlEvent::EndElement { .. } => {
+                stack.pop();
+            }
+            XmlEvent::Characters(_) | XmlEvent::CData(_) => {
+                let tag = stack.last().expect("the root is never popped");
+                if !tag.allows_text() {
+                    report(XmlDeviationKind::UnexpectedText(tag.name().to_string()));
+                }
+            }
+            XmlEvent::EndDocument => break,
+            _ => {}
+        }
+    }
+
+    Ok(deviations)
+}
+
+impl EbmlSchema {
+    /// Reads a schema from its XML form, rejecting any deviations from the RFC 8794 schema file
+    /// structure (see `check_schema_xml`).
+    pub fn from_reader_strict<R: Read>(mut reader: R) -> Result<Self, StrictSchemaError> {
+        let mut source = Vec::new();
+        reader.read_to_end(&mut source)?;
+
+        let deviations = check_schema_xml(source.as_slice())?;
+        if !deviations.is_empty() {
+            return Err(StrictSchemaError::Nonconforming(deviations));
+        }
+        from_reader(source.as_slice()).map_err(StrictSchemaError::Deserialize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn check_conforming_schema() {
+        let source = include_str!("../../example_generated/eg_schema.xml");
+
+        assert_eq!(check_schema_xml(source.as_bytes()).unwrap(), vec![]);
+    }
+
+    #[rstest]
+    fn check_nonconforming_schema() {
+        let source = r#"<?xml version="1.0" encoding="utf-8"?>
+<EBMLSchema xmlns="urn:ietf:rfc:8794" docType="demo" version="0">
+  <element name="Files" path="\Files" id="0x1946696C" type="master" minoccurs="1"/>
+  <element name="File" path="\Files\File" id="6146" type="master" recursive="yes">
+    <restriction><enum value="1"/></restriction>
+    <documentation purpose="definition">A <b>file</b>.</documentation>
+    <extension type="libmatroska" cppname="File"><anything/></extension>
+    <note>stray</note>
+  </element>
+  <element path="\Files\File\Name" id="0x614E" type="utf-8">text</element>
+  <x:element xmlns:x="urn:other" name="Size" path="\Files\File\Size" id="0x4653" type="uinteger"/>
+</EBMLSchema>"#;
+        let deviations = check_schema_xml(source.as_bytes())
+            .unwrap()
+            .into_iter()
+            .map(|deviation| deviation.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            deviations,
+            vec![
+                "2:1: invalid value \"0\" for attribute version in <EBMLSchema>",
+                "3:3: unknown attribute minoccurs in <element>",
+                "4:3: invalid value \"6146\" for attribute id in <element>",
+                "4:3: invalid value \"yes\" for attribute recursive in <element>",
+                "6:5: tag <documentation> out of order or repeated in <element>",
+                "6:43: unexpected tag <b> in <documentation>",
+                "8:5: unexpected tag <note> in <element>",
+                "10:3: missing attribute name in <element>",
+                "10:61: unexpected text in <element>",
+                "11:3: tag <element> not in the urn:ietf:rfc:8794 namespace (found Some(\"urn:other\"))",
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(r#"<EBMLSchema docType="demo" version="1"/>"#, "schema doesn't conform to RFC 8794: 2:1: tag <EBMLSchema> not in the urn:ietf:rfc:8794 namespace (found None)")]
+    #[case(
+        r#"<EBMLSchema xmlns="urn:ietf:rfc:8794" version="1"/>"#,
+        "schema doesn't conform to RFC 8794: 2:1: missing attribute docType in <EBMLSchema>"
+    )]
+    fn from_reader_strict_err(#[case] source: &str, #[case] expt_error: &str) {
+        let source = format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}", source);
+        let error = EbmlSchema::from_reader_strict(source.as_bytes())
+            .err()
+            .unwrap();
+
+        assert_eq!(format!("{}", error), expt_error);
+    }
+
+    #[rstest]
+    fn from_reader_strict() {
+        let source = include_str!("../../example_generated/eg_schema.xml");
+
+        assert_eq!(
+            EbmlSchema::from_reader_strict(source.as_bytes()).unwrap(),
+            EbmlSchema::from_reader(source.as_bytes()).unwrap()
+        );
+    }
+}
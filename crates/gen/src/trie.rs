@@ -36,7 +36,7 @@ where
             Some(next_key) => self
                 .subtries
                 .entry(next_key)
-                .or_insert_with(Self::default)
+                .or_default()
                 .insert(keys, value),
             None => self.leaf.replace(value),
         }
@@ -61,7 +61,7 @@ where
         match keys.next() {
             Some(next_key) => self
                 .subtries
-                .get(&next_key)
+                .get(next_key)
                 .and_then(|trie| trie.subtrie(keys)),
             None => Some(self),
         }
@@ -110,20 +110,6 @@ where
         })
         .filter_map(|(depth, trie)| trie.leaf.as_ref().map(|value| (depth, value)))
     }
-
-    pub fn iter_values(&self) -> impl core::iter::Iterator<Item = &V> {
-        let mut trie_buffer = vec![self];
-
-        core::iter::from_fn(move || {
-            if let Some(trie) = trie_buffer.pop() {
-                trie_buffer.extend(trie.subtries.values());
-                Some(trie)
-            } else {
-                None
-            }
-        })
-        .filter_map(|trie| trie.leaf.as_ref())
-    }
 }
 
 impl<K, V, I> FromIterator<(I, V)> for Trie<K, V>
@@ -0,0 +1,226 @@
+use crate::base::events::Event;
+use crate::base::owned::{value_data, write_header, MAX_HEADER_LEN};
+use crate::base::parser::StateError;
+use crate::base::stream::serialize::SizeWidth;
+
+use bytes::BytesMut;
+use tokio_util::codec::Encoder;
+
+use std::collections::BTreeMap;
+
+#[derive(thiserror::Error, Debug)]
+pub enum CodecError {
+    #[error("IOError: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("ParseError: {0}")]
+    Parse(#[from] StateError),
+    #[error("the stream ended within an element")]
+    Incomplete,
+    #[error("cannot serialize element {0:#X}")]
+    Serialize(u32),
+    #[error("unbalanced event for element {0:#X}")]
+    Unbalanced(u32),
+}
+
+/**
+Serializes `Start`, `Value` & `End` events (e.g., as yielded by the generated `Events` iterator or
+`Decoder`) into an EBML stream.
+
+The data size of a master element is only known at its `End` event, so an element is written out
+once its top-level ancestor has ended; the `offset` & `size` of `Start` events are disregarded.
+Numbers use their shortest encodings, & data sizes those of the set `SizeWidth`s (by default, the
+shortest ones).
+
+**/
+#[derive(Debug, Default)]
+pub struct EventEncoder {
+    open: Vec<(u32, Vec<u8>)>, // the IDs of the started elements, & their data so far
+    size_width: SizeWidth,
+    element_size_widths: BTreeMap<u32, SizeWidth>,
+}
+
+impl EventEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the data size width of all encoded elements (by default, `SizeWidth::Minimal`).
+    pub fn size_width(mut self, width: SizeWidth) -> Self {
+        self.size_width = width;
+        self
+    }
+
+    /// Sets the data size width of encoded elements with the given ID, overriding `size_width`.
+    pub fn element_size_width(mut self, id: u32, width: SizeWidth) -> Self {
+        self.element_size_widths.insert(id, width);
+        self
+    }
+
+    fn is_open(&self, id: u32) -> bool {
+        matches!(self.open.last(), Some((open_id, _)) if *open_id == id)
+    }
+}
+
+impl Encoder<Event> for EventEncoder {
+    type Error = CodecError;
+
+    fn encode(&mut self, event: Event, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        match event {
+            Event::Start { id, .. } => self.open.push((id, Vec::new())),
+            Event::Value { id, .. } | Event::End { id } if !self.is_open(id) => {
+                return Err(CodecError::Unbalanced(id));
+            }
+            Event::Value { id, value } => {
+                let data = value_data(&value).ok_or(CodecError::Serialize(id))?;
+                let (_, open_data) = self.open.last_mut().expect("the element is open");
+                open_data.extend_from_slice(&data);
+            }
+            Event::End { id } => {
+                let mut header = [0u8; MAX_HEADER_LEN];
+                let (_, data) = self.open.last().expect("the element is open");
+                let width = *self
+                    .element_size_widths
+                    .get(&id)
+                    .unwrap_or(&self.size_width);
+                let header_len = write_header(&mut header, id, data.len() as u64, width)
+                    .ok_or(CodecError::Serialize(id))?;
+
+                let (_, data) = self.open.pop().expect("the element is open");
+                match self.open.last_mut() {
+                    Some((_, parent_data)) => {
+                        parent_data.extend_from_slice(&header[..header_len]);
+                        parent_data.extend_from_slice(&data);
+                    }
+                    None => {
+                        dst.extend_from_slice(&header[..header_len]);
+                        dst.extend_from_slice(&data);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::date::Date;
+    use crate::base::events::Value;
+    use rstest::*;
+
+    fn start(id: u32) -> Event {
+        Event::Start {
+            id,
+            path: String::new(),
+            offset: 0,
+            size: Some(0),
+        }
+    }
+
+    fn encode(events: Vec<Event>) -> Result<Vec<u8>, CodecError> {
+        let mut encoder = EventEncoder::new();
+        let mut dst = BytesMut::new();
+        for event in events {
+            encoder.encode(event, &mut dst)?;
+        }
+        Ok(dst.to_vec())
+    }
+
+    #[rstest]
+    #[case(Value::UInt(0), &[0x42, 0x86, 0x81, 0x00])]
+    #[case(Value::UInt(256), &[0x42, 0x86, 0x82, 0x01, 0x00])]
+    #[case(Value::Int(-1), &[0x42, 0x86, 0x81, 0xFF])]
+    #[case(Value::Float(0.5), &[0x42, 0x86, 0x88, 0x3F, 0xE0, 0, 0, 0, 0, 0, 0])]
+    #[case(Value::Date(Date::from_nanos(1)), &[0x42, 0x86, 0x88, 0, 0, 0, 0, 0, 0, 0, 1])]
+    #[case(Value::String("a".to_string()), &[0x42, 0x86, 0x81, 0x61])]
+    #[case(Value::Utf8("é".to_string()), &[0x42, 0x86, 0x82, 0xC3, 0xA9])]
+    #[case(Value::Binary(vec![]), &[0x42, 0x86, 0x80])]
+    fn encode_value(#[case] value: Value, #[case] expt_stream: &[u8]) {
+        let events = vec![
+            start(0x4286),
+            Event::Value { id: 0x4286, value },
+            Event::End { id: 0x4286 },
+        ];
+        assert_eq!(encode(events).unwrap(), expt_stream);
+    }
+
+    #[test]
+    fn encode_master() {
+        let mut encoder = EventEncoder::new();
+        let mut dst = BytesMut::new();
+        let events = vec![
+            start(0x1A45DFA3),
+            start(0xEC),
+            Event::Value {
+                id: 0xEC,
+                value: Value::Binary(vec![0xFF, 0xFF]),
+            },
+            Event::End { id: 0xEC },
+            start(0xEC),
+            Event::End { id: 0xEC },
+        ];
+        for event in events {
+            encoder.encode(event, &mut dst).unwrap();
+        }
+        // nothing is written until the top-level element ends
+        assert!(dst.is_empty());
+
+        encoder
+            .encode(Event::End { id: 0x1A45DFA3 }, &mut dst)
+            .unwrap();
+        assert_eq!(
+            dst.to_vec(),
+            [0x1A, 0x45, 0xDF, 0xA3, 0x86, 0xEC, 0x82, 0xFF, 0xFF, 0xEC, 0x80]
+        );
+    }
+
+    #[test]
+    fn encode_size_widths() {
+        let mut encoder = EventEncoder::new()
+            .size_width(SizeWidth::Fixed(8))
+            .element_size_width(0xEC, SizeWidth::Reserved(1));
+        let mut dst = BytesMut::new();
+        let events = vec![start(0x1A45DFA3), start(0xEC), Event::End { id: 0xEC }];
+        for event in events.into_iter().chain([Event::End { id: 0x1A45DFA3 }]) {
+            encoder.encode(event, &mut dst).unwrap();
+        }
+        assert_eq!(
+            dst.to_vec(),
+            [
+                0x1A, 0x45, 0xDF, 0xA3, // EBML element ID
+                0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, // EBML length = 3 (8 bytes)
+                0xEC, // Void element ID
+                0x40, 0x00, // Void length = 0 (2 bytes)
+            ]
+        );
+
+        // a fixed width that's too narrow for the data size
+        let mut encoder = EventEncoder::new().size_width(SizeWidth::Fixed(1));
+        let value = Value::Binary(vec![0; 127]);
+        let events = vec![start(0xEC), Event::Value { id: 0xEC, value }];
+        for event in events {
+            encoder.encode(event, &mut dst).unwrap();
+        }
+        assert!(matches!(
+            encoder.encode(Event::End { id: 0xEC }, &mut dst),
+            Err(CodecError::Serialize(0xEC))
+        ));
+    }
+
+    #[rstest]
+    #[case(vec![Event::End { id: 0xEC }], 0xEC)]
+    #[case(vec![start(0x1A45DFA3), Event::End { id: 0xEC }], 0xEC)]
+    #[case(vec![Event::Value { id: 0xEC, value: Value::UInt(0) }], 0xEC)]
+    fn encode_unbalanced(#[case] events: Vec<Event>, #[case] expt_id: u32) {
+        assert!(matches!(encode(events), Err(CodecError::Unbalanced(id)) if id == expt_id));
+    }
+
+    #[rstest]
+    #[case(0xFF, Value::UInt(0))] // invalid (reserved) element ID
+    #[case(0x4286, Value::String("é".to_string()))] // non-ASCII string
+    fn encode_unserializable(#[case] id: u32, #[case] value: Value) {
+        let events = vec![start(id), Event::Value { id, value }, Event::End { id }];
+        assert!(matches!(encode(events), Err(CodecError::Serialize(x)) if x == id));
+    }
+}
@@ -0,0 +1,184 @@
+use crate::base::date::Date;
+use crate::base::stream::parse;
+
+use std::io::{BufRead, Read};
+
+// A decoded (owned) leaf element value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    UInt(u64),
+    Int(i64),
+    Float(f64),
+    Date(Date),
+    String(String),
+    Utf8(String),
+    Binary(Vec<u8>),
+}
+
+// An element event, as yielded in document order by the generated `Events` iterator
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Start {
+        id: u32,
+        path: String, // the names of the element & its ancestors, e.g. `\Files\File\FileName`
+        offset: u64,  // stream position of the first byte of the element ID
+        size: Option<u64>, // the length of the element data (`None` -> unknown-sized)
+    },
+    Value {
+        id: u32,
+        value: Value,
+    },
+    End {
+        id: u32,
+    },
+}
+
+/**
+A buffered reader that keeps count of the bytes consumed from it, i.e. of its stream position.
+
+It also keeps the position before the last bytes read or consumed: a reader step consumes the
+header of the element it lands on last, after discarding any skipped elements before it, so this
+is then the offset of that header.
+
+**/
+#[derive(Debug)]
+pub struct OffsetReader<R> {
+    reader: R,
+    offset: u64,
+    last_offset: u64,
+}
+
+impl<R: BufRead> OffsetReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            offset: 0,
+            last_offset: 0,
+        }
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn last_offset(&self) -> u64 {
+        self.last_offset
+    }
+
+    fn advance(&mut self, len: usize) {
+        if len > 0 {
+            self.last_offset = self.offset;
+            self.offset += len as u64;
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Read for OffsetReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.advance(len);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead> BufRead for OffsetReader<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt);
+        self.advance(amt);
+    }
+}
+
+/**
+Scans the element headers at the start of a buffered stream, as positioned at the given offset.
+
+The readers skip over elements with (schema-specific) skipped IDs in a single step, so the element
+that a step lands on may start after any number of them. This returns the `(header, data)` offsets
+of the first header, and of each header following a skipped element; scanning stops at the first
+non-skipped element, or at the first header or skipped element not completely in the buffer.
+
+**/
+pub fn scan_headers(mut stream: &[u8], mut offset: u64, skipped_ids: &[u32]) -> Vec<(u64, u64)> {
+    let mut headers = Vec::new();
+    loop {
+        let header = parse::element_id(stream).and_then(|(next_stream, id)| {
+            parse::element_len(next_stream).map(|(next_stream, len)| (next_stream, id, len))
+        });
+        let (next_stream, id, len) = match header {
+            Ok(header) => header,
+            Err(_) => break,
+        };
+        let header_len = (stream.len() - next_stream.len()) as u64;
+        headers.push((offset, offset + header_len));
+
+        match len {
+            Some(len) if skipped_ids.contains(&id) && len <= next_stream.len() as u64 => {
+                offset += header_len + len;
+                stream = &next_stream[len as usize..];
+            }
+            _ => break,
+        }
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    const STREAM: [u8; 13] = [
+        0xEC, // Void element ID
+        0x82, // Void length = 2
+        0x00, 0x00, // Void data
+        0x42, 0x86, // EBMLVersion element ID
+        0x40, 0x01, // EBMLVersion length = 1
+        0x01, // EBMLVersion data
+        0xEC, // Void element ID
+        0x84, // Void length = 4
+        0x00, 0x00, // incomplete Void data
+    ];
+
+    #[rstest]
+    #[case(&[], vec![(100, 102)])]
+    #[case(&[0xEC], vec![(100, 102), (104, 108)])]
+    #[case(&[0xEC, 0x4286], vec![(100, 102), (104, 108), (109, 111)])]
+    fn scan_headers_skipped(#[case] skipped_ids: &[u32], #[case] expt_headers: Vec<(u64, u64)>) {
+        assert_eq!(scan_headers(&STREAM, 100, skipped_ids), expt_headers);
+    }
+
+    #[rstest]
+    #[case(&[], vec![])]
+    #[case(&[0x42], vec![])] // incomplete element ID
+    #[case(&[0x42, 0x86, 0x40], vec![])] // incomplete element length
+    fn scan_headers_incomplete(#[case] stream: &[u8], #[case] expt_headers: Vec<(u64, u64)>) {
+        assert_eq!(scan_headers(stream, 0, &[0x4286]), expt_headers);
+    }
+
+    #[test]
+    fn offset_reader() {
+        let mut reader = OffsetReader::new(&STREAM[..]);
+        assert_eq!(reader.fill_buf().unwrap().len(), STREAM.len());
+        reader.consume(4);
+        assert_eq!(reader.offset(), 4);
+        assert_eq!(reader.last_offset(), 0);
+
+        let mut buffer = [0u8; 2];
+        reader.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x42, 0x86]);
+        assert_eq!(reader.offset(), 6);
+        assert_eq!(reader.last_offset(), 4);
+
+        // consuming nothing keeps the last position
+        reader.consume(0);
+        assert_eq!(reader.last_offset(), 4);
+        assert_eq!(reader.into_inner(), &STREAM[6..]);
+    }
+}
@@ -0,0 +1,10 @@
+#[cfg(feature = "codec")]
+pub mod codec;
+pub mod date;
+pub mod edit;
+pub mod element_defs;
+pub mod events;
+pub mod owned;
+pub mod parser;
+pub mod raw;
+pub mod stream;
@@ -0,0 +1,265 @@
+use crate::base::date::Date;
+use crate::base::element_defs::{ElementDef, Range};
+use crate::base::events::{Event, Value};
+use crate::base::stream::serialize::{self, SizeWidth};
+
+use std::num::NonZeroU32;
+
+// the longest possible element header: a 4-byte element ID + an 8-byte data size
+pub(crate) const MAX_HEADER_LEN: usize = 12;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum OwnedError {
+    #[error("unexpected event {0:?}")]
+    Unexpected(Event),
+    #[error("the events ended within an element")]
+    Incomplete,
+    #[error("element {0:#X} occurs more than once")]
+    Duplicate(u32),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WriteError {
+    #[error("IOError: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("cannot serialize element {0:#X}")]
+    Serialize(u32),
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error("element {0:#X} occurs {1} times")]
+    Occurrences(u32, usize),
+    #[error("element {0:#X} is out of its range")]
+    OutOfRange(u32),
+}
+
+// The Rust types of leaf element values in the generated owned types
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Result<Self, Value>;
+}
+
+impl FromValue for u64 {
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::UInt(x) => Ok(x),
+            value => Err(value),
+        }
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Int(x) => Ok(x),
+            value => Err(value),
+        }
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Float(x) => Ok(x),
+            value => Err(value),
+        }
+    }
+}
+
+impl FromValue for Date {
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Date(x) => Ok(x),
+            value => Err(value),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::String(x) | Value::Utf8(x) => Ok(x),
+            value => Err(value),
+        }
+    }
+}
+
+impl FromValue for Vec<u8> {
+    fn from_value(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Binary(x) => Ok(x),
+            value => Err(value),
+        }
+    }
+}
+
+// Reads a leaf element's value from the events following its `Start` event, up to & including its
+// `End` event
+pub fn read_value<T: FromValue, I: Iterator<Item = Event>>(
+    events: &mut I,
+    id: u32,
+) -> Result<T, OwnedError> {
+    let value = match events.next().ok_or(OwnedError::Incomplete)? {
+        Event::Value {
+            id: value_id,
+            value,
+        } if value_id == id => T::from_value(value)
+            .map_err(|value| OwnedError::Unexpected(Event::Value { id, value }))?,
+        event => return Err(OwnedError::Unexpected(event)),
+    };
+    match events.next().ok_or(OwnedError::Incomplete)? {
+        Event::End { id: end_id } if end_id == id => Ok(value),
+        event => Err(OwnedError::Unexpected(event)),
+    }
+}
+
+// Sets an element that may occur at most once
+pub fn set_once<T>(field: &mut Option<T>, value: T, id: u32) -> Result<(), OwnedError> {
+    match field {
+        Some(_) => Err(OwnedError::Duplicate(id)),
+        None => {
+            *field = Some(value);
+            Ok(())
+        }
+    }
+}
+
+// Writes an element header with a data size of the given width, returning its length
+pub fn write_header(header: &mut [u8], id: u32, len: u64, width: SizeWidth) -> Option<usize> {
+    let (_, header_len) =
+        serialize::element_header(header, NonZeroU32::new(id)?, Some(len), width).ok()?;
+    Some(header_len)
+}
+
+/**
+Appends an element with the given data to `buffer`. A `None` data, i.e. a value without an
+encoding (see `uint_data`, etc.), fails with `WriteError::Serialize`, as does an invalid ID.
+
+**/
+pub fn write_element(buffer: &mut Vec<u8>, id: u32, data: Option<&[u8]>) -> Result<(), WriteError> {
+    let data = data.ok_or(WriteError::Serialize(id))?;
+    let mut header = [0u8; MAX_HEADER_LEN];
+    let header_len = write_header(&mut header, id, data.len() as u64, SizeWidth::Minimal)
+        .ok_or(WriteError::Serialize(id))?;
+    buffer.extend_from_slice(&header[..header_len]);
+    buffer.extend_from_slice(data);
+    Ok(())
+}
+
+// The shortest encodings of element values
+pub fn uint_data(x: u64) -> Option<Vec<u8>> {
+    let length = (1..=8).find(|&length| serialize::uint(&mut [0u8; 8], x, length).is_ok())?;
+    let mut data = vec![0u8; length];
+    serialize::uint(&mut data, x, length).ok()?;
+    Some(data)
+}
+
+pub fn int_data(x: i64) -> Option<Vec<u8>> {
+    let length = (1..=8).find(|&length| serialize::int(&mut [0u8; 8], x, length).is_ok())?;
+    let mut data = vec![0u8; length];
+    serialize::int(&mut data, x, length).ok()?;
+    Some(data)
+}
+
+pub fn float_data(x: f64) -> Option<Vec<u8>> {
+    let mut data = vec![0u8; 8];
+    serialize::float64(&mut data, x, 8).ok()?;
+    Some(data)
+}
+
+pub fn date_data(x: Date) -> Option<Vec<u8>> {
+    let mut data = vec![0u8; 8];
+    serialize::date(&mut data, x.nanos(), 8).ok()?;
+    Some(data)
+}
+
+// String elements are restricted to printable ASCII
+pub fn ascii_data(x: &str) -> Option<&[u8]> {
+    x.is_ascii().then_some(x.as_bytes())
+}
+
+pub fn value_data(value: &Value) -> Option<Vec<u8>> {
+    match value {
+        Value::UInt(x) => uint_data(*x),
+        Value::Int(x) => int_data(*x),
+        Value::Float(x) => float_data(*x),
+        Value::Date(x) => date_data(*x),
+        Value::String(x) => ascii_data(x).map(<[u8]>::to_vec),
+        Value::Utf8(x) => Some(x.as_bytes().to_vec()),
+        Value::Binary(x) => Some(x.clone()),
+    }
+}
+
+/**
+Checks the number of occurrences of an element within its parent against its `MIN_OCCURS` &
+`MAX_OCCURS`. An absent element with a default value counts as present.
+
+**/
+pub fn check_occurrences<E: ElementDef>(
+    count: usize,
+    has_default: bool,
+) -> Result<(), ValidationError> {
+    let min_count = if has_default { count.max(1) } else { count };
+    if min_count < E::MIN_OCCURS || E::MAX_OCCURS.is_some_and(|max| count > max) {
+        return Err(ValidationError::Occurrences(E::ID, count));
+    }
+    Ok(())
+}
+
+pub fn check_range<T: PartialOrd>(
+    range: &Range<T>,
+    value: &T,
+    id: u32,
+) -> Result<(), ValidationError> {
+    match range.contains(value) {
+        true => Ok(()),
+        false => Err(ValidationError::OutOfRange(id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_value_events() {
+        let mut events = vec![
+            Event::Value {
+                id: 0x4286,
+                value: Value::UInt(1),
+            },
+            Event::End { id: 0x4286 },
+        ]
+        .into_iter();
+        assert_eq!(read_value::<u64, _>(&mut events, 0x4286), Ok(1));
+
+        let mut events = vec![Event::Value {
+            id: 0x4286,
+            value: Value::Int(1),
+        }]
+        .into_iter();
+        assert!(matches!(
+            read_value::<u64, _>(&mut events, 0x4286),
+            Err(OwnedError::Unexpected(Event::Value { id: 0x4286, .. }))
+        ));
+        assert_eq!(
+            read_value::<u64, _>(&mut events, 0x4286),
+            Err(OwnedError::Incomplete)
+        );
+    }
+
+    #[test]
+    fn write_element_data() {
+        let mut buffer = Vec::new();
+        write_element(&mut buffer, 0x4286, uint_data(256).as_deref()).unwrap();
+        assert_eq!(buffer, [0x42, 0x86, 0x82, 0x01, 0x00]);
+        assert!(matches!(
+            write_element(&mut buffer, 0x4286, ascii_data("é")),
+            Err(WriteError::Serialize(0x4286))
+        ));
+        assert!(matches!(
+            write_element(&mut buffer, 0xFF, Some(&[])),
+            Err(WriteError::Serialize(0xFF))
+        ));
+    }
+}
@@ -0,0 +1,960 @@
+use crate::base::element_defs::{
+    BinaryElementDef, DateElementDef, ElementDef, FloatElementDef, IntElementDef, StringElementDef,
+    UIntElementDef, Utf8ElementDef,
+};
+use crate::base::date::Date;
+use crate::base::stream::{parse, stream_diff};
+
+use core::convert::{From, TryInto};
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+// marks an object with a single respective element type
+pub trait BoundTo
+where
+    Self::Element: ElementDef,
+{
+    type Element;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementState<E: ElementDef, S> {
+    pub bytes_left: usize,
+    pub unknown_size: bool, // the bytes left are then those of the innermost sized ancestor
+    pub parent_state: S,
+    pub _phantom: PhantomData<E>,
+}
+
+impl<E: ElementDef, S> ElementState<E, S> {
+    // The length of the element data (`None` -> unknown-sized), as of entering the element
+    pub fn size(&self) -> Option<usize> {
+        match self.unknown_size {
+            true => None,
+            false => Some(self.bytes_left),
+        }
+    }
+}
+
+impl<E: ElementDef, S: BytesLeft> ElementState<E, S> {
+    // Moves on to the parent state, which an unknown-sized element leaves with its own bytes left
+    pub fn into_parent_state(self) -> S {
+        let mut parent_state = self.parent_state;
+        if self.unknown_size {
+            parent_state.set_bytes_left(self.bytes_left);
+        }
+        parent_state
+    }
+}
+
+// Sets the bytes left in a state, e.g. in the parent of an unknown-sized element on its end
+pub trait BytesLeft {
+    fn set_bytes_left(&mut self, bytes_left: usize);
+}
+
+impl<E: ElementDef, S> BytesLeft for ElementState<E, S> {
+    fn set_bytes_left(&mut self, bytes_left: usize) {
+        self.bytes_left = bytes_left;
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum StateError {
+    #[error("invalid subelement id {1} (parent id = {:?})", *.0)]
+    InvalidChildId(Option<u32>, u32),
+    #[error("excluded subelement id {1} (parent id = {:?})", *.0)]
+    ExcludedChildId(Option<u32>, u32),
+    #[error("unknown-sized element id {0}, whose size must be known")]
+    UnknownSizeNotAllowed(u32),
+    #[error("unknown-sized excluded element id {0}, which can't be skipped over")]
+    UnknownSizeSkipped(u32),
+    #[error("error parsing token")]
+    BadToken,
+}
+
+impl From<()> for StateError {
+    fn from(_value: ()) -> Self {
+        Self::BadToken
+    }
+}
+
+pub trait SkipStateNavigation {
+    type PrevStates;
+
+    fn skip(self, stream: &[u8]) -> nom::IResult<&[u8], Self::PrevStates, StateError>;
+
+    // Like `skip`, but leaves the element's remaining data to the caller: returns the parent state,
+    // along with the data to skip over
+    fn skip_unread(self) -> (Self::PrevStates, Unread);
+}
+
+pub trait NextStateNavigation: Sized {
+    type NextStates;
+
+    fn next(self, stream: &[u8]) -> nom::IResult<&[u8], Self::NextStates, StateError>;
+
+    // If `stream` starts with a subelement that is skipped over by `next`, returns the stream after
+    // its header, along with the state after the subelement & the length of its data, which is left
+    // to the caller to skip over (e.g., if it's larger than the buffered stream)
+    fn next_skipped<'a>(
+        &self,
+        stream: &'a [u8],
+    ) -> nom::IResult<&'a [u8], Option<(Self, usize)>, StateError>;
+
+    // Ends an unknown-sized element at the end of the stream, moving on to its parent; any other
+    // state is handed back, as the stream can't end there
+    fn end_of_stream(self) -> Result<Self::NextStates, Self>;
+}
+
+// The remaining data of an element, as left to the caller to skip over
+#[derive(Debug, Clone, PartialEq)]
+pub enum Unread {
+    Sized(usize), // the number of bytes left
+    UnknownSized(UnknownSizeSkip),
+}
+
+/**
+Skips over the data of an unknown-sized element child by child, given the (generated) lookup of
+the children of unknown-sized elements. The element ends at the first element that isn't its child,
+or at the end of its innermost sized ancestor; its children may themselves be unknown-sized.
+
+**/
+#[derive(Debug, Clone)]
+pub struct UnknownSizeSkip {
+    open: Vec<&'static [u32]>, // the child IDs of the skipped unknown-sized elements, innermost last
+    bytes_left: usize,         // the bytes left in their innermost sized ancestor
+    pending: usize,            // the data left of the child being skipped over
+    children: fn(u32) -> Result<&'static [u32], StateError>,
+}
+
+// the lookup of children is that of the generated module, whatever the skip
+impl PartialEq for UnknownSizeSkip {
+    fn eq(&self, other: &Self) -> bool {
+        self.open == other.open
+            && self.bytes_left == other.bytes_left
+            && self.pending == other.pending
+    }
+}
+
+impl UnknownSizeSkip {
+    pub fn new(
+        id: u32,
+        bytes_left: usize,
+        children: fn(u32) -> Result<&'static [u32], StateError>,
+    ) -> Self {
+        Self {
+            open: vec![children(id).unwrap_or_default()],
+            bytes_left,
+            pending: 0,
+            children,
+        }
+    }
+
+    pub fn bytes_left(&self) -> usize {
+        self.bytes_left
+    }
+
+    // Steps over the header of the next child in `stream`, returning the length of its data, which
+    // is left to the caller to skip over (`0` for an unknown-sized child, whose children follow), or
+    // `None` once the element has ended
+    pub fn next<'a>(
+        &mut self,
+        stream: &'a [u8],
+    ) -> nom::IResult<&'a [u8], Option<usize>, StateError> {
+        while let Some(child_ids) = self.open.last() {
+            if self.bytes_left == 0 {
+                self.open.clear();
+                break;
+            }
+
+            let (next_stream, id) = parse::element_id(stream).map_err(nom::Err::convert)?;
+            let (next_stream, len) = parse::element_len(next_stream).map_err(nom::Err::convert)?;
+            if !child_ids.contains(&id) {
+                self.open.pop();
+                continue;
+            }
+
+            self.bytes_left -= stream_diff(stream, next_stream);
+            match len {
+                Some(len) => {
+                    let len: usize = len
+                        .try_into()
+                        .expect("overflow in storing element bytelength");
+                    self.bytes_left -= len;
+                    return Ok((next_stream, Some(len)));
+                }
+                None => {
+                    self.open
+                        .push((self.children)(id).map_err(nom::Err::Failure)?);
+                    return Ok((next_stream, Some(0)));
+                }
+            }
+        }
+
+        Ok((stream, None))
+    }
+
+    // Skips over the element's remaining data, which must be in `stream` in full
+    pub fn skip<'a>(&mut self, mut stream: &'a [u8]) -> nom::IResult<&'a [u8], (), StateError> {
+        loop {
+            match self.next(stream)? {
+                (next_stream, Some(len)) => {
+                    stream = nom::bytes::streaming::take::<_, _, ()>(len)(next_stream)
+                        .map_err(nom::Err::convert)?
+                        .0;
+                }
+                (next_stream, None) => return Ok((next_stream, ())),
+            }
+        }
+    }
+}
+
+pub struct UIntParserMarker;
+pub struct IntParserMarker;
+pub struct FloatParserMarker;
+pub struct DateParserMarker;
+pub struct StringParserMarker;
+pub struct Utf8ParserMarker;
+pub struct BinaryParserMarker;
+
+pub trait ParserMarker {}
+impl ParserMarker for UIntParserMarker {}
+impl ParserMarker for IntParserMarker {}
+impl ParserMarker for FloatParserMarker {}
+impl ParserMarker for DateParserMarker {}
+impl ParserMarker for StringParserMarker {}
+impl ParserMarker for Utf8ParserMarker {}
+impl ParserMarker for BinaryParserMarker {}
+
+pub trait StateDataParser<'a, M: ParserMarker, T: 'a> {
+    type NextState;
+    fn read(self, stream: &'a [u8]) -> nom::IResult<&'a [u8], (Self::NextState, T), StateError>;
+}
+
+impl<E: UIntElementDef, S> StateDataParser<'_, UIntParserMarker, u64> for ElementState<E, S> {
+    type NextState = S;
+
+    fn read(self, stream: &[u8]) -> nom::IResult<&[u8], (S, u64), StateError> {
+        let (stream, data) = parse::uint(stream, self.bytes_left).map_err(nom::Err::convert)?;
+
+        Ok((stream, (self.parent_state, data)))
+    }
+}
+
+impl<E: IntElementDef, S> StateDataParser<'_, IntParserMarker, i64> for ElementState<E, S> {
+    type NextState = S;
+
+    fn read(self, stream: &[u8]) -> nom::IResult<&[u8], (S, i64), StateError> {
+        let (stream, data) = parse::int(stream, self.bytes_left).map_err(nom::Err::convert)?;
+
+        Ok((stream, (self.parent_state, data)))
+    }
+}
+
+impl<E: FloatElementDef, S> StateDataParser<'_, FloatParserMarker, f64> for ElementState<E, S> {
+    type NextState = S;
+
+    fn read(self, stream: &[u8]) -> nom::IResult<&[u8], (S, f64), StateError> {
+        // floats are stored in 0 (-> 0.0), 4 or 8 octets
+        let (stream, data) = match self.bytes_left {
+            0 => Ok((stream, 0.0)),
+            4 => parse::float32(stream, 4).map(|(stream, data)| (stream, data.into())),
+            length => parse::float64(stream, length),
+        }
+        .map_err(nom::Err::convert)?;
+
+        Ok((stream, (self.parent_state, data)))
+    }
+}
+
+impl<E: DateElementDef, S> StateDataParser<'_, DateParserMarker, Date> for ElementState<E, S> {
+    type NextState = S;
+
+    fn read(self, stream: &[u8]) -> nom::IResult<&[u8], (S, Date), StateError> {
+        let (stream, data) = parse::date(stream, self.bytes_left).map_err(nom::Err::convert)?;
+
+        Ok((stream, (self.parent_state, Date::from_nanos(data))))
+    }
+}
+
+impl<'a, E: StringElementDef, S> StateDataParser<'a, StringParserMarker, &'a str>
+    for ElementState<E, S>
+{
+    type NextState = S;
+
+    fn read(self, stream: &'a [u8]) -> nom::IResult<&'a [u8], (S, &'a str), StateError> {
+        let (stream, data) =
+            parse::ascii_str(stream, self.bytes_left).map_err(nom::Err::convert)?;
+
+        Ok((stream, (self.parent_state, data)))
+    }
+}
+
+impl<'a, E: Utf8ElementDef, S> StateDataParser<'a, Utf8ParserMarker, &'a str>
+    for ElementState<E, S>
+{
+    type NextState = S;
+
+    fn read(self, stream: &[u8]) -> nom::IResult<&[u8], (S, &str), StateError> {
+        let (stream, data) =
+            parse::unicode_str(stream, self.bytes_left).map_err(nom::Err::convert)?;
+
+        Ok((stream, (self.parent_state, data)))
+    }
+}
+
+impl<'a, E: BinaryElementDef, S> StateDataParser<'a, BinaryParserMarker, &'a [u8]>
+    for ElementState<E, S>
+{
+    type NextState = S;
+
+    fn read(self, stream: &[u8]) -> nom::IResult<&[u8], (S, &[u8]), StateError> {
+        let (stream, data) = parse::binary(stream, self.bytes_left).map_err(nom::Err::convert)?;
+
+        Ok((stream, (self.parent_state, data)))
+    }
+}
+
+impl<E: ElementDef, S> BoundTo for ElementState<E, S> {
+    type Element = E;
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ElementReader<R, S> {
+    pub reader: R,
+    pub state: S,
+    pub unread: Option<Unread>, // the data left to skip over by a step interrupted by an error
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ReaderError {
+    #[error("IOError: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("ParseError: {0}")]
+    Parse(#[from] nom::Err<StateError>),
+}
+
+// Returned on entering a master element while walking a document (see the generated `Visitor`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    Continue, // descend into the element's children
+    Skip,     // discard the element's children undecoded, & move on without its exit callback
+}
+
+pub trait SkipReaderNavigation<R>: Sized {
+    type PrevReaders;
+
+    fn skip(self) -> Result<Self::PrevReaders, ReaderError>;
+
+    // Like `skip`, but hands back the reader on an error (e.g., a read that would block, or data that
+    // hasn't arrived yet), along with the data skipped so far, so that a retry resumes the step
+    fn try_skip(self) -> Result<Self::PrevReaders, (Self, ReaderError)>;
+}
+
+pub trait NextReaderNavigation<R>: Sized {
+    type NextReaders;
+
+    fn next(self) -> Result<Self::NextReaders, ReaderError>;
+
+    // Like `next`, but hands back the reader on an error (e.g., a read that would block, or data that
+    // hasn't arrived yet), along with the data skipped so far, so that a retry resumes the step
+    fn try_next(self) -> Result<Self::NextReaders, (Self, ReaderError)>;
+}
+
+impl<R: std::io::BufRead, S: SkipStateNavigation + BytesLeft + Clone> SkipReaderNavigation<R>
+    for ElementReader<R, S>
+where
+    S::PrevStates: IntoReader<R> + BytesLeft,
+{
+    type PrevReaders = <S::PrevStates as IntoReader<R>>::Reader;
+
+    fn skip(self) -> Result<Self::PrevReaders, ReaderError> {
+        self.try_skip().map_err(|(_, err)| err)
+    }
+
+    fn try_skip(mut self) -> Result<Self::PrevReaders, (Self, ReaderError)> {
+        let mut unread = match self.unread.take() {
+            // the rest of an unknown-sized element, as left by an interrupted skip
+            Some(unread @ Unread::UnknownSized(_)) => unread,
+            // the rest of a skipped subelement, as left by an interrupted `try_next`
+            Some(mut unread) => match discard_unread(&mut self.reader, &mut unread) {
+                Ok(()) => self.state.clone().skip_unread().1,
+                Err(err) => {
+                    self.unread = Some(unread);
+                    return Err((self, err));
+                }
+            },
+            None => self.state.clone().skip_unread().1,
+        };
+        if let Err(err) = discard_unread(&mut self.reader, &mut unread) {
+            // keeps count of the data left, for a retry to skip over
+            match unread {
+                Unread::Sized(len) => self.state.set_bytes_left(len),
+                unread => self.unread = Some(unread),
+            }
+            return Err((self, err));
+        }
+
+        let (mut next_state, _) = self.state.skip_unread();
+        if let Unread::UnknownSized(skip) = unread {
+            next_state.set_bytes_left(skip.bytes_left());
+        }
+        Ok(next_state.into_reader(self.reader))
+    }
+}
+
+impl<R: std::io::BufRead, S: NextStateNavigation + Clone> ElementReader<R, S> {
+    // Steps the state to the next element, skipping over subelements without buffering their data,
+    // which may not fit in the buffer; on an error, the reader is left to resume the step
+    fn next_state(&mut self) -> Result<S::NextStates, ReaderError> {
+        loop {
+            if let Some(unread) = self.unread.as_mut() {
+                discard_unread(&mut self.reader, unread)?;
+                self.unread = None;
+            }
+
+            let stream = self.reader.fill_buf()?;
+            match self.state.next_skipped(stream) {
+                Ok((next_stream, Some((next_state, len)))) => {
+                    let stream_dist = stream.len() - next_stream.len();
+                    self.reader.consume(stream_dist);
+                    self.state = next_state;
+                    self.unread = Some(Unread::Sized(len));
+                }
+                // errors are left to `next` to report
+                _ => break,
+            }
+        }
+
+        // an unknown-sized element may end along with the stream
+        if self.reader.fill_buf()?.is_empty() {
+            if let Ok(next_state) = self.state.clone().end_of_stream() {
+                return Ok(next_state);
+            }
+        }
+
+        let stream = self.reader.fill_buf()?;
+        let (next_stream, next_state) = self.state.clone().next(stream)?;
+        let stream_dist = stream.len() - next_stream.len();
+        self.reader.consume(stream_dist);
+
+        Ok(next_state)
+    }
+}
+
+impl<R: std::io::BufRead, S: NextStateNavigation + Clone> NextReaderNavigation<R>
+    for ElementReader<R, S>
+where
+    S::NextStates: IntoReader<R>,
+{
+    type NextReaders = <S::NextStates as IntoReader<R>>::Reader;
+
+    fn next(self) -> Result<Self::NextReaders, ReaderError> {
+        self.try_next().map_err(|(_, err)| err)
+    }
+
+    fn try_next(mut self) -> Result<Self::NextReaders, (Self, ReaderError)> {
+        match self.next_state() {
+            Ok(next_state) => Ok(next_state.into_reader(self.reader)),
+            Err(err) => Err((self, err)),
+        }
+    }
+}
+
+// Skips over the remaining data of an element without buffering it, counting down the data left
+// so that, after an error, a retry resumes where it left off
+fn discard_unread<R: std::io::BufRead>(
+    reader: &mut R,
+    unread: &mut Unread,
+) -> Result<(), ReaderError> {
+    match unread {
+        Unread::Sized(len) => discard(reader, len),
+        Unread::UnknownSized(skip) => loop {
+            discard(reader, &mut skip.pending)?;
+
+            let stream = reader.fill_buf()?;
+            // an unknown-sized element may end along with the stream
+            if stream.is_empty() {
+                return Ok(());
+            }
+
+            let (next_stream, len) = skip.next(stream)?;
+            let stream_dist = stream.len() - next_stream.len();
+            reader.consume(stream_dist);
+            match len {
+                Some(len) => skip.pending = len,
+                None => return Ok(()),
+            }
+        },
+    }
+}
+
+// Skips over the next `len` bytes of a reader without buffering them, counting down `len` (reads
+// past the buffer, like `std::io::copy`, so that the buffer is refilled after the skipped data)
+fn discard<R: std::io::BufRead>(reader: &mut R, len: &mut usize) -> Result<(), ReaderError> {
+    let mut buf = [0; 8192];
+    while *len > 0 {
+        let buf_len = buf.len().min(*len);
+        match reader.read(&mut buf[..buf_len]) {
+            // the rest of the data may be yet to arrive
+            Ok(0) => return Err(nom::Err::Incomplete(nom::Needed::new(*len)).into()),
+            Ok(read_len) => *len -= read_len,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(())
+}
+
+pub trait ReaderDataParser<'a, R, M: ParserMarker, T: 'a> {
+    fn read(&'a mut self) -> Result<T, ReaderError>;
+}
+
+impl<R: std::io::BufRead, E: UIntElementDef + Clone, S: Clone>
+    ReaderDataParser<'_, R, UIntParserMarker, u64> for ElementReader<R, ElementState<E, S>>
+{
+    fn read(&mut self) -> Result<u64, ReaderError> {
+        let stream = self.reader.fill_buf()?;
+        let (_, (_, data)) = self.state.clone().read(stream)?;
+
+        Ok(data)
+    }
+}
+
+impl<R: std::io::BufRead, E: IntElementDef + Clone, S: Clone>
+    ReaderDataParser<'_, R, IntParserMarker, i64> for ElementReader<R, ElementState<E, S>>
+{
+    fn read(&mut self) -> Result<i64, ReaderError> {
+        let stream = self.reader.fill_buf()?;
+        let (_, (_, data)) = self.state.clone().read(stream)?;
+
+        Ok(data)
+    }
+}
+
+impl<R: std::io::BufRead, E: FloatElementDef + Clone, S: Clone>
+    ReaderDataParser<'_, R, FloatParserMarker, f64> for ElementReader<R, ElementState<E, S>>
+{
+    fn read(&mut self) -> Result<f64, ReaderError> {
+        let stream = self.reader.fill_buf()?;
+        let (_, (_, data)) = self.state.clone().read(stream)?;
+
+        Ok(data)
+    }
+}
+
+impl<R: std::io::BufRead, E: DateElementDef + Clone, S: Clone>
+    ReaderDataParser<'_, R, DateParserMarker, Date> for ElementReader<R, ElementState<E, S>>
+{
+    fn read(&mut self) -> Result<Date, ReaderError> {
+        let stream = self.reader.fill_buf()?;
+        let (_, (_, data)) = self.state.clone().read(stream)?;
+
+        Ok(data)
+    }
+}
+
+impl<'a, R: std::io::BufRead, E: StringElementDef + Clone, S: Clone>
+    ReaderDataParser<'a, R, StringParserMarker, &'a str> for ElementReader<R, ElementState<E, S>>
+{
+    fn read(&mut self) -> Result<&str, ReaderError> {
+        let stream = self.reader.fill_buf()?;
+        let (_, (_, data)) = self.state.clone().read(stream)?;
+
+        Ok(data)
+    }
+}
+
+impl<'a, R: std::io::BufRead, E: Utf8ElementDef + Clone, S: Clone>
+    ReaderDataParser<'a, R, Utf8ParserMarker, &'a str> for ElementReader<R, ElementState<E, S>>
+{
+    fn read(&mut self) -> Result<&str, ReaderError> {
+        let stream = self.reader.fill_buf()?;
+        let (_, (_, data)) = self.state.clone().read(stream)?;
+
+        Ok(data)
+    }
+}
+
+impl<'a, R: std::io::BufRead, E: BinaryElementDef + Clone, S: Clone>
+    ReaderDataParser<'a, R, BinaryParserMarker, &'a [u8]> for ElementReader<R, ElementState<E, S>>
+{
+    fn read(&mut self) -> Result<&[u8], ReaderError> {
+        let stream = self.reader.fill_buf()?;
+        let (_, (_, data)) = self.state.clone().read(stream)?;
+
+        Ok(data)
+    }
+}
+
+impl<E: ElementDef, S, R> From<ElementReader<R, ElementState<E, S>>> for ElementState<E, S> {
+    fn from(reader: ElementReader<R, ElementState<E, S>>) -> Self {
+        reader.state
+    }
+}
+
+// the (boxed) state of a recursive element, as held by its own parent states
+impl<E: ElementDef, S, R> From<ElementReader<R, ElementState<E, S>>> for Box<ElementState<E, S>> {
+    fn from(reader: ElementReader<R, ElementState<E, S>>) -> Self {
+        Box::new(reader.state)
+    }
+}
+
+impl<R, S: BoundTo> BoundTo for ElementReader<R, S> {
+    type Element = S::Element;
+}
+
+pub trait IntoReader<R: std::io::BufRead> {
+    type Reader;
+
+    fn into_reader(self, reader: R) -> Self::Reader;
+}
+
+impl<E: ElementDef, S, R: std::io::BufRead> IntoReader<R> for ElementState<E, S> {
+    type Reader = ElementReader<R, ElementState<E, S>>;
+
+    fn into_reader(self, reader: R) -> Self::Reader {
+        Self::Reader {
+            reader,
+            state: self,
+            unread: None,
+        }
+    }
+}
+
+// the (boxed) state of a recursive element, as held by its own parent states
+impl<T: IntoReader<R>, R: std::io::BufRead> IntoReader<R> for Box<T> {
+    type Reader = T::Reader;
+
+    fn into_reader(self, reader: R) -> Self::Reader {
+        (*self).into_reader(reader)
+    }
+}
+
+#[macro_export]
+macro_rules! impl_skip_state_navigation {
+    ( $State:ident, $PrevStates:ident ) => {
+        impl SkipStateNavigation for $State {
+            type PrevStates = $PrevStates;
+
+            fn skip(self, stream: &[u8]) -> nom::IResult<&[u8], Self::PrevStates, StateError> {
+                match self.skip_unread() {
+                    (parent_state, Unread::Sized(len)) => {
+                        let (stream, _) = nom::bytes::streaming::take::<_, _, ()>(len)(stream)
+                            .map_err(nom::Err::convert)?;
+                        Ok((stream, parent_state))
+                    }
+                    (mut parent_state, Unread::UnknownSized(mut skip)) => {
+                        let (stream, _) = skip.skip(stream)?;
+                        parent_state.set_bytes_left(skip.bytes_left());
+                        Ok((stream, parent_state))
+                    }
+                }
+            }
+
+            fn skip_unread(self) -> (Self::PrevStates, Unread) {
+                match self.unknown_size {
+                    true => (
+                        self.parent_state,
+                        Unread::UnknownSized(UnknownSizeSkip::new(
+                            <<Self as BoundTo>::Element as ElementDef>::ID,
+                            self.bytes_left,
+                            unknown_size_children,
+                        )),
+                    ),
+                    false => (self.parent_state, Unread::Sized(self.bytes_left)),
+                }
+            }
+        }
+    };
+}
+
+// Sets the bytes left in the variants of a `PrevStates` enum
+#[macro_export]
+macro_rules! impl_bytes_left {
+    ( $States:ident, [ $( $ElementName:ident ),* ] ) => {
+        impl BytesLeft for $States {
+            fn set_bytes_left(&mut self, bytes_left: usize) {
+                match self {
+                    $(
+                        Self::$ElementName(state) => state.set_bytes_left(bytes_left),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+// The optional fourth argument lists the IDs of known subelements that are skipped over, rather than
+// rejected as invalid (e.g., subelements that were excluded from code generation); the optional
+// fifth argument lists those that are rejected as `StateError::ExcludedChildId` instead.
+//
+// An unknown-sized element ends at the first element that isn't its child (as looked up with the
+// generated `unknown_size_children`), or at the end of its innermost sized ancestor.
+#[macro_export]
+macro_rules! impl_next_state_navigation {
+    ( $State:ident, $NextStates:ident, [] $(, [ $( $SkippedId:expr ),* ] )? ) => {
+        impl NextStateNavigation for $State {
+            type NextStates = $NextStates;
+
+            fn next(self, stream: &[u8]) -> nom::IResult<&[u8], Self::NextStates, StateError> {
+                self.skip(stream)
+            }
+
+            fn next_skipped<'a>(
+                &self,
+                stream: &'a [u8],
+            ) -> nom::IResult<&'a [u8], Option<(Self, usize)>, StateError> {
+                // all of the remaining data is skipped over...
+                if !self.unknown_size {
+                    return Ok((stream, match self.bytes_left {
+                        0 => None,
+                        len => Some((Self { bytes_left: 0, ..self.clone() }, len)),
+                    }));
+                }
+
+                // ...child by child for an unknown-sized element (leaving its end, & any unknown-sized
+                // children, to `next`)
+                if self.bytes_left == 0 {
+                    return Ok((stream, None));
+                }
+
+                let (next_stream, id) = parse::element_id(stream).map_err(nom::Err::convert)?;
+                let (next_stream, len) = parse::element_len(next_stream).map_err(nom::Err::convert)?;
+                let child_ids = unknown_size_children(<<Self as BoundTo>::Element as ElementDef>::ID)
+                    .unwrap_or_default();
+
+                match len {
+                    Some(len) if child_ids.contains(&id) => {
+                        let len: usize = len.try_into().expect("overflow in storing element bytelength");
+                        let mut state = self.clone();
+                        state.bytes_left -= len + stream_diff(stream, next_stream);
+                        Ok((next_stream, Some((state, len))))
+                    }
+                    _ => Ok((stream, None)),
+                }
+            }
+
+            fn end_of_stream(self) -> Result<Self::NextStates, Self> {
+                match self.unknown_size {
+                    true => Ok(self.into_parent_state()),
+                    false => Err(self),
+                }
+            }
+        }
+    };
+
+    ( $State:ident, $NextStates:ident, [ $( ($ElementName:ident, $ElementState:ident) ),+ ] ) => {
+        impl_next_state_navigation!($State, $NextStates, [ $( ($ElementName, $ElementState) ),+ ], []);
+    };
+
+    ( _DocumentState, _DocumentNextStates, [ $( ($ElementName:ident, $ElementState:ident) ),* ], [ $( $SkippedId:expr ),* ] $(, [ $( $RejectedId:expr ),* ] )? ) => {
+        // No parent or bytes_left -> custom impl
+        impl NextStateNavigation for _DocumentState {
+            type NextStates = _DocumentNextStates;
+
+            fn next(self, mut stream: &[u8]) -> nom::IResult<&[u8], Self::NextStates, StateError> {
+                loop {
+                    let (next_stream, id) = parse::element_id(stream).map_err(nom::Err::convert)?;
+                    let (next_stream, len) = parse::element_len(next_stream).map_err(nom::Err::convert)?;
+                    let len: Option<usize> = len
+                        .map(|len| len.try_into().expect("overflow in storing element bytelength"));
+
+                    match id {
+                        $(
+                            <<$ElementState as BoundTo>::Element as ElementDef>::ID => {
+                                // an unknown-sized top-level element is only bounded by the stream
+                                let mut state = $ElementState::new(len.unwrap_or(usize::MAX), self.into());
+                                if len.is_none() {
+                                    unknown_size_children(id).map_err(nom::Err::Failure)?;
+                                    state.unknown_size = true;
+                                }
+                                return Ok((next_stream, Self::NextStates::$ElementName(state)));
+                            }
+                        )*
+                        id if [$( $SkippedId ),*].contains(&id) => {
+                            let len = len.ok_or(nom::Err::Failure(StateError::UnknownSizeSkipped(id)))?;
+                            stream = nom::bytes::streaming::take::<_, _, ()>(len)(next_stream)
+                                .map_err(nom::Err::convert)?
+                                .0;
+                        }
+                        id if [$( $( $RejectedId ),* )?].contains(&id) => {
+                            return Err(nom::Err::Failure(StateError::ExcludedChildId(None, id)))
+                        }
+                        id => return Err(nom::Err::Failure(StateError::InvalidChildId(None, id))),
+                    }
+                }
+            }
+
+            fn next_skipped<'a>(
+                &self,
+                stream: &'a [u8],
+            ) -> nom::IResult<&'a [u8], Option<(Self, usize)>, StateError> {
+                let (next_stream, id) = parse::element_id(stream).map_err(nom::Err::convert)?;
+                let (next_stream, len) = parse::element_len(next_stream).map_err(nom::Err::convert)?;
+
+                match len {
+                    Some(len) if [$( $SkippedId ),*].contains(&id) => {
+                        let len: usize = len.try_into().expect("overflow in storing element bytelength");
+                        Ok((next_stream, Some((self.clone(), len))))
+                    }
+                    _ => Ok((stream, None)),
+                }
+            }
+
+            fn end_of_stream(self) -> Result<Self::NextStates, Self> {
+                Err(self)
+            }
+        }
+    };
+
+    ( $State:ident, $NextStates:ident, [ $( ($ElementName:ident, $ElementState:ident) ),* ], [ $( $SkippedId:expr ),* ] $(, [ $( $RejectedId:expr ),* ] )? ) => {
+        impl NextStateNavigation for $State {
+            type NextStates = $NextStates;
+
+            fn next(mut self, mut stream: &[u8]) -> nom::IResult<&[u8], Self::NextStates, StateError> {
+                loop {
+                    if self.bytes_left == 0 {
+                        return Ok((stream, Self::NextStates::Parent(self.into_parent_state())));
+                    }
+
+                    let (next_stream, id) = parse::element_id(stream).map_err(nom::Err::convert)?;
+                    let (next_stream, len) = parse::element_len(next_stream).map_err(nom::Err::convert)?;
+                    if self.unknown_size
+                        && !unknown_size_children(<<Self as BoundTo>::Element as ElementDef>::ID)
+                            .unwrap_or_default()
+                            .contains(&id)
+                    {
+                        return Ok((stream, Self::NextStates::Parent(self.into_parent_state())));
+                    }
+                    let len: Option<usize> = len
+                        .map(|len| len.try_into().expect("overflow in storing element bytelength"));
+
+                    self.bytes_left -= len.unwrap_or(0) + stream_diff(stream, next_stream);
+
+                    match id {
+                        $(
+                            <<$ElementState as BoundTo>::Element as ElementDef>::ID => {
+                                // an unknown-sized child is bounded by its parent
+                                let mut state = $ElementState::new(len.unwrap_or(self.bytes_left), self.into());
+                                if len.is_none() {
+                                    unknown_size_children(id).map_err(nom::Err::Failure)?;
+                                    state.unknown_size = true;
+                                }
+                                return Ok((next_stream, Self::NextStates::$ElementName(state)));
+                            }
+                        )*
+                        id if [$( $SkippedId ),*].contains(&id) => {
+                            let len = len.ok_or(nom::Err::Failure(StateError::UnknownSizeSkipped(id)))?;
+                            stream = nom::bytes::streaming::take::<_, _, ()>(len)(next_stream)
+                                .map_err(nom::Err::convert)?
+                                .0;
+                        }
+                        id if [$( $( $RejectedId ),* )?].contains(&id) => {
+                            return Err(nom::Err::Failure(StateError::ExcludedChildId(
+                                Some(<<Self as BoundTo>::Element as ElementDef>::ID),
+                                id,
+                            )))
+                        }
+                        id => {
+                            return Err(nom::Err::Failure(StateError::InvalidChildId(
+                                Some(<<Self as BoundTo>::Element as ElementDef>::ID),
+                                id,
+                            )))
+                        }
+                    }
+                }
+            }
+
+            fn next_skipped<'a>(
+                &self,
+                stream: &'a [u8],
+            ) -> nom::IResult<&'a [u8], Option<(Self, usize)>, StateError> {
+                if self.bytes_left == 0 {
+                    return Ok((stream, None));
+                }
+
+                let (next_stream, id) = parse::element_id(stream).map_err(nom::Err::convert)?;
+                let (next_stream, len) = parse::element_len(next_stream).map_err(nom::Err::convert)?;
+
+                match len {
+                    Some(len) if [$( $SkippedId ),*].contains(&id) => {
+                        let len: usize = len.try_into().expect("overflow in storing element bytelength");
+                        let mut state = self.clone();
+                        state.bytes_left -= len + stream_diff(stream, next_stream);
+                        Ok((next_stream, Some((state, len))))
+                    }
+                    _ => Ok((stream, None)),
+                }
+            }
+
+            fn end_of_stream(self) -> Result<Self::NextStates, Self> {
+                match self.unknown_size {
+                    true => Ok(Self::NextStates::Parent(self.into_parent_state())),
+                    false => Err(self),
+                }
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_into_reader {
+    ( $States:ident, $Readers:ident, [ $( $ElementName:ident ),* ] ) => {
+        impl<R: BufRead> IntoReader<R> for $States {
+            type Reader = $Readers<R>;
+            fn into_reader(self, reader: R) -> Self::Reader {
+                match self {
+                    $(
+                        Self::$ElementName(state) => Self::Reader::$ElementName(state.into_reader(reader)),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_from_readers_for_states {
+    ( $Readers:ident, $States:ident, [ $( $ElementName:ident ),* ] ) => {
+        impl<R> From<$Readers<R>> for $States {
+            fn from(enumed_reader: $Readers<R>) -> Self {
+                match enumed_reader {
+                    $(
+                        $Readers::$ElementName(reader) => Self::$ElementName(reader.into()),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_from_substates_for_states {
+    ( $SubStates:ident, $States:ident, [ $( $ElementName:ident ),* ] ) => {
+        impl From<$SubStates> for $States {
+            fn from(enumed_states: $SubStates) -> Self {
+                match enumed_states {
+                    $(
+                        $SubStates::$ElementName(state) => state.into(),
+                    )*
+                }
+            }
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! impl_from_subreaders_for_readers {
+    ( $SubReaders:ident, $Readers:ident, [ $( $ElementName:ident ),* ] ) => {
+        impl<R: BufRead> From<$SubReaders<R>> for $Readers<R> {
+            fn from(enumed_states: $SubReaders<R>) -> Self {
+                match enumed_states {
+                    $(
+                        $SubReaders::$ElementName(state) => state.into(),
+                    )*
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use iron_ebmlem::parser_gen::Builder;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+use std::path::PathBuf;
+
+/**
+Generates a parser from an EBML schema file at compile time, expanding to the same `base` and
+schema-specific modules that `Parsers::write_module` writes:
+
+```ignore
+mod ebml {
+    iron_ebmlem_macros::ebml_schema!("schemas/matroska.xml");
+}
+```
+
+The path is relative to the invoking crate's manifest directory. Like for `write_module`, the
+invoking crate needs `nom`, `enum_dispatch` and `thiserror` as dependencies.
+**/
+#[proc_macro]
+pub fn ebml_schema(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as LitStr);
+    expand(&path)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+fn expand(path: &LitStr) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let error = |message: String| syn::Error::new(path.span(), message);
+
+    let schema_path = std::env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .map_err(|e| error(format!("no env variable 'CARGO_MANIFEST_DIR': {}", e)))?
+        .join(path.value());
+    let parsers = Builder::from_path(&schema_path)
+        .map_err(|e| error(format!("couldn't parse schema file: {}", e)))?
+        .generate()
+        .map_err(|e| error(format!("invalid schema: {}", e)))?;
+
+    let mut source = Vec::new();
+    parsers
+        .write_module_source(&mut source)
+        .map_err(|e| error(format!("couldn't write parser module: {}", e)))?;
+    let module = String::from_utf8_lossy(&source)
+        .parse::<proc_macro2::TokenStream>()
+        .map_err(|e| error(format!("couldn't tokenize parser module: {}", e)))?;
+
+    // including the schema makes the compiler rebuild the invoking crate when it changes
+    let schema_path = schema_path.to_string_lossy();
+    Ok(quote! {
+        const _: &[u8] = include_bytes!(#schema_path);
+        #module
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("tests/fixtures/missing.xml", "couldn't parse schema file: IO error: ")]
+    #[case("Cargo.toml", "couldn't parse schema file: ")]
+    #[case(
+        "tests/fixtures/invalid_schema.xml",
+        "invalid schema: invalid default value for date element Created: "
+    )]
+    fn expand_err(#[case] path: &str, #[case] expt_error_prefix: &str) {
+        let path = LitStr::new(path, proc_macro2::Span::call_site());
+        let error = expand(&path).err().unwrap().to_string();
+
+        assert!(error.starts_with(expt_error_prefix), "{}", error);
+    }
+}
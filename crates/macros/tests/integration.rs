@@ -0,0 +1,45 @@
+#[allow(dead_code)]
+mod ebml {
+    iron_ebmlem_macros::ebml_schema!("../example_generated/eg_schema.xml");
+}
+
+use ebml::base::parser::{NextReaderNavigation, ReaderDataParser, ReaderError};
+use ebml::core::parser;
+
+const BYTE_STREAM: [u8; 16] = [
+    0x19, 0x46, 0x69, 0x6C, // Files element ID
+    0x8B, // Files length = 11
+    0x61, 0x46, // File element ID
+    0x88, // File length = 8
+    0x61, 0x4E, // FileName element ID
+    0x85, // FileName length = 5
+    0x61, 0x2e, 0x74, 0x78, 0x74, // FileName data = "a.txt"
+];
+
+#[test]
+fn read_file_names() {
+    let mut reader: parser::Readers<_> = parser::_DocumentReader::new(&BYTE_STREAM[..]).into();
+    let mut result = Vec::new();
+
+    loop {
+        reader = match reader {
+            parser::Readers::_Document(r) => match r.next() {
+                Ok(r_next) => r_next.into(),
+                Err(ReaderError::Parse(nom::Err::Incomplete(_))) => break,
+                Err(_) => panic!(), // in an actual function, this should return the error
+            },
+            parser::Readers::FileName(mut r) => {
+                result.push(r.read().unwrap().to_string());
+                r.next().unwrap().into()
+            }
+            parser::Readers::Void(r) => r.next().unwrap().into(),
+            parser::Readers::Files(r) => r.next().unwrap().into(),
+            parser::Readers::File(r) => r.next().unwrap().into(),
+            parser::Readers::MimeType(r) => r.next().unwrap().into(),
+            parser::Readers::ModificationTimestamp(r) => r.next().unwrap().into(),
+            parser::Readers::Data(r) => r.next().unwrap().into(),
+        };
+    }
+
+    assert_eq!(result, vec!["a.txt"]);
+}